@@ -1,10 +1,20 @@
 //! Futures support for reactive scopes.
 
+use std::cell::{Cell, RefCell};
+use std::mem;
 use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
 
+use futures::channel::oneshot;
+use futures::executor::{LocalPool, LocalSpawner};
 use futures::future::abortable;
-use futures::Future;
-use sycamore_reactive::Scope;
+use futures::task::LocalSpawnExt;
+use futures::{Future, Stream};
+use sycamore_reactive::{
+    capture_dependencies, create_scope, BoundedScopeRef, ReadSignal, Scope, ScopeDisposer,
+};
 
 /// If running on `wasm32` target, does nothing. Otherwise creates a new `tokio::task::LocalSet`
 /// scope.
@@ -23,13 +33,339 @@ pub async fn provide_executor_scope<U>(f: impl Future<Output = U>) -> U {
     }
 }
 
+/// An executor that can run `!Send` futures on the current thread.
+///
+/// The executor used by [`ScopeSpawnLocal::spawn_local`] can be configured with
+/// [`ScopeSpawnLocal::provide_executor`]. If no executor is provided, [`DefaultExecutor`] is used.
+pub trait SpawnLocal {
+    /// Spawns a `!Send` future on the executor.
+    fn spawn_local(&self, f: Pin<Box<dyn Future<Output = ()>>>);
+}
+
+/// The executor that is used when no other executor is provided.
+///
+/// On `wasm32` targets, futures are spawned using `wasm_bindgen_futures::spawn_local`. On other
+/// targets, futures are spawned on the current `tokio::task::LocalSet` (see
+/// [`provide_executor_scope`]).
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DefaultExecutor;
+
+impl SpawnLocal for DefaultExecutor {
+    fn spawn_local(&self, f: Pin<Box<dyn Future<Output = ()>>>) {
+        #[cfg(not(target_arch = "wasm32"))]
+        tokio::task::spawn_local(f);
+        #[cfg(target_arch = "wasm32")]
+        wasm_bindgen_futures::spawn_local(f);
+    }
+}
+
+/// An executor that only makes progress when it is driven manually. This is mostly useful for
+/// testing async code deterministically.
+///
+/// Cloning a [`ManualExecutor`] returns a handle to the same executor.
+///
+/// # Example
+/// ```
+/// # use sycamore_futures::*;
+/// # use sycamore_reactive::*;
+/// # use std::cell::Cell;
+/// # use std::rc::Rc;
+/// let executor = ManualExecutor::new();
+/// let done = Rc::new(Cell::new(false));
+/// create_scope_immediate(|ctx| {
+///     ctx.provide_executor(executor.clone());
+///     ctx.spawn_local({
+///         let done = Rc::clone(&done);
+///         async move { done.set(true) }
+///     });
+///     assert!(!done.get());
+///     executor.run_until_stalled();
+///     assert!(done.get());
+/// });
+/// ```
+#[derive(Clone)]
+pub struct ManualExecutor {
+    pool: Rc<RefCell<LocalPool>>,
+    spawner: LocalSpawner,
+}
+
+impl ManualExecutor {
+    /// Create a new [`ManualExecutor`] without any tasks.
+    pub fn new() -> Self {
+        let pool = LocalPool::new();
+        let spawner = pool.spawner();
+        Self {
+            pool: Rc::new(RefCell::new(pool)),
+            spawner,
+        }
+    }
+
+    /// Runs all the spawned tasks until none of them can make progress anymore.
+    ///
+    /// # Panics
+    /// Panics if called from inside a task that is run by the same executor.
+    pub fn run_until_stalled(&self) {
+        self.pool.borrow_mut().run_until_stalled();
+    }
+
+    /// Polls the spawned tasks until one of them completes. Returns `false` if no task could be
+    /// completed without waiting.
+    ///
+    /// # Panics
+    /// Panics if called from inside a task that is run by the same executor.
+    pub fn try_run_one(&self) -> bool {
+        self.pool.borrow_mut().try_run_one()
+    }
+}
+
+impl Default for ManualExecutor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SpawnLocal for ManualExecutor {
+    fn spawn_local(&self, f: Pin<Box<dyn Future<Output = ()>>>) {
+        self.spawner
+            .spawn_local(f)
+            .expect("executor should not be shut down");
+    }
+}
+
+/// A source of time for futures that need to wait, e.g. to retry a failed request after a delay.
+///
+/// The timer used by [`ScopeSpawnLocal::sleep`] can be configured with
+/// [`ScopeSpawnLocal::provide_timer`]. If no timer is provided, [`DefaultTimer`] is used.
+pub trait Timer {
+    /// Returns a future that completes once `duration` has elapsed.
+    fn sleep(&self, duration: Duration) -> Pin<Box<dyn Future<Output = ()>>>;
+}
+
+/// The timer that is used when no other timer is provided.
+///
+/// On `wasm32` targets, this uses `setTimeout`. On other targets, this uses `tokio::time::sleep`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DefaultTimer;
+
+impl Timer for DefaultTimer {
+    fn sleep(&self, duration: Duration) -> Pin<Box<dyn Future<Output = ()>>> {
+        #[cfg(not(target_arch = "wasm32"))]
+        return Box::pin(tokio::time::sleep(duration));
+        #[cfg(target_arch = "wasm32")]
+        return Box::pin(gloo_timers::future::sleep(duration));
+    }
+}
+
+/// A timer whose time only advances when it is advanced manually. This is mostly useful for
+/// testing code that waits deterministically, together with a [`ManualExecutor`].
+///
+/// Cloning a [`ManualTimer`] returns a handle to the same timer.
+///
+/// # Example
+/// ```
+/// # use std::time::Duration;
+/// # use sycamore_futures::*;
+/// # use sycamore_reactive::*;
+/// # use std::cell::Cell;
+/// # use std::rc::Rc;
+/// let executor = ManualExecutor::new();
+/// let timer = ManualTimer::new();
+/// let done = Rc::new(Cell::new(false));
+/// create_scope_immediate(|ctx| {
+///     ctx.provide_executor(executor.clone());
+///     ctx.provide_timer(timer.clone());
+///     ctx.spawn_local({
+///         let done = Rc::clone(&done);
+///         async move {
+///             ctx.sleep(Duration::from_secs(1)).await;
+///             done.set(true);
+///         }
+///     });
+///     executor.run_until_stalled();
+///     assert_eq!(timer.requested(), [Duration::from_secs(1)]);
+///
+///     timer.advance(Duration::from_secs(1));
+///     executor.run_until_stalled();
+///     assert!(done.get());
+/// });
+/// ```
+#[derive(Clone, Default)]
+pub struct ManualTimer(Rc<RefCell<ManualTimerInner>>);
+
+#[derive(Default)]
+struct ManualTimerInner {
+    /// The time elapsed since the timer was created.
+    now: Duration,
+    /// The pending sleeps, with the time at which they complete.
+    pending: Vec<(Duration, oneshot::Sender<()>)>,
+    /// The durations of all the sleeps, in the order in which they were requested.
+    requested: Vec<Duration>,
+}
+
+impl ManualTimer {
+    /// Create a new [`ManualTimer`] without any pending sleeps.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Advances the time by `duration`, completing the sleeps that have elapsed. The futures that
+    /// were waiting on them still need to be polled by the executor.
+    pub fn advance(&self, duration: Duration) {
+        let mut inner = self.0.borrow_mut();
+        inner.now += duration;
+        let now = inner.now;
+        let (elapsed, pending) = mem::take(&mut inner.pending)
+            .into_iter()
+            .partition(|(deadline, _)| *deadline <= now);
+        inner.pending = pending;
+        drop(inner);
+        for (_, sender) in elapsed {
+            let _ = sender.send(());
+        }
+    }
+
+    /// Returns the durations of all the sleeps that were requested from this timer, in order.
+    pub fn requested(&self) -> Vec<Duration> {
+        self.0.borrow().requested.clone()
+    }
+}
+
+impl Timer for ManualTimer {
+    fn sleep(&self, duration: Duration) -> Pin<Box<dyn Future<Output = ()>>> {
+        let (sender, receiver) = oneshot::channel();
+        let mut inner = self.0.borrow_mut();
+        let deadline = inner.now + duration;
+        inner.pending.push((deadline, sender));
+        inner.requested.push(duration);
+        Box::pin(async move {
+            let _ = receiver.await;
+        })
+    }
+}
+
+/// Context type used for storing the executor provided with
+/// [`ScopeSpawnLocal::provide_executor`].
+struct ExecutorContext(Rc<dyn SpawnLocal>);
+
+/// Context type used for storing the timer provided with [`ScopeSpawnLocal::provide_timer`].
+struct TimerContext(Rc<dyn Timer>);
+
 /// Extension trait for Sycamore, providing the [`spawn_local`](ScopeSpawnLocal::spawn_local)
 /// method.
 pub trait ScopeSpawnLocal<'a> {
     /// Spawns a `!Send` future on the current scope. If the scope is destroyed before the future is
     /// completed, it is aborted immediately. This ensures that it is impossible to access any
     /// values referencing the scope after they are destroyed.
+    ///
+    /// The future is spawned on the executor provided with
+    /// [`provide_executor`](ScopeSpawnLocal::provide_executor) in this scope or in a parent scope.
+    /// If there is no such executor, [`DefaultExecutor`] is used.
     fn spawn_local(&'a self, f: impl Future<Output = ()> + 'a);
+
+    /// Provides the executor that is used by [`spawn_local`](ScopeSpawnLocal::spawn_local) in this
+    /// scope and in all child scopes.
+    ///
+    /// # Panics
+    /// This method panics if an executor has already been provided in this scope.
+    fn provide_executor(&'a self, executor: impl SpawnLocal + 'static);
+
+    /// Returns a future that completes once `duration` has elapsed, according to the timer
+    /// provided with [`provide_timer`](ScopeSpawnLocal::provide_timer) in this scope or in a
+    /// parent scope. If there is no such timer, [`DefaultTimer`] is used.
+    fn sleep(&'a self, duration: Duration) -> Pin<Box<dyn Future<Output = ()>>>;
+
+    /// Provides the timer that is used by [`sleep`](ScopeSpawnLocal::sleep) in this scope and in
+    /// all child scopes.
+    ///
+    /// # Panics
+    /// This method panics if a timer has already been provided in this scope.
+    fn provide_timer(&'a self, timer: impl Timer + 'static);
+
+    /// Creates a signal that starts at `0` and is incremented every time `interval` elapses,
+    /// according to the timer used by [`sleep`](ScopeSpawnLocal::sleep). This is useful for clocks
+    /// and animations.
+    ///
+    /// Each interval starts once the previous tick has been processed, so the ticks can drift if
+    /// the executor is busy. The signal stops being incremented when the scope is disposed.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::time::Duration;
+    /// # use sycamore_futures::*;
+    /// # use sycamore_reactive::*;
+    /// # create_scope_immediate(|ctx| {
+    /// # ctx.provide_executor(ManualExecutor::new());
+    /// let seconds = ctx.create_interval_signal(Duration::from_secs(1));
+    /// ctx.create_effect(|| println!("{} seconds elapsed", seconds.get()));
+    /// # });
+    /// ```
+    fn create_interval_signal(&'a self, interval: Duration) -> &'a ReadSignal<u64>;
+
+    /// Creates a memoized derived signal whose value is only recomputed once its dependencies
+    /// stopped changing for `duration`, according to the timer used by
+    /// [`sleep`](ScopeSpawnLocal::sleep). This is useful for expensive computations whose inputs
+    /// change rapidly, e.g. that depend on the position of the mouse.
+    ///
+    /// `f` is called immediately to compute the initial value. Every time one of the signals read
+    /// by `f` is updated, the timer is restarted and the signal keeps its current value. `f` is
+    /// only called again when the timer elapses.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::time::Duration;
+    /// # use sycamore_futures::*;
+    /// # use sycamore_reactive::*;
+    /// # fn expensive(query: &str) -> usize { query.len() }
+    /// # create_scope_immediate(|ctx| {
+    /// # ctx.provide_executor(ManualExecutor::new());
+    /// let query = ctx.create_signal(String::new());
+    /// let results = ctx
+    ///     .create_debounced_memo(Duration::from_millis(300), || expensive(&query.get()));
+    /// # });
+    /// ```
+    fn create_debounced_memo<T: 'a>(
+        &'a self,
+        duration: Duration,
+        f: impl FnMut() -> T + 'a,
+    ) -> &'a ReadSignal<T>;
+
+    /// Creates an effect that runs an async body inside a new child scope every time one of its
+    /// dependencies changes.
+    ///
+    /// `f` is called synchronously inside the effect with the new child scope. Signals that are
+    /// accessed while calling `f` are tracked as dependencies. Signals that are only accessed
+    /// inside the returned future are not tracked. The future is then spawned on the child scope
+    /// using [`spawn_local`](ScopeSpawnLocal::spawn_local).
+    ///
+    /// When the effect re-runs, the previous child scope is disposed before the new one is
+    /// created. This aborts the previous future and cancels the
+    /// [`CancellationToken`](sycamore_reactive::CancellationToken) of the previous scope, so that
+    /// two runs never overlap.
+    ///
+    /// # Example
+    /// ```
+    /// # use sycamore_futures::*;
+    /// # use sycamore_reactive::*;
+    /// # let executor = ManualExecutor::new();
+    /// create_scope_immediate(|ctx| {
+    /// #   ctx.provide_executor(executor.clone());
+    ///     let id = ctx.create_signal(1);
+    ///     ctx.create_effect_scoped_async(|ctx| {
+    ///         // Tracked, because it is accessed before the future is returned.
+    ///         let id = *id.get();
+    ///         let token = ctx.cancellation_token();
+    ///         async move {
+    ///             // Fetch some data for `id`. If `id` changes before the data is fetched, this
+    ///             // future is aborted and `token` is cancelled.
+    ///             # let _ = (id, token);
+    ///         }
+    ///     });
+    /// });
+    /// ```
+    fn create_effect_scoped_async<F, Fut>(&'a self, f: F)
+    where
+        F: for<'child_lifetime> FnMut(BoundedScopeRef<'child_lifetime, 'a>) -> Fut + 'a,
+        Fut: Future<Output = ()> + 'a;
 }
 
 impl<'a> ScopeSpawnLocal<'a> for Scope<'a> {
@@ -42,11 +378,442 @@ impl<'a> ScopeSpawnLocal<'a> for Scope<'a> {
             unsafe { std::mem::transmute(boxed) };
         let (abortable, handle) = abortable(extended);
         self.on_cleanup(move || handle.abort());
-        #[cfg(not(target_arch = "wasm32"))]
-        tokio::task::spawn_local(abortable);
-        #[cfg(target_arch = "wasm32")]
-        wasm_bindgen_futures::spawn_local(async move {
+        let task = Box::pin(async move {
             let _ = abortable.await;
         });
+        match self.try_use_context::<ExecutorContext>() {
+            Some(ExecutorContext(executor)) => executor.spawn_local(task),
+            None => DefaultExecutor.spawn_local(task),
+        }
+    }
+
+    fn provide_executor(&'a self, executor: impl SpawnLocal + 'static) {
+        self.provide_context(ExecutorContext(Rc::new(executor)));
+    }
+
+    fn sleep(&'a self, duration: Duration) -> Pin<Box<dyn Future<Output = ()>>> {
+        match self.try_use_context::<TimerContext>() {
+            Some(TimerContext(timer)) => timer.sleep(duration),
+            None => DefaultTimer.sleep(duration),
+        }
+    }
+
+    fn provide_timer(&'a self, timer: impl Timer + 'static) {
+        self.provide_context(TimerContext(Rc::new(timer)));
+    }
+
+    fn create_interval_signal(&'a self, interval: Duration) -> &'a ReadSignal<u64> {
+        let ticks = self.create_signal(0);
+        self.spawn_local(async move {
+            loop {
+                self.sleep(interval).await;
+                ticks.set(*ticks.get_untracked() + 1);
+            }
+        });
+        ticks
+    }
+
+    fn create_debounced_memo<T: 'a>(
+        &'a self,
+        duration: Duration,
+        mut f: impl FnMut() -> T + 'a,
+    ) -> &'a ReadSignal<T> {
+        let (initial, dependencies) = capture_dependencies(&mut f);
+        let value = self.create_signal(initial);
+        let dependencies = self.create_ref(RefCell::new(dependencies));
+        let f = self.create_ref(RefCell::new(f));
+        let elapsed = self.create_ref(Cell::new(false));
+        let timeout = self.create_signal(());
+        let mut first_run = true;
+
+        // Use a scoped effect so that the timer is restarted when a dependency changes.
+        self.create_effect_scoped(move |ctx| {
+            timeout.track();
+            if elapsed.replace(false) {
+                let (new_value, new_dependencies) = capture_dependencies(&mut *f.borrow_mut());
+                new_dependencies.track();
+                *dependencies.borrow_mut() = new_dependencies;
+                value.set(new_value);
+                return;
+            }
+            dependencies.borrow().track();
+            if mem::take(&mut first_run) {
+                return;
+            }
+            let child = *ctx;
+            child.spawn_local(async move {
+                child.sleep(duration).await;
+                elapsed.set(true);
+                // Re-runs the effect, which disposes this future's scope. Nothing can be accessed
+                // after this.
+                timeout.set(());
+            });
+        });
+
+        value
+    }
+
+    fn create_effect_scoped_async<F, Fut>(&'a self, mut f: F)
+    where
+        F: for<'child_lifetime> FnMut(BoundedScopeRef<'child_lifetime, 'a>) -> Fut + 'a,
+        Fut: Future<Output = ()> + 'a,
+    {
+        self.create_effect_scoped(move |ctx| {
+            let child = *ctx;
+            let fut = f(ctx);
+            child.spawn_local(fut);
+        });
+    }
+}
+
+/// Extension trait for [`ReadSignal`] adding the [`to_stream`](SignalStreamExt::to_stream)
+/// method.
+pub trait SignalStreamExt<'a, T> {
+    /// Returns a [`Stream`] that yields the new value of the signal every time it is updated,
+    /// starting with the first update after the stream is created. This allows composing signals
+    /// with stream combinators.
+    ///
+    /// The stream does not buffer values: if the signal is updated several times before the next
+    /// item is polled, only the latest value is yielded. The stream never ends and it stops
+    /// listening to the signal when it is dropped.
+    ///
+    /// # Example
+    /// ```
+    /// # use futures::StreamExt;
+    /// # use sycamore_futures::*;
+    /// # use sycamore_reactive::*;
+    /// # create_scope_immediate(|ctx| {
+    /// # ctx.provide_executor(ManualExecutor::new());
+    /// let query = ctx.create_signal(String::new());
+    /// ctx.spawn_local(async {
+    ///     let mut queries = query.to_stream();
+    ///     while let Some(query) = queries.next().await {
+    ///         println!("Searching for {query}");
+    ///     }
+    /// });
+    /// # });
+    /// ```
+    fn to_stream(&'a self) -> SignalStream<'a, T>;
+}
+
+impl<'a, T> SignalStreamExt<'a, T> for ReadSignal<T> {
+    fn to_stream(&'a self) -> SignalStream<'a, T> {
+        let state = Rc::new(SignalStreamState::default());
+        let ((), dependencies) = capture_dependencies(|| self.track());
+        let subscription = create_scope({
+            let state = Rc::clone(&state);
+            move |ctx| {
+                let mut first = true;
+                ctx.create_effect(move || {
+                    dependencies.track();
+                    if !mem::take(&mut first) {
+                        state.changed.set(true);
+                        if let Some(waker) = state.waker.take() {
+                            waker.wake();
+                        }
+                    }
+                });
+            }
+        });
+        SignalStream {
+            signal: self,
+            state,
+            subscription: Some(subscription),
+        }
+    }
+}
+
+/// The state shared between a [`SignalStream`] and the effect notifying it.
+#[derive(Default)]
+struct SignalStreamState {
+    /// Whether the signal was updated since the last item was yielded.
+    changed: Cell<bool>,
+    /// The waker of the task waiting for the next item, if any.
+    waker: RefCell<Option<Waker>>,
+}
+
+/// A [`Stream`] of the values of a signal. Created with [`SignalStreamExt::to_stream`].
+pub struct SignalStream<'a, T> {
+    signal: &'a ReadSignal<T>,
+    state: Rc<SignalStreamState>,
+    /// The scope of the effect that listens to the signal. The effect is disposed along with the
+    /// stream.
+    subscription: Option<ScopeDisposer<'static>>,
+}
+
+impl<'a, T> Drop for SignalStream<'a, T> {
+    fn drop(&mut self) {
+        if let Some(subscription) = self.subscription.take() {
+            // SAFETY: The scope is a root scope that only contains the effect listening to the
+            // signal, which does not have access to the stream.
+            unsafe { subscription.dispose() };
+        }
+    }
+}
+
+impl<'a, T: Clone> Stream for SignalStream<'a, T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        if self.state.changed.replace(false) {
+            Poll::Ready(Some(self.signal.get_untracked().as_ref().clone()))
+        } else {
+            *self.state.waker.borrow_mut() = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use futures::channel::oneshot;
+    use sycamore_reactive::*;
+
+    use super::*;
+
+    #[test]
+    fn spawn_local_with_manual_executor() {
+        let executor = ManualExecutor::new();
+        let progress = Rc::new(Cell::new(0));
+        let (sender, receiver) = oneshot::channel::<()>();
+
+        let disposer = create_scope(|ctx| {
+            ctx.provide_executor(executor.clone());
+            let progress = Rc::clone(&progress);
+            ctx.spawn_local(async move {
+                progress.set(1);
+                let _ = receiver.await;
+                progress.set(2);
+            });
+        });
+        assert_eq!(
+            progress.get(),
+            0,
+            "task should not run until the executor is driven"
+        );
+
+        executor.run_until_stalled();
+        assert_eq!(progress.get(), 1);
+
+        unsafe {
+            disposer.dispose();
+        }
+        let _ = sender.send(());
+        executor.run_until_stalled();
+        assert_eq!(
+            progress.get(),
+            1,
+            "task should be aborted when the scope is disposed"
+        );
+    }
+
+    #[test]
+    fn effect_scoped_async_cancels_previous_run() {
+        let executor = ManualExecutor::new();
+        create_scope_immediate(|ctx| {
+            ctx.provide_executor(executor.clone());
+            let trigger = ctx.create_signal(0);
+            let tokens = ctx.create_ref(RefCell::new(Vec::new()));
+            let senders = ctx.create_ref(RefCell::new(Vec::new()));
+            let finished = ctx.create_ref(RefCell::new(Vec::new()));
+
+            ctx.create_effect_scoped_async(move |ctx| {
+                let run = *trigger.get();
+                tokens.borrow_mut().push(ctx.cancellation_token());
+                let (sender, receiver) = oneshot::channel::<()>();
+                senders.borrow_mut().push(sender);
+                async move {
+                    let _ = receiver.await;
+                    finished.borrow_mut().push(run);
+                }
+            });
+            executor.run_until_stalled();
+            assert_eq!(tokens.borrow().len(), 1);
+
+            trigger.set(1);
+            assert_eq!(tokens.borrow().len(), 2);
+            assert!(tokens.borrow()[0].is_cancelled());
+            executor.run_until_stalled();
+
+            trigger.set(2);
+            assert!(tokens.borrow()[1].is_cancelled());
+            assert!(!tokens.borrow()[2].is_cancelled());
+
+            // Only the latest run can still complete.
+            for sender in senders.take() {
+                let _ = sender.send(());
+            }
+            executor.run_until_stalled();
+            assert_eq!(*finished.borrow(), [2]);
+        });
+    }
+
+    #[test]
+    fn manual_timer_completes_elapsed_sleeps() {
+        let executor = ManualExecutor::new();
+        let timer = ManualTimer::new();
+        create_scope_immediate(|ctx| {
+            ctx.provide_executor(executor.clone());
+            ctx.provide_timer(timer.clone());
+            let done = ctx.create_ref(RefCell::new(Vec::new()));
+            for ms in [200, 100] {
+                ctx.spawn_local(async move {
+                    ctx.sleep(Duration::from_millis(ms)).await;
+                    done.borrow_mut().push(ms);
+                });
+            }
+            executor.run_until_stalled();
+            assert_eq!(
+                timer.requested(),
+                [Duration::from_millis(200), Duration::from_millis(100)]
+            );
+
+            timer.advance(Duration::from_millis(150));
+            executor.run_until_stalled();
+            assert_eq!(*done.borrow(), [100]);
+
+            timer.advance(Duration::from_millis(50));
+            executor.run_until_stalled();
+            assert_eq!(*done.borrow(), [100, 200]);
+        });
+    }
+
+    #[test]
+    fn interval_signal_ticks_until_disposed() {
+        let executor = ManualExecutor::new();
+        let timer = ManualTimer::new();
+        let interval = Duration::from_millis(100);
+        create_scope_immediate(|ctx| {
+            ctx.provide_executor(executor.clone());
+            ctx.provide_timer(timer.clone());
+            let seen = ctx.create_ref(RefCell::new(Vec::new()));
+            let disposer = ctx.create_child_scope(|ctx| {
+                let ticks = ctx.create_interval_signal(interval);
+                ctx.create_effect(|| seen.borrow_mut().push(*ticks.get()));
+            });
+            executor.run_until_stalled();
+            assert_eq!(*seen.borrow(), [0]);
+
+            timer.advance(Duration::from_millis(50));
+            executor.run_until_stalled();
+            assert_eq!(*seen.borrow(), [0]);
+
+            for _ in 0..3 {
+                timer.advance(interval);
+                executor.run_until_stalled();
+            }
+            assert_eq!(*seen.borrow(), [0, 1, 2, 3]);
+
+            unsafe { disposer.dispose() };
+            timer.advance(interval);
+            executor.run_until_stalled();
+            assert_eq!(*seen.borrow(), [0, 1, 2, 3]);
+            assert_eq!(timer.requested(), [interval; 4]);
+        });
+    }
+
+    #[test]
+    fn debounced_memo_waits_for_quiescence() {
+        let executor = ManualExecutor::new();
+        let timer = ManualTimer::new();
+        let window = Duration::from_millis(100);
+        create_scope_immediate(|ctx| {
+            ctx.provide_executor(executor.clone());
+            ctx.provide_timer(timer.clone());
+            let position = ctx.create_signal(0);
+            let runs = ctx.create_ref(Cell::new(0));
+            let double = ctx.create_debounced_memo(window, || {
+                runs.set(runs.get() + 1);
+                *position.get() * 2
+            });
+            assert_eq!(*double.get(), 0);
+            assert_eq!(runs.get(), 1);
+
+            for i in 1..=5 {
+                position.set(i);
+                executor.run_until_stalled();
+                timer.advance(Duration::from_millis(50));
+                executor.run_until_stalled();
+            }
+            assert_eq!(runs.get(), 1, "recomputation is debounced");
+            assert_eq!(*double.get(), 0, "the old value is kept meanwhile");
+
+            timer.advance(Duration::from_millis(50));
+            executor.run_until_stalled();
+            assert_eq!(runs.get(), 2);
+            assert_eq!(*double.get(), 10);
+
+            // Still tracks its dependencies after recomputing.
+            position.set(6);
+            executor.run_until_stalled();
+            timer.advance(window);
+            executor.run_until_stalled();
+            assert_eq!(runs.get(), 3);
+            assert_eq!(*double.get(), 12);
+        });
+    }
+
+    #[test]
+    fn signal_to_stream() {
+        use futures::{FutureExt, StreamExt};
+
+        create_scope_immediate(|ctx| {
+            let state = ctx.create_signal(0);
+            let mut stream = state.to_stream();
+            assert_eq!(stream.next().now_or_never(), None, "no update yet");
+
+            state.set(1);
+            assert_eq!(stream.next().now_or_never(), Some(Some(1)));
+
+            // Only the latest value is kept.
+            state.set(2);
+            state.set(3);
+            assert_eq!(stream.next().now_or_never(), Some(Some(3)));
+            assert_eq!(stream.next().now_or_never(), None);
+
+            // Dropping a stream stops listening to the signal.
+            let dependents = state.dependent_count();
+            for _ in 0..5 {
+                drop(state.to_stream());
+            }
+            assert_eq!(state.dependent_count(), dependents);
+            state.set(3);
+            assert_eq!(stream.next().now_or_never(), Some(Some(3)));
+
+            // The task waiting for the next item is woken up.
+            let executor = ManualExecutor::new();
+            let seen = ctx.create_ref(RefCell::new(Vec::new()));
+            ctx.provide_executor(executor.clone());
+            ctx.spawn_local(async move {
+                while let Some(value) = stream.next().await {
+                    seen.borrow_mut().push(value);
+                }
+            });
+            executor.run_until_stalled();
+            assert!(seen.borrow().is_empty());
+
+            state.set(4);
+            executor.run_until_stalled();
+            state.set(5);
+            executor.run_until_stalled();
+            assert_eq!(*seen.borrow(), [4, 5]);
+        });
+    }
+
+    #[test]
+    fn executor_is_inherited_by_child_scopes() {
+        let executor = ManualExecutor::new();
+        let done = Rc::new(Cell::new(false));
+        create_scope_immediate(|ctx| {
+            ctx.provide_executor(executor.clone());
+            let _ = ctx.create_child_scope(|ctx| {
+                let done = Rc::clone(&done);
+                ctx.spawn_local(async move { done.set(true) });
+            });
+            executor.run_until_stalled();
+            assert!(done.get());
+        });
     }
 }
@@ -0,0 +1,177 @@
+//! Cooperative cancellation tied to the lifetime of a [`Scope`].
+
+use std::cell::Cell;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll, Waker};
+
+use crate::*;
+
+/// Internal state shared between all the clones of a [`CancellationToken`].
+#[derive(Default)]
+struct CancellationState {
+    cancelled: Cell<bool>,
+    wakers: RefCell<Vec<Waker>>,
+}
+
+/// A token that is cancelled when the [`Scope`] it was created from is disposed.
+///
+/// The token is cheaply cloneable and `'static`, which means that it can be moved into spawned
+/// tasks that do not otherwise have access to the scope. Long-running tasks can check
+/// [`is_cancelled`](Self::is_cancelled) or await [`cancelled`](Self::cancelled) to learn when
+/// they should stop.
+///
+/// To create a [`CancellationToken`], use [`Scope::cancellation_token`].
+#[derive(Clone, Default)]
+pub struct CancellationToken(Rc<CancellationState>);
+
+impl CancellationToken {
+    /// Returns `true` if the token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.get()
+    }
+
+    /// Returns a future that resolves once the token is cancelled. If the token is already
+    /// cancelled, the future resolves immediately.
+    pub fn cancelled(&self) -> Cancelled {
+        Cancelled(self.clone())
+    }
+
+    /// Cancel the token and wake up all the tasks that are waiting on it.
+    ///
+    /// If the token has already been cancelled, calling this again does nothing.
+    pub(crate) fn cancel(&self) {
+        if !self.0.cancelled.replace(true) {
+            for waker in self.0.wakers.take() {
+                waker.wake();
+            }
+        }
+    }
+}
+
+impl fmt::Debug for CancellationToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CancellationToken")
+            .field("is_cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+/// Future returned by [`CancellationToken::cancelled`].
+#[must_use = "futures do nothing unless polled"]
+pub struct Cancelled(CancellationToken);
+
+impl Future for Cancelled {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let state = &(self.0).0;
+        if state.cancelled.get() {
+            Poll::Ready(())
+        } else {
+            let mut wakers = state.wakers.borrow_mut();
+            if !wakers.iter().any(|w| w.will_wake(cx.waker())) {
+                wakers.push(cx.waker().clone());
+            }
+            Poll::Pending
+        }
+    }
+}
+
+impl<'a> Scope<'a> {
+    /// Returns a [`CancellationToken`] that is cancelled when this [`Scope`] is disposed.
+    ///
+    /// Calling this method multiple times on the same scope returns clones of the same token.
+    ///
+    /// # Example
+    /// ```
+    /// # use sycamore_reactive::*;
+    /// let mut token = None;
+    /// let disposer = create_scope(|ctx| {
+    ///     token = Some(ctx.cancellation_token());
+    /// });
+    /// let token = token.unwrap();
+    /// assert!(!token.is_cancelled());
+    ///
+    /// unsafe { disposer.dispose(); }
+    /// assert!(token.is_cancelled());
+    /// ```
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.inner
+            .borrow_mut()
+            .cancellation_token
+            .get_or_insert_with(Default::default)
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::task::Wake;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct CountingWaker(AtomicUsize);
+
+    impl Wake for CountingWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn cancellation_token() {
+        let mut token = None;
+        let disposer = create_scope(|ctx| {
+            token = Some(ctx.cancellation_token());
+        });
+        let token = token.unwrap();
+        assert!(!token.is_cancelled());
+
+        unsafe {
+            disposer.dispose();
+        }
+        assert!(token.is_cancelled());
+    }
+
+    #[test]
+    fn cancellation_token_is_shared() {
+        create_scope_immediate(|ctx| {
+            let a = ctx.cancellation_token();
+            let b = ctx.cancellation_token();
+            assert!(Rc::ptr_eq(&a.0, &b.0));
+        });
+    }
+
+    #[test]
+    fn cancelled_resolves_on_dispose() {
+        create_scope_immediate(|ctx| {
+            let mut token = None;
+            let disposer = ctx.create_child_scope(|ctx| {
+                token = Some(ctx.cancellation_token());
+            });
+
+            let counter = Arc::new(CountingWaker::default());
+            let waker = Waker::from(Arc::clone(&counter));
+            let mut cx = Context::from_waker(&waker);
+            let mut task = Box::pin(token.unwrap().cancelled());
+
+            assert!(task.as_mut().poll(&mut cx).is_pending());
+            assert_eq!(counter.0.load(Ordering::SeqCst), 0);
+
+            unsafe {
+                disposer.dispose();
+            }
+            assert_eq!(
+                counter.0.load(Ordering::SeqCst),
+                1,
+                "task should be woken up"
+            );
+            assert!(task.as_mut().poll(&mut cx).is_ready());
+        });
+    }
+}
@@ -27,20 +27,40 @@ impl<'a> Scope<'a> {
     /// The context can also be accessed in the same scope in which it is provided.
     ///
     /// Unlike [`provide_context`](Self::provide_context), this method accepts a reference that
-    /// lives at least as long as the scope.
+    /// lives at least as long as the scope. This makes it possible to share data that is already
+    /// owned by the scope, such as a [`Signal`], without cloning it, as well as unsized values.
+    /// The context is accessed with [`use_context_ref`](Self::use_context_ref) or
+    /// [`use_context`](Self::use_context).
+    ///
+    /// # Example
+    /// ```
+    /// # use sycamore_reactive::*;
+    /// # create_scope_immediate(|ctx| {
+    /// let state = ctx.create_signal(0);
+    /// ctx.provide_context_ref(state);
+    /// ctx.provide_context_ref::<str>("unsized");
+    ///
+    /// let _ = ctx.create_child_scope(|ctx| {
+    ///     ctx.use_context_ref::<Signal<i32>>().set(1);
+    ///     assert_eq!(ctx.use_context_ref::<str>(), "unsized");
+    /// });
+    /// assert_eq!(*state.get(), 1);
+    /// # });
+    /// ```
     ///
     /// # Panics
     /// This method panics if a context with the same type exists already in this scope.
     /// Note that if a context with the same type exists in a parent scope, the new context will
     /// shadow the old context.
     #[track_caller]
-    pub fn provide_context_ref<T: 'static>(&'a self, value: &'a T) -> &'a T {
+    pub fn provide_context_ref<T: ?Sized + 'static>(&'a self, value: &'a T) -> &'a T {
         let type_id = TypeId::of::<T>();
+        let ptr = self.create_ref(value as *const T);
         if self
             .inner
             .borrow_mut()
             .contexts
-            .insert(type_id, value)
+            .insert(type_id, ptr)
             .is_some()
         {
             panic!("existing context with type exists already");
@@ -50,16 +70,18 @@ impl<'a> Scope<'a> {
 
     /// Tries to get a context value of the given type. If no context with the right type found,
     /// returns `None`. For a panicking version, see [`use_context`](Self::use_context).
-    pub fn try_use_context<T: 'static>(&'a self) -> Option<&'a T> {
+    pub fn try_use_context<T: ?Sized + 'static>(&'a self) -> Option<&'a T> {
         let type_id = TypeId::of::<T>();
         let mut this = Some(self);
         while let Some(current) = this {
             if let Some(value) = current.inner.borrow().contexts.get(&type_id) {
-                let value = value.downcast_ref::<T>().unwrap();
-                return Some(value);
+                let ptr = *value.downcast_ref::<*const T>().unwrap();
+                // SAFETY: `ptr` was created from a reference that lives as long as `current`,
+                // which necessarily lives longer than `self`.
+                return Some(unsafe { &*ptr });
             } else {
                 // SAFETY: `current.parent` necessarily lives longer than `current`.
-                this = current.parent.map(|x| unsafe { &*x });
+                this = current.parent.get().map(|x| unsafe { &*x });
             }
         }
         None
@@ -71,26 +93,65 @@ impl<'a> Scope<'a> {
     /// This method panics if the context cannot be found in the current scope hierarchy.
     /// For a non-panicking version, see [`try_use_context`](Self::try_use_context).
     #[track_caller]
-    pub fn use_context<T: 'static>(&'a self) -> &'a T {
+    pub fn use_context<T: ?Sized + 'static>(&'a self) -> &'a T {
         self.try_use_context().expect("context not found for type")
     }
 
+    /// Gets a context value of the given type that was provided with
+    /// [`provide_context_ref`](Self::provide_context_ref). This is the same as
+    /// [`use_context`](Self::use_context).
+    ///
+    /// # Panics
+    /// This method panics if the context cannot be found in the current scope hierarchy.
+    /// For a non-panicking version, see [`try_use_context`](Self::try_use_context).
+    #[track_caller]
+    pub fn use_context_ref<T: ?Sized + 'static>(&'a self) -> &'a T {
+        self.use_context()
+    }
+
     /// Gets a context value of the given type or computes it from a closure.
     pub fn use_context_or_else<T: 'static>(&'a self, f: impl FnOnce() -> T) -> &'a T {
         self.try_use_context()
             .unwrap_or_else(|| self.provide_context(f()))
     }
 
+    /// Provides `value` as a context in a new child [`Scope`] and runs `f` with the child scope.
+    /// The context can be accessed from the child scope but not from `self`.
+    ///
+    /// The child scope, and therefore the context and everything else created in it, is disposed
+    /// as soon as `f` returns. To provide a context to a reactive subtree, use
+    /// [`provide_context`](Self::provide_context) in a child component instead.
+    ///
+    /// # Example
+    /// ```
+    /// # use sycamore_reactive::*;
+    /// # create_scope_immediate(|ctx| {
+    /// let doubled = ctx.with_context(21i32, |ctx| *ctx.use_context::<i32>() * 2);
+    /// assert_eq!(doubled, 42);
+    /// assert!(ctx.try_use_context::<i32>().is_none());
+    /// # });
+    /// ```
+    pub fn with_context<T: 'static, R>(
+        &'a self,
+        value: T,
+        f: impl for<'child_lifetime> FnOnce(BoundedScopeRef<'child_lifetime, 'a>) -> R,
+    ) -> R {
+        let mut ret = None;
+        let disposer = self.create_child_scope(|ctx| {
+            ctx.provide_context(value);
+            ret = Some(f(ctx));
+        });
+        // SAFETY: `ret` cannot reference the child scope since `R` does not depend on
+        // `'child_lifetime`.
+        unsafe {
+            disposer.dispose();
+        }
+        ret.unwrap()
+    }
+
     /// Returns the current depth of the scope. If the scope is the root scope, returns `0`.
     pub fn scope_depth(&self) -> u32 {
-        let mut depth = 0;
-        let mut this = Some(self);
-        while let Some(current) = this {
-            // SAFETY: `current.parent` necessarily lives longer than `current`.
-            this = current.parent.map(|x| unsafe { &*x });
-            depth += 1;
-        }
-        depth
+        self.depth.get()
     }
 }
 
@@ -118,6 +179,60 @@ mod tests {
         });
     }
 
+    #[test]
+    fn context_ref_in_descendant_effect() {
+        create_scope_immediate(|ctx| {
+            let state = ctx.create_signal(0);
+            ctx.provide_context_ref(state);
+            let seen = ctx.create_ref(RefCell::new(Vec::new()));
+
+            let _ = ctx.create_child_scope(|ctx| {
+                let _ = ctx.create_child_scope(|ctx| {
+                    ctx.create_effect(move || {
+                        let state = ctx.use_context_ref::<Signal<i32>>();
+                        seen.borrow_mut().push(*state.get());
+                    });
+                });
+            });
+            assert_eq!(*seen.borrow(), [0]);
+
+            // The context is the same signal, not a copy.
+            state.set(1);
+            assert_eq!(*seen.borrow(), [0, 1]);
+        });
+    }
+
+    #[test]
+    fn unsized_context_ref() {
+        create_scope_immediate(|ctx| {
+            let items = ctx.create_ref(vec![1, 2, 3]);
+            ctx.provide_context_ref::<[i32]>(items);
+            let _ = ctx.create_child_scope(|ctx| {
+                assert_eq!(ctx.use_context_ref::<[i32]>(), [1, 2, 3]);
+                assert!(ctx.try_use_context::<Vec<i32>>().is_none());
+            });
+        });
+    }
+
+    #[test]
+    fn with_context() {
+        create_scope_immediate(|ctx| {
+            let x = ctx.with_context(42i32, |ctx| {
+                let _ = ctx.create_child_scope(|ctx| {
+                    assert_eq!(*ctx.use_context::<i32>(), 42);
+                });
+                *ctx.use_context::<i32>()
+            });
+            assert_eq!(x, 42);
+            assert!(ctx.try_use_context::<i32>().is_none());
+
+            ctx.provide_context(0i32);
+            let x = ctx.with_context(1i32, |ctx| *ctx.use_context::<i32>());
+            assert_eq!(x, 1, "context is shadowed in the child scope");
+            assert_eq!(*ctx.use_context::<i32>(), 0);
+        });
+    }
+
     #[test]
     // Do not run under miri as there is a memory leak false positive.
     #[cfg_attr(miri, ignore)]
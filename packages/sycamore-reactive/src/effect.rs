@@ -1,6 +1,7 @@
 //! Side effects.
 
 use std::collections::HashSet;
+use std::fmt;
 
 use crate::*;
 
@@ -9,6 +10,49 @@ thread_local! {
     /// Because this is a global variable, the lifetime is necessarily `'static`. However, that does not mean
     /// that it can last forever. The `EffectState` should only be used the time it is inside [`EFFECTS`].
     pub(crate) static EFFECTS: RefCell<Vec<*mut EffectState<'static>>> = Default::default();
+    /// Global counter that is incremented every time an effect finishes running.
+    static RUN_COUNT: Cell<u64> = Default::default();
+    /// The names of the named effects in the order in which they ran. See [`effect_run_log`].
+    #[cfg(feature = "testing")]
+    static EFFECT_RUN_LOG: RefCell<Vec<&'static str>> = Default::default();
+    /// See [`set_effect_dependency_limit`].
+    #[cfg(debug_assertions)]
+    static DEPENDENCY_LIMIT: Cell<Option<usize>> = Default::default();
+    /// See [`on_effect_dependency_limit_exceeded`].
+    #[cfg(debug_assertions)]
+    static DEPENDENCY_LIMIT_CALLBACK: RefCell<Option<DependencyLimitCallback>> = Default::default();
+}
+
+/// The information needed to order an effect relative to the other subscribers of a signal.
+///
+/// It is shared with the [`SignalEmitter`]s that the effect is subscribed to, so that it can be
+/// updated after every run without having to re-subscribe.
+#[derive(Default)]
+pub(crate) struct EffectSchedule {
+    /// The priority of the effect. See [`Scope::create_effect_with_priority`].
+    pub priority: i32,
+    /// The phase of the effect. See [`Scope::create_effect_phased`].
+    pub phase: Option<EffectPhase>,
+    /// The height of the effect in the dependency graph. See [`EffectState::height`].
+    pub height: Cell<u32>,
+    /// The value of the global run counter when the effect last finished running.
+    pub last_run: Cell<u64>,
+    /// The signal that caused the effect to be queued, until the effect has run. See
+    /// [`current_trigger`].
+    #[cfg(feature = "devtools")]
+    pub trigger: Cell<Option<SignalId>>,
+    /// The name of the effect. See [`Scope::create_effect_named`].
+    #[cfg(feature = "testing")]
+    pub name: Option<&'static str>,
+}
+
+/// The phase of an effect created with [`Scope::create_effect_phased`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EffectPhase {
+    /// The effect reads from the DOM, e.g. to measure the layout.
+    Read,
+    /// The effect writes to the DOM.
+    Write,
 }
 
 /// The internal state of an effect. The effect callback and the effect dependencies are stored in
@@ -18,44 +62,187 @@ pub(crate) struct EffectState<'a> {
     cb: Rc<RefCell<dyn FnMut() + 'a>>,
     /// A list of dependencies that can trigger this effect.
     dependencies: HashSet<EffectDependency>,
+    /// See [`EffectSchedule`].
+    schedule: Rc<EffectSchedule>,
+    /// A weak reference to the cell that holds this state while the effect is not running.
+    this: Weak<RefCell<Option<EffectState<'a>>>>,
 }
 
+/// A weak reference to the state of an effect. The lifetime is erased: the state is only used to
+/// read the dependencies of the effect.
+pub(crate) type WeakEffectState = Weak<RefCell<Option<EffectState<'static>>>>;
+
 /// Implements reference equality for [`WeakSignalEmitter`]s.
-pub(crate) struct EffectDependency(WeakSignalEmitter);
+pub(crate) struct EffectDependency {
+    emitter: WeakSignalEmitter,
+    /// The value of the global trigger counter when the dependency was first tracked.
+    tracked_at: u64,
+}
+impl EffectDependency {
+    /// Returns `true` if the signal has been updated since it was tracked. This means that the
+    /// effect might have observed an outdated value.
+    fn is_stale(&self) -> bool {
+        match self.emitter.upgrade() {
+            Some(emitter) => emitter.triggered_since(self.tracked_at),
+            None => false,
+        }
+    }
+}
 impl std::cmp::PartialEq for EffectDependency {
     fn eq(&self, other: &Self) -> bool {
-        Weak::as_ptr(&self.0 .0) == Weak::as_ptr(&other.0 .0)
+        Weak::as_ptr(&self.emitter.0) == Weak::as_ptr(&other.emitter.0)
     }
 }
 impl std::cmp::Eq for EffectDependency {}
 impl std::hash::Hash for EffectDependency {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        Weak::as_ptr(&self.0 .0).hash(state);
+        Weak::as_ptr(&self.emitter.0).hash(state);
     }
 }
 
 impl<'a> EffectState<'a> {
-    // Clears the dependencies (both links and backlinks).
-    /// Should be called when re-executing an effect to recreate all dependencies.
-    pub fn clear_dependencies(&mut self) {
-        for dependency in &self.dependencies {
-            if let Some(dep) = dependency.0.upgrade() {
+    /// Updates the backlinks from the signals to the effect after it has run. `previous` are the
+    /// dependencies of the previous run. Only the dependencies that were added or removed since
+    /// then are subscribed or unsubscribed.
+    ///
+    /// While the effect runs, it is still subscribed to `previous`, but the triggers that it
+    /// receives are dropped since it is running (see [`SignalEmitter::trigger_subscribers`]). No
+    /// update is lost that way: if a dependency of the current run is updated after it was
+    /// tracked, [`EffectDependency::is_stale`] detects it and the effect is run again. A dependency
+    /// of the previous run that is no longer tracked does not need to re-run the effect, and it is
+    /// unsubscribed here.
+    pub fn update_subscriptions(&mut self, previous: HashSet<EffectDependency>) {
+        for dependency in previous.difference(&self.dependencies) {
+            // The SignalEmitter might have been destroyed in the meantime.
+            if let Some(emitter) = dependency.emitter.upgrade() {
                 // SAFETY: We only access the pointer, not the pointed data.
-                dep.unsubscribe(unsafe { std::mem::transmute(Rc::as_ptr(&self.cb)) })
+                emitter.unsubscribe(unsafe { std::mem::transmute(Rc::as_ptr(&self.cb)) })
             };
         }
-        self.dependencies.clear();
+        for dependency in self.dependencies.difference(&previous) {
+            // The SignalEmitter might have been destroyed between when the signal was accessed and
+            // now.
+            if let Some(emitter) = dependency.emitter.upgrade() {
+                // SAFETY: When the effect is destroyed or when the emitter is dropped, this link
+                // will be destroyed to prevent dangling references. The emitter only holds a weak
+                // reference, which can only be upgraded while the scope that owns the effect is
+                // alive, and everything that the callback borrows outlives that scope since the
+                // callback is `'a`.
+                emitter.subscribe(
+                    Rc::downgrade(unsafe { std::mem::transmute(&self.cb) }),
+                    Rc::clone(&self.schedule),
+                );
+            }
+        }
+
+        self.schedule.height.set(self.height());
+        self.schedule.last_run.set(RUN_COUNT.with(|count| {
+            count.set(count.get() + 1);
+            count.get()
+        }));
     }
 
-    /// Add a dependency to the effect.
+    /// Add a dependency to the effect. If the dependency already exists, does nothing.
     pub fn add_dependency(&mut self, signal: WeakSignalEmitter) {
-        self.dependencies.insert(EffectDependency(signal));
+        self.dependencies.insert(EffectDependency {
+            emitter: signal,
+            tracked_at: current_trigger_tick(),
+        });
     }
+
+    /// Add several dependencies to the effect at once. Dependencies that already exist are
+    /// ignored.
+    pub fn add_dependencies(&mut self, signals: impl ExactSizeIterator<Item = WeakSignalEmitter>) {
+        let tracked_at = current_trigger_tick();
+        self.dependencies.reserve(signals.len());
+        self.dependencies
+            .extend(signals.map(|emitter| EffectDependency {
+                emitter,
+                tracked_at,
+            }));
+    }
+
+    /// The height of the effect in the dependency graph, which is one more than the greatest
+    /// height of its dependencies. When a signal is updated, effects with a lower height run
+    /// first.
+    pub fn height(&self) -> u32 {
+        self.dependencies
+            .iter()
+            .filter_map(|dependency| dependency.emitter.upgrade())
+            .map(|emitter| emitter.height())
+            .max()
+            .unwrap_or(0)
+            .saturating_add(1)
+    }
+}
+
+/// Returns the height of the effect that is currently running, based on the dependencies that it
+/// has tracked so far, or `None` if no effect is running.
+pub(crate) fn current_effect_height() -> Option<u32> {
+    EFFECTS.with(|effects| {
+        effects.borrow().last().map(|last| {
+            // SAFETY: See guarantee on EffectState within EFFECTS.
+            let last = unsafe { &**last };
+            last.height()
+        })
+    })
+}
+
+/// Returns a weak reference to the state of the effect that is currently running, or `None` if no
+/// effect is running.
+pub(crate) fn current_effect_state() -> Option<WeakEffectState> {
+    EFFECTS.with(|effects| {
+        effects.borrow().last().map(|last| {
+            // SAFETY: See guarantee on EffectState within EFFECTS.
+            let last = unsafe { &**last };
+            last.this.clone()
+        })
+    })
+}
+
+/// Returns the signals that the effect depended on in its last run. Returns nothing if the effect
+/// is running or was destroyed.
+pub(crate) fn effect_dependencies(state: &WeakEffectState) -> Vec<SignalEmitter> {
+    let state = match state.upgrade() {
+        Some(state) => state,
+        None => return Vec::new(),
+    };
+    let state = match state.try_borrow() {
+        Ok(state) => state,
+        Err(_) => return Vec::new(),
+    };
+    state
+        .iter()
+        .flat_map(|state| &state.dependencies)
+        .filter_map(|dependency| dependency.emitter.upgrade())
+        .collect()
+}
+
+/// Returns a weak reference to the callback of the effect that is currently running, or `None` if
+/// no effect is running.
+pub(crate) fn current_effect_callback() -> Option<WeakEffectCallback> {
+    EFFECTS.with(|effects| {
+        effects.borrow().last().map(|last| {
+            // SAFETY: See guarantee on EffectState within EFFECTS.
+            let last = unsafe { &**last };
+            Rc::downgrade(&last.cb) as WeakEffectCallback
+        })
+    })
 }
 
 impl<'a> Scope<'a> {
     /// Creates an effect on signals used inside the effect closure.
     ///
+    /// When updating a signal causes several dependencies of the effect to change (for instance
+    /// two memos derived from the same signal), the effect only runs once, after all of them have
+    /// been updated.
+    ///
+    /// An effect is never re-entered while it is running. If a dependency changes while the effect
+    /// runs, including when the effect itself sets a signal that it has read, the effect runs again
+    /// once after it returns, but only once, to prevent infinite loops. An effect that reads and
+    /// then writes to its own dependency therefore runs twice. Use [`untrack`] for the read, or
+    /// [`Signal::set_silent`] for the write, to avoid this.
+    ///
     /// # Example
     /// ```
     /// # use sycamore_reactive::*;
@@ -69,12 +256,134 @@ impl<'a> Scope<'a> {
     /// state.set(1); // Prints "State changed. New state value = 1"
     /// # });
     /// ```
+    ///
+    /// # Lifetimes
+    ///
+    /// The effect is owned by the scope and is destroyed when the scope is disposed. The closure
+    /// can therefore borrow anything that outlives the scope, such as the signals of the scope
+    /// itself or of its parent scopes, but not the signals of a child scope, since the effect might
+    /// run after the child scope is disposed. To read a signal from a child scope, create it with
+    /// [`create_rc_signal`] so that the effect can own it instead.
+    ///
+    /// ```compile_fail
+    /// # use sycamore_reactive::*;
+    /// # create_scope_immediate(|ctx| {
+    /// let _ = ctx.create_child_scope(|child| {
+    ///     let signal = child.create_signal(0);
+    ///     ctx.create_effect(move || signal.track());
+    /// });
+    /// # });
+    /// ```
+    #[track_caller]
     pub fn create_effect(&self, f: impl FnMut() + 'a) {
-        self._create_effect(Box::new(f))
+        self._create_effect(Box::new(f), EffectSchedule::default())
+    }
+
+    /// Creates an effect on signals used inside the effect closure, with the given `priority`.
+    ///
+    /// When a signal is updated, the effects that depend on it are run in ascending order of
+    /// priority. Effects created with [`create_effect`](Self::create_effect) have a priority of
+    /// `0`. Effects with the same priority run in the same order as they would without a priority.
+    ///
+    /// This is useful, for instance, to make sure that effects that compute derived state run
+    /// before effects that update the DOM.
+    ///
+    /// # Example
+    /// ```
+    /// # use sycamore_reactive::*;
+    /// # create_scope_immediate(|ctx| {
+    /// let state = ctx.create_signal(0);
+    ///
+    /// ctx.create_effect_with_priority(1, || {
+    ///     println!("Runs second. State = {}", state.get());
+    /// });
+    /// ctx.create_effect_with_priority(-1, || {
+    ///     println!("Runs first. State = {}", state.get());
+    /// });
+    ///
+    /// state.set(1);
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn create_effect_with_priority(&self, priority: i32, f: impl FnMut() + 'a) {
+        self._create_effect(
+            Box::new(f),
+            EffectSchedule {
+                priority,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Creates an effect on signals used inside the effect closure, in the given `phase`.
+    ///
+    /// When a signal is updated, all the [`Read`](EffectPhase::Read) effects that need to re-run
+    /// are run before any of the [`Write`](EffectPhase::Write) effects, regardless of the order in
+    /// which they subscribed to their dependencies. Batching reads and writes this way prevents the
+    /// browser from having to re-compute the layout in between. Effects without a phase, which
+    /// include memos, run before both.
+    ///
+    /// # Example
+    /// ```
+    /// # use sycamore_reactive::*;
+    /// # create_scope_immediate(|ctx| {
+    /// let state = ctx.create_signal(0);
+    ///
+    /// ctx.create_effect_phased(EffectPhase::Write, || {
+    ///     println!("Runs second. State = {}", state.get());
+    /// });
+    /// ctx.create_effect_phased(EffectPhase::Read, || {
+    ///     println!("Runs first. State = {}", state.get());
+    /// });
+    ///
+    /// state.set(1);
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn create_effect_phased(&self, phase: EffectPhase, f: impl FnMut() + 'a) {
+        self._create_effect(
+            Box::new(f),
+            EffectSchedule {
+                phase: Some(phase),
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Creates an effect on signals used inside the effect closure, with a `name` that is recorded
+    /// in the [`effect_run_log`] every time the effect runs.
+    ///
+    /// # Example
+    /// ```
+    /// # use sycamore_reactive::*;
+    /// # create_scope_immediate(|ctx| {
+    /// let state = ctx.create_signal(0);
+    /// ctx.create_effect_named("logger", || println!("State = {}", state.get()));
+    ///
+    /// reset_effect_run_log();
+    /// state.set(1);
+    /// assert_eq!(effect_run_log(), ["logger"]);
+    /// # });
+    /// ```
+    ///
+    /// _This API requires the following crate features to be activated: `testing`_
+    #[cfg(feature = "testing")]
+    #[track_caller]
+    pub fn create_effect_named(&self, name: &'static str, f: impl FnMut() + 'a) {
+        self._create_effect(
+            Box::new(f),
+            EffectSchedule {
+                name: Some(name),
+                ..Default::default()
+            },
+        )
     }
 
     /// Internal implementation for `create_effect`. Use dynamic dispatch to reduce code-bloat.
-    fn _create_effect(&self, mut f: Box<dyn FnMut() + 'a>) {
+    #[track_caller]
+    fn _create_effect(&self, mut f: Box<dyn FnMut() + 'a>, schedule: EffectSchedule) {
+        #[cfg(debug_assertions)]
+        let location = std::panic::Location::caller();
         let effect = Rc::new(RefCell::new(None::<EffectState<'a>>));
         let cb = Rc::new(RefCell::new({
             let effect = Rc::downgrade(&effect);
@@ -88,40 +397,56 @@ impl<'a> Scope<'a> {
 
                     // Take effect out.
                     let mut effect = effect_ref.take().unwrap();
-                    effect.clear_dependencies();
-
-                    // Push the effect onto the effect stack so that it is visible by signals.
-                    effects
-                        .borrow_mut()
-                        .push(unsafe { std::mem::transmute(&mut effect as *mut EffectState<'a>) });
-                    // Now we can call the user-provided function.
-                    f();
-                    // Pop the effect from the effect stack.
-                    effects.borrow_mut().pop().unwrap();
-                    // The raw pointer pushed onto `effects` is dead and can no longer be accessed.
-                    // We can now access `effect` directly again.
-
-                    // For all the signals collected by the EffectState,
-                    // we need to add backlinks from the signal to the effect, so that
-                    // updating the signal will trigger the effect.
-                    for emitter in &effect.dependencies {
-                        // The SignalEmitter might have been destroyed between when the signal was
-                        // accessed and now.
-                        if let Some(emitter) = emitter.0.upgrade() {
-                            // SAFETY: When the effect is destroyed or when the emitter is dropped,
-                            // this link will be destroyed to prevent
-                            // dangling references.
-                            emitter.subscribe(Rc::downgrade(unsafe {
-                                std::mem::transmute(&effect.cb)
-                            }));
+
+                    // The effect stays subscribed to the dependencies of its previous run while it
+                    // is running so that only the dependencies that changed need to be updated
+                    // afterwards. It is not triggered while running however: if a dependency is
+                    // updated after it was tracked (e.g. by the effect itself), the effect is run
+                    // again once after it has completed instead of being re-entered. The effect is
+                    // only re-run once to prevent infinite loops.
+                    let previous = mem::take(&mut effect.dependencies);
+                    let mut rerun = false;
+                    loop {
+                        effect.dependencies.clear();
+
+                        // Push the effect onto the effect stack so that it is visible by signals.
+                        effects.borrow_mut().push(unsafe {
+                            std::mem::transmute(&mut effect as *mut EffectState<'a>)
+                        });
+                        #[cfg(feature = "testing")]
+                        if let Some(name) = effect.schedule.name {
+                            EFFECT_RUN_LOG.with(|log| log.borrow_mut().push(name));
+                        }
+                        // Now we can call the user-provided function.
+                        f();
+                        // Pop the effect from the effect stack.
+                        effects.borrow_mut().pop().unwrap();
+                        // The raw pointer pushed onto `effects` is dead and can no longer be
+                        // accessed. We can now access `effect` directly again.
+
+                        if rerun || !effect.dependencies.iter().any(EffectDependency::is_stale) {
+                            break;
                         }
+                        rerun = true;
                     }
 
+                    #[cfg(debug_assertions)]
+                    check_dependency_limit(&effect, previous.len(), location);
+
+                    // For all the signals collected by the EffectState, we need to add backlinks
+                    // from the signal to the effect, so that updating the signal will trigger the
+                    // effect.
+                    effect.update_subscriptions(previous);
+                    #[cfg(feature = "devtools")]
+                    effect.schedule.trigger.set(None);
+
                     // Get the effect state back into the Rc
                     *effect_ref.borrow_mut() = Some(effect);
 
                     debug_assert_eq!(effects.borrow().len(), initial_effect_stack_len);
                 });
+                // The effect might have disposed its own scope.
+                run_deferred_disposals();
             }
         }));
 
@@ -129,13 +454,16 @@ impl<'a> Scope<'a> {
         *effect.borrow_mut() = Some(EffectState {
             cb: cb.clone(),
             dependencies: HashSet::new(),
+            schedule: Rc::new(schedule),
+            this: Rc::downgrade(&effect),
         });
 
+        // Push Rc to self.effects so that it is not dropped immediately. This is done before the
+        // first run so that the scope knows that the effect is running.
+        self.inner.borrow_mut().effects.push(effect);
+
         // Initial callback call to get everything started.
         cb.borrow_mut()();
-
-        // Push Rc to self.effects so that it is not dropped immediately.
-        self.inner.borrow_mut().effects.push(effect);
     }
 
     /// Creates an effect on signals used inside the effect closure.
@@ -147,6 +475,18 @@ impl<'a> Scope<'a> {
     /// Items created within the scope cannot escape outside the effect because that can result in
     /// an use-after-free.
     ///
+    /// # Ordering
+    ///
+    /// When the effect re-runs, the scope of the previous run is disposed before anything else
+    /// happens: its cleanup callbacks are called and its nested effects and signals are dropped
+    /// before `f` is called with the new scope. A nested effect of the previous run is therefore
+    /// never triggered again, even if the new run writes to a signal that it depends on.
+    ///
+    /// The effect stays subscribed to the dependencies of the previous run while `f` is running and
+    /// its subscriptions are only updated once `f` returns. By then, the signals of the previous
+    /// scope have been dropped, so the effect does not keep a reference to them, even if it
+    /// subscribed to them.
+    ///
     /// # Example
     /// ```
     /// # use sycamore_reactive::*;
@@ -158,15 +498,17 @@ impl<'a> Scope<'a> {
     /// });
     /// # });
     /// ```
+    #[track_caller]
     pub fn create_effect_scoped<F>(&'a self, mut f: F)
     where
         F: for<'child_lifetime> FnMut(BoundedScopeRef<'child_lifetime, 'a>) + 'a,
     {
         let mut disposer: Option<Box<ScopeDisposer<'a>>> = None;
         self.create_effect(move || {
-            // We run the disposer inside the effect, after effect dependencies have been cleared.
-            // This is to make sure that if the effect subscribes to its own signal, there is no
-            // use-after-free during the clear dependencies phase.
+            // We run the disposer inside the effect, before the new scope is created. If the effect
+            // subscribed to a signal created in the previous child scope, the signal is dropped
+            // along with the scope and is skipped when the subscriptions are updated after the
+            // effect has run.
             if let Some(disposer) = disposer.take() {
                 // SAFETY: we are not accessing the scope after the effect has been dropped.
                 unsafe {
@@ -186,6 +528,130 @@ impl<'a> Scope<'a> {
             disposer = new_disposer;
         });
     }
+
+    /// Creates an effect on signals used inside the effect closure, unless an effect was already
+    /// created by this call site on this scope while the same effect was running.
+    ///
+    /// Calls made outside of an effect always create a new effect.
+    ///
+    /// Effects created on the scope provided by
+    /// [`create_effect_scoped`](Self::create_effect_scoped) are disposed and created again
+    /// every time the outer effect re-runs. To keep a nested effect alive across these re-runs,
+    /// call this method on the _outer_ scope instead: the effect is created on the first run
+    /// and the following runs reuse it. It is disposed along with the outer scope.
+    ///
+    /// Because the effect is owned by the outer scope, it cannot capture anything created in the
+    /// inner scope.
+    ///
+    /// Effects are identified by their call site only. Calling this method several times from the
+    /// same call site while the same effect is running, e.g. in a loop, only creates an effect for
+    /// the first call. Use [`create_effect`](Self::create_effect) on a scope that lives as long as
+    /// the effects should to create several of them.
+    ///
+    /// # Example
+    /// ```
+    /// # use sycamore_reactive::*;
+    /// # create_scope_immediate(|ctx| {
+    /// let trigger = ctx.create_signal(());
+    /// let state = ctx.create_signal(0);
+    /// ctx.create_effect_scoped(|inner| {
+    ///     trigger.track();
+    ///     // Recreated every time `trigger` changes.
+    ///     inner.create_effect(|| println!("Scoped: {}", state.get()));
+    ///     // Only created once.
+    ///     ctx.create_effect_persistent(|| println!("Persistent: {}", state.get()));
+    /// });
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn create_effect_persistent(&self, f: impl FnMut() + 'a) {
+        // Outside of an effect, there is no re-run to dedupe so every call creates an effect.
+        if let Some(running) = current_effect_callback() {
+            let location = std::panic::Location::caller();
+            let mut inner = self.inner.borrow_mut();
+            // Holding a `Weak` keeps the address of the running effect from being reused, so
+            // comparing pointers is enough to identify it.
+            let exists = inner
+                .persistent_effects
+                .iter()
+                .any(|(effect, call_site)| *call_site == location && effect.ptr_eq(&running));
+            if exists {
+                return;
+            }
+            inner.persistent_effects.push((running, location));
+        }
+        self.create_effect(f);
+    }
+
+    /// Creates an effect on signals used inside the effect closure, which receives a
+    /// [`CleanupRegistrar`] for registering cleanup callbacks.
+    ///
+    /// The cleanup callbacks registered during a run are called before the effect is re-run, and
+    /// when the scope is disposed. This is useful for tearing down resources created by each run,
+    /// such as event listeners or timers.
+    ///
+    /// # Example
+    /// ```
+    /// # use sycamore_reactive::*;
+    /// # create_scope_immediate(|ctx| {
+    /// let interval = ctx.create_signal(1000);
+    /// ctx.create_effect_with_cleanup(|cleanup| {
+    ///     println!("Starting a timer every {}ms", interval.get());
+    ///     cleanup.on_cleanup(|| println!("Stopping the timer"));
+    /// });
+    ///
+    /// interval.set(500); // Prints "Stopping the timer" and then "Starting a timer every 500ms"
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn create_effect_with_cleanup<F>(&'a self, mut f: F)
+    where
+        F: for<'child_lifetime> FnMut(CleanupRegistrar<'child_lifetime, 'a>) + 'a,
+    {
+        // The previous child scope, and therefore the cleanup callbacks registered in it, is
+        // disposed before the closure is re-run.
+        self.create_effect_scoped(move |ctx| f(CleanupRegistrar(ctx)));
+    }
+
+    /// Creates an effect on signals used inside the effect closure, which can fail. If `f`
+    /// returns an error, it is passed to `on_error` instead of being propagated.
+    ///
+    /// The signals read by `f` are tracked whether it succeeds or not, so the effect is re-run when
+    /// they change, e.g. once the input that caused the error is fixed. `on_error` is untracked.
+    /// This is useful for reporting the errors of an effect somewhere in the UI, such as in a
+    /// toast, without unwrapping them.
+    ///
+    /// # Example
+    /// ```
+    /// # use sycamore_reactive::*;
+    /// # create_scope_immediate(|ctx| {
+    /// let input = ctx.create_signal(String::from("1"));
+    /// let error = ctx.create_signal(None);
+    /// ctx.create_effect_fallible(
+    ///     || {
+    ///         let value: i32 = input.get().parse()?;
+    ///         println!("Value = {value}");
+    ///         Ok(())
+    ///     },
+    ///     |err: std::num::ParseIntError| error.set(Some(err.to_string())),
+    /// );
+    ///
+    /// input.set(String::from("a"));
+    /// assert!(error.get().is_some());
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn create_effect_fallible<E>(
+        &self,
+        mut f: impl FnMut() -> Result<(), E> + 'a,
+        on_error: impl Fn(E) + 'a,
+    ) {
+        self.create_effect(move || {
+            if let Err(err) = f() {
+                untrack(|| on_error(err));
+            }
+        });
+    }
 }
 
 /// Run the passed closure inside an untracked dependency scope.
@@ -208,14 +674,239 @@ impl<'a> Scope<'a> {
 /// # });
 /// ```
 pub fn untrack<T>(f: impl FnOnce() -> T) -> T {
+    /// Restores the effect stack when dropped, even if `f` panicked.
+    struct RestoreEffects(Vec<*mut EffectState<'static>>);
+
+    impl Drop for RestoreEffects {
+        fn drop(&mut self) {
+            EFFECTS.with(|effects| *effects.borrow_mut() = mem::take(&mut self.0));
+        }
+    }
+
+    let _restore = RestoreEffects(EFFECTS.with(|effects| effects.take()));
+    f()
+}
+
+/// The signals that were read by a closure, captured with [`capture_dependencies`].
+pub struct Dependencies(Vec<WeakSignalEmitter>);
+
+impl Dependencies {
+    /// Track all the signals as dependencies of the running effect, as if they were read again.
+    /// The signals that were dropped in the meantime are ignored.
+    pub fn track(&self) {
+        for emitter in self.0.iter().filter_map(WeakSignalEmitter::upgrade) {
+            emitter.track();
+        }
+    }
+}
+
+impl fmt::Debug for Dependencies {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Dependencies")
+            .field("len", &self.0.len())
+            .finish()
+    }
+}
+
+/// Runs `f` and returns the signals that it read, instead of tracking them as dependencies of the
+/// running effect. The signals can then be tracked later with [`Dependencies::track`].
+///
+/// This is useful for effects that need to keep depending on the inputs of a computation without
+/// re-running the computation every time, e.g. to defer it.
+///
+/// # Example
+/// ```
+/// # use sycamore_reactive::*;
+/// # create_scope_immediate(|ctx| {
+/// let state = ctx.create_signal(1);
+/// let (double, dependencies) = capture_dependencies(|| *state.get() * 2);
+/// assert_eq!(double, 2);
+///
+/// let runs = ctx.create_signal(0);
+/// ctx.create_effect(move || {
+///     dependencies.track();
+///     runs.set(*runs.get_untracked() + 1);
+/// });
+/// state.set(2);
+/// assert_eq!(*runs.get(), 2);
+/// # });
+/// ```
+pub fn capture_dependencies<T>(f: impl FnOnce() -> T) -> (T, Dependencies) {
+    /// Removes the capturing effect from the effect stack when dropped, even if `f` panicked.
+    struct PopEffect;
+
+    impl Drop for PopEffect {
+        fn drop(&mut self) {
+            EFFECTS.with(|effects| effects.borrow_mut().pop());
+        }
+    }
+
+    let mut state: EffectState<'static> = EffectState {
+        cb: Rc::new(RefCell::new(|| {})),
+        dependencies: HashSet::new(),
+        schedule: Default::default(),
+        this: Weak::new(),
+    };
+    // The state is only used while it is in the effect stack, which it is removed from before the
+    // end of this function.
+    EFFECTS.with(|effects| effects.borrow_mut().push(&mut state));
+    let pop = PopEffect;
+    let value = f();
+    drop(pop);
+
+    let dependencies = state
+        .dependencies
+        .into_iter()
+        .map(|dependency| dependency.emitter)
+        .collect();
+    (value, Dependencies(dependencies))
+}
+
+/// Returns the signal that caused the currently running effect to re-run, or `None` if no effect
+/// is running or if the effect is running for the first time.
+///
+/// If several signals were updated before the effect re-ran, this returns the first one. Since
+/// [`untrack`] hides the running effect, this also returns `None` inside of it.
+///
+/// # Example
+/// ```
+/// # use sycamore_reactive::*;
+/// # create_scope_immediate(|ctx| {
+/// let a = ctx.create_signal(0);
+/// let b = ctx.create_signal(0);
+/// ctx.create_effect(move || {
+///     a.track();
+///     b.track();
+///     if current_trigger() == Some(b.id()) {
+///         println!("Re-running because b changed");
+///     }
+/// });
+/// b.set(1);
+/// # });
+/// ```
+///
+/// _This API requires the following crate features to be activated: `devtools`_
+#[cfg(feature = "devtools")]
+pub fn current_trigger() -> Option<SignalId> {
     EFFECTS.with(|effects| {
-        let tmp = effects.take();
-        let ret = f();
-        *effects.borrow_mut() = tmp;
-        ret
+        let last = *effects.borrow().last()?;
+        // SAFETY: See guarantee on EffectState within EFFECTS.
+        let last = unsafe { &*last };
+        last.schedule.trigger.get()
     })
 }
 
+/// Details about an effect that tracked more dependencies than the limit set with
+/// [`set_effect_dependency_limit`].
+#[derive(Debug, Clone, Copy)]
+pub struct DependencyLimitExceeded {
+    /// The source location where the effect was created.
+    pub location: &'static std::panic::Location<'static>,
+    /// The name of the effect if it was created with [`Scope::create_effect_named`].
+    pub name: Option<&'static str>,
+    /// The number of dependencies tracked by the last run of the effect.
+    pub dependencies: usize,
+    /// The limit that was exceeded.
+    pub limit: usize,
+}
+
+impl fmt::Display for DependencyLimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "effect ")?;
+        if let Some(name) = self.name {
+            write!(f, "`{name}` ")?;
+        }
+        write!(
+            f,
+            "created at {} tracked {} dependencies, which is more than the limit of {}",
+            self.location, self.dependencies, self.limit
+        )
+    }
+}
+
+/// A function that is called when an effect exceeds the dependency limit. See
+/// [`on_effect_dependency_limit_exceeded`].
+pub type DependencyLimitCallback = Rc<dyn Fn(&DependencyLimitExceeded)>;
+
+/// Sets the maximum number of dependencies that an effect on the current thread can track before
+/// a warning is emitted, or `None` to disable the check, which is the default.
+///
+/// An effect that reads many signals, for instance in a loop over a large list of signals, is
+/// slowed down by the bookkeeping of its dependencies every time it runs. This is usually a sign
+/// that the effect should read a single signal holding the list instead. The warning is emitted
+/// once each time the number of dependencies of an effect goes over the limit. It is printed to
+/// stderr unless a callback is set with [`on_effect_dependency_limit_exceeded`].
+///
+/// The check is only performed in debug builds. In release builds, this function does nothing.
+///
+/// # Example
+/// ```
+/// # use sycamore_reactive::*;
+/// set_effect_dependency_limit(Some(1000));
+/// ```
+pub fn set_effect_dependency_limit(limit: Option<usize>) {
+    #[cfg(debug_assertions)]
+    DEPENDENCY_LIMIT.with(|x| x.set(limit));
+    #[cfg(not(debug_assertions))]
+    let _ = limit;
+}
+
+/// Sets the function that is called instead of printing a warning when an effect exceeds the
+/// limit set with [`set_effect_dependency_limit`], or `None` to print the warning to stderr again.
+///
+/// Since stderr is not visible in the browser, this can be used to log the warning to the console
+/// instead. Like the check itself, the callback is only called in debug builds.
+pub fn on_effect_dependency_limit_exceeded(callback: Option<DependencyLimitCallback>) {
+    #[cfg(debug_assertions)]
+    DEPENDENCY_LIMIT_CALLBACK.with(|x| *x.borrow_mut() = callback);
+    #[cfg(not(debug_assertions))]
+    let _ = callback;
+}
+
+/// Emits a warning if `effect` tracked more dependencies than the limit, but `previous`
+/// dependencies, the ones of its last run, did not.
+#[cfg(debug_assertions)]
+fn check_dependency_limit(
+    effect: &EffectState<'_>,
+    previous: usize,
+    location: &'static std::panic::Location<'static>,
+) {
+    let limit = match DEPENDENCY_LIMIT.with(Cell::get) {
+        Some(limit) if effect.dependencies.len() > limit && previous <= limit => limit,
+        _ => return,
+    };
+    let exceeded = DependencyLimitExceeded {
+        location,
+        #[cfg(feature = "testing")]
+        name: effect.schedule.name,
+        #[cfg(not(feature = "testing"))]
+        name: None,
+        dependencies: effect.dependencies.len(),
+        limit,
+    };
+    match DEPENDENCY_LIMIT_CALLBACK.with(|callback| callback.borrow().clone()) {
+        Some(callback) => callback(&exceeded),
+        None => eprintln!("warning: {exceeded}"),
+    }
+}
+
+/// Returns the names of the effects created with [`Scope::create_effect_named`] in the order in
+/// which they ran on the current thread since the last call to [`reset_effect_run_log`].
+///
+/// _This API requires the following crate features to be activated: `testing`_
+#[cfg(feature = "testing")]
+pub fn effect_run_log() -> Vec<&'static str> {
+    EFFECT_RUN_LOG.with(|log| log.borrow().clone())
+}
+
+/// Clears the [`effect_run_log`] of the current thread.
+///
+/// _This API requires the following crate features to be activated: `testing`_
+#[cfg(feature = "testing")]
+pub fn reset_effect_run_log() {
+    EFFECT_RUN_LOG.with(|log| log.borrow_mut().clear());
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -258,6 +949,43 @@ mod tests {
         });
     }
 
+    #[test]
+    fn effect_tracks_all_signals() {
+        create_scope_immediate(|ctx| {
+            let signals: Vec<&Signal<i32>> = (0..50).map(|_| ctx.create_signal(0)).collect();
+            let fields: Vec<&ReadSignal<i32>> = signals.iter().map(|&signal| &**signal).collect();
+            let fields = ctx.create_ref(fields);
+
+            let runs = ctx.create_ref(Cell::new(0));
+            ctx.create_effect(|| {
+                track_all(fields);
+                runs.set(runs.get() + 1);
+            });
+            let explicit_runs = ctx.create_ref(Cell::new(0));
+            ctx.create_effect(on_all(fields.clone(), || {
+                explicit_runs.set(explicit_runs.get() + 1);
+            }));
+            assert_eq!(runs.get(), 1);
+            assert_eq!(explicit_runs.get(), 1);
+
+            signals[0].set(1);
+            assert_eq!(runs.get(), 2);
+            assert_eq!(explicit_runs.get(), 2);
+
+            signals[49].set(1);
+            assert_eq!(runs.get(), 3);
+            assert_eq!(explicit_runs.get(), 3);
+
+            batch(|| {
+                for signal in &signals {
+                    signal.set(2);
+                }
+            });
+            assert_eq!(runs.get(), 4);
+            assert_eq!(explicit_runs.get(), 4);
+        });
+    }
+
     #[test]
     fn effect_cannot_create_infinite_loop() {
         create_scope_immediate(|ctx| {
@@ -270,6 +998,52 @@ mod tests {
         });
     }
 
+    #[test]
+    fn effect_reruns_once_when_dependency_updated_while_running() {
+        create_scope_immediate(|ctx| {
+            let state = ctx.create_signal(0);
+            let log = ctx.create_signal(Vec::new());
+            let push = |event: &'static str| {
+                let mut tmp = (*log.get_untracked()).clone();
+                tmp.push(event);
+                log.set(tmp);
+            };
+
+            ctx.create_effect(move || {
+                push("start");
+                if *state.get() == 0 {
+                    // Update own dependency to a different value.
+                    state.set(1);
+                }
+                push("end");
+            });
+            assert_eq!(
+                *log.get(),
+                ["start", "end", "start", "end"],
+                "effect should re-run once after completion rather than recursively"
+            );
+            assert_eq!(*state.get(), 1);
+
+            // The effect is still subscribed to `state` after the re-run.
+            state.set(0);
+            assert_eq!(log.get().len(), 8);
+        });
+    }
+
+    #[test]
+    fn effect_does_not_rerun_when_dependency_updated_before_read() {
+        create_scope_immediate(|ctx| {
+            let state = ctx.create_signal(0);
+            let counter = ctx.create_signal(0);
+            ctx.create_effect(|| {
+                counter.set(*counter.get_untracked() + 1);
+                state.set(*state.get_untracked() + 1);
+                state.track();
+            });
+            assert_eq!(*counter.get(), 1);
+        });
+    }
+
     #[test]
     fn effect_should_only_subscribe_once_to_same_signal() {
         create_scope_immediate(|ctx| {
@@ -329,6 +1103,87 @@ mod tests {
         });
     }
 
+    #[test]
+    fn effect_ignores_removed_dependency_updated_while_running() {
+        create_scope_immediate(|ctx| {
+            let condition = ctx.create_signal(true);
+            let state = ctx.create_signal(0);
+            let runs = ctx.create_ref(Cell::new(0));
+            ctx.create_effect(|| {
+                runs.set(runs.get() + 1);
+                if *condition.get() {
+                    state.track();
+                } else {
+                    // `state` is no longer tracked, but the effect is still subscribed to it until
+                    // it returns.
+                    state.set(*state.get_untracked() + 1);
+                }
+            });
+            assert_eq!(runs.get(), 1);
+
+            condition.set(false);
+            assert_eq!(
+                runs.get(),
+                2,
+                "the removed dependency does not re-run the effect"
+            );
+            assert_eq!(*state.get(), 1);
+
+            state.set(2);
+            assert_eq!(runs.get(), 2, "the removed dependency is unsubscribed");
+
+            condition.set(true);
+            assert_eq!(runs.get(), 3);
+            state.set(3);
+            assert_eq!(runs.get(), 4);
+        });
+    }
+
+    #[test]
+    fn effect_only_resubscribes_changed_dependencies() {
+        use crate::signal::SUBSCRIPTION_CHANGES;
+
+        let changes = || SUBSCRIPTION_CHANGES.with(Cell::get);
+        create_scope_immediate(|ctx| {
+            let condition = ctx.create_signal(false);
+            let state1 = ctx.create_signal(0);
+            let state2 = ctx.create_signal(0);
+
+            let counter = ctx.create_signal(0);
+            ctx.create_effect(|| {
+                counter.set(*counter.get_untracked() + 1);
+
+                state1.track();
+                if *condition.get() {
+                    state2.track();
+                }
+            });
+            let initial = changes();
+
+            // The dependencies did not change.
+            state1.set(1);
+            assert_eq!(*counter.get(), 2);
+            assert_eq!(changes(), initial);
+
+            // Only `state2` is subscribed.
+            condition.set(true);
+            assert_eq!(*counter.get(), 3);
+            assert_eq!(changes(), initial + 1);
+
+            state2.set(1);
+            assert_eq!(*counter.get(), 4);
+            assert_eq!(changes(), initial + 1);
+
+            // Only `state2` is unsubscribed.
+            condition.set(false);
+            assert_eq!(*counter.get(), 5);
+            assert_eq!(changes(), initial + 2);
+
+            state2.set(2);
+            assert_eq!(*counter.get(), 5); // not tracked
+        });
+    }
+
     #[test]
     fn outer_effects_run_first() {
         create_scope_immediate(|ctx| {
@@ -357,6 +1212,222 @@ mod tests {
         });
     }
 
+    #[test]
+    fn persistent_effect_in_loop_is_created_once() {
+        create_scope_immediate(|ctx| {
+            let trigger = ctx.create_signal(());
+            let runs = ctx.create_ref(Cell::new(0));
+            ctx.create_effect(|| {
+                trigger.track();
+                // All the iterations share the same call site.
+                for _ in 0..3 {
+                    ctx.create_effect_persistent(|| runs.set(runs.get() + 1));
+                }
+            });
+            assert_eq!(runs.get(), 1);
+
+            trigger.set(());
+            assert_eq!(runs.get(), 1);
+        });
+    }
+
+    #[test]
+    fn persistent_nested_effect_survives_reruns() {
+        create_scope_immediate(|ctx| {
+            let trigger = ctx.create_signal(());
+            let state = ctx.create_signal(0);
+
+            let outer_counter = ctx.create_ref(Cell::new(0));
+            let scoped_runs = ctx.create_ref(Cell::new(0));
+            let persistent_runs = ctx.create_ref(Cell::new(0));
+
+            ctx.create_effect_scoped(|inner| {
+                trigger.track();
+                outer_counter.set(outer_counter.get() + 1);
+
+                inner.create_effect(|| {
+                    state.track();
+                    scoped_runs.set(scoped_runs.get() + 1);
+                });
+
+                ctx.create_effect_persistent(|| {
+                    state.track();
+                    persistent_runs.set(persistent_runs.get() + 1);
+                });
+            });
+            assert_eq!(outer_counter.get(), 1);
+            assert_eq!(scoped_runs.get(), 1);
+            assert_eq!(persistent_runs.get(), 1);
+
+            for _ in 0..3 {
+                trigger.set(());
+            }
+            assert_eq!(outer_counter.get(), 4);
+            assert_eq!(scoped_runs.get(), 4, "the scoped effect is recreated");
+            assert_eq!(
+                persistent_runs.get(),
+                1,
+                "the persistent effect should not be recreated"
+            );
+
+            // The persistent effect is still alive and subscribed to `state`, and only one copy of
+            // it exists.
+            state.set(1);
+            assert_eq!(scoped_runs.get(), 5);
+            assert_eq!(persistent_runs.get(), 2);
+        });
+    }
+
+    #[test]
+    fn persistent_effect_outside_effect_is_not_deduped() {
+        create_scope_immediate(|ctx| {
+            let state = ctx.create_signal(0);
+            let runs = ctx.create_ref(Cell::new(0));
+
+            for _ in 0..2 {
+                ctx.create_effect_persistent(|| {
+                    state.track();
+                    runs.set(runs.get() + 1);
+                });
+            }
+            assert_eq!(runs.get(), 2);
+
+            state.set(1);
+            assert_eq!(runs.get(), 4, "both effects should run");
+        });
+    }
+
+    #[test]
+    fn diamond_dependency_runs_effect_once() {
+        create_scope_immediate(|ctx| {
+            let a = ctx.create_signal(1);
+            let b = ctx.create_memo(|| *a.get() * 2);
+            let c = ctx.create_memo(|| *a.get() * 3);
+
+            let runs = ctx.create_ref(RefCell::new(Vec::new()));
+            ctx.create_effect(|| runs.borrow_mut().push(*b.get() + *c.get()));
+            assert_eq!(*runs.borrow(), [5]);
+
+            a.set(2);
+            assert_eq!(*runs.borrow(), [5, 10]);
+
+            a.set(3);
+            assert_eq!(*runs.borrow(), [5, 10, 15]);
+        });
+    }
+
+    #[test]
+    fn uneven_diamond_dependency_runs_effect_once() {
+        create_scope_immediate(|ctx| {
+            let a = ctx.create_signal(1);
+            let b = ctx.create_memo(|| *a.get() + 1);
+            let c = ctx.create_memo(|| *b.get() * 10);
+
+            // Depends on `a` directly and on `a` through two levels of memos.
+            let runs = ctx.create_ref(RefCell::new(Vec::new()));
+            ctx.create_effect(|| runs.borrow_mut().push((*a.get(), *c.get())));
+            assert_eq!(*runs.borrow(), [(1, 20)]);
+
+            a.set(2);
+            assert_eq!(*runs.borrow(), [(1, 20), (2, 30)]);
+        });
+    }
+
+    #[test]
+    fn effects_run_in_priority_order() {
+        create_scope_immediate(|ctx| {
+            let state = ctx.create_signal(0);
+            let log = ctx.create_ref(RefCell::new(Vec::new()));
+
+            // Without priorities, these effects would run in reverse creation order.
+            ctx.create_effect_with_priority(-10, || {
+                state.track();
+                log.borrow_mut().push("derived");
+            });
+            ctx.create_effect_with_priority(10, || {
+                state.track();
+                log.borrow_mut().push("dom");
+            });
+            ctx.create_effect(|| {
+                state.track();
+                log.borrow_mut().push("default");
+            });
+            log.borrow_mut().clear();
+
+            state.set(1);
+            assert_eq!(*log.borrow(), ["derived", "default", "dom"]);
+        });
+    }
+
+    #[test]
+    fn effects_run_in_phase_order() {
+        create_scope_immediate(|ctx| {
+            let state = ctx.create_signal(0);
+            let derived = ctx.create_memo(|| *state.get());
+            let log = ctx.create_ref(RefCell::new(Vec::new()));
+
+            ctx.create_effect_phased(EffectPhase::Write, || {
+                state.track();
+                log.borrow_mut().push("write");
+            });
+            ctx.create_effect_phased(EffectPhase::Read, || {
+                derived.track();
+                log.borrow_mut().push("read");
+            });
+            ctx.create_effect_phased(EffectPhase::Write, || {
+                derived.track();
+                log.borrow_mut().push("write");
+            });
+            ctx.create_effect_phased(EffectPhase::Read, || {
+                state.track();
+                log.borrow_mut().push("read");
+            });
+            log.borrow_mut().clear();
+
+            state.set(1);
+            assert_eq!(*log.borrow(), ["read", "read", "write", "write"]);
+        });
+    }
+
+    #[cfg(feature = "devtools")]
+    #[test]
+    fn effect_current_trigger() {
+        create_scope_immediate(|ctx| {
+            let a = ctx.create_signal(0);
+            let b = ctx.create_signal(0);
+            let triggers = ctx.create_ref(RefCell::new(Vec::new()));
+            ctx.create_effect(|| {
+                a.track();
+                b.track();
+                triggers.borrow_mut().push(current_trigger());
+            });
+
+            b.set(1);
+            a.set(1);
+            assert_eq!(*triggers.borrow(), [None, Some(b.id()), Some(a.id())]);
+            // The trigger is cleared after the effect has run.
+            assert_eq!(current_trigger(), None);
+        });
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn effect_run_log_records_order() {
+        create_scope_immediate(|ctx| {
+            let a = ctx.create_signal(0);
+            let b = ctx.create_signal(0);
+            ctx.create_effect_named("copy", || b.set(*a.get()));
+            ctx.create_effect_named("read_b", || b.track());
+            ctx.create_effect_named("read_a", || a.track());
+            assert_eq!(effect_run_log(), ["copy", "read_b", "read_a"]);
+
+            reset_effect_run_log();
+            a.set(1);
+            // "read_b" depends on "copy" and therefore runs after it.
+            assert_eq!(effect_run_log(), ["read_a", "copy", "read_b"]);
+        });
+    }
+
     #[test]
     fn destroy_effects_on_scope_dispose() {
         create_scope_immediate(|ctx| {
@@ -391,7 +1462,7 @@ mod tests {
             let parent: &Signal<Option<*const ()>> = ctx.create_signal(None);
             ctx.create_effect_scoped(|ctx| {
                 trigger.track();
-                let p = ctx.parent.unwrap();
+                let p = ctx.parent.get().unwrap();
                 parent.set(Some(p as *const ()));
             });
             assert_eq!(
@@ -408,6 +1479,101 @@ mod tests {
         });
     }
 
+    #[test]
+    fn effect_with_cleanup() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        create_scope_immediate(|ctx| {
+            let state = ctx.create_signal(0);
+            ctx.create_effect_with_cleanup({
+                let log = Rc::clone(&log);
+                move |cleanup| {
+                    let run = *state.get();
+                    log.borrow_mut().push(format!("run {run}"));
+                    let log = Rc::clone(&log);
+                    cleanup.on_cleanup(move || log.borrow_mut().push(format!("cleanup {run}")));
+                }
+            });
+            state.set(1);
+            state.set(2);
+        });
+        assert_eq!(
+            *log.borrow(),
+            [
+                "run 0",
+                "cleanup 0",
+                "run 1",
+                "cleanup 1",
+                "run 2",
+                "cleanup 2"
+            ],
+            "cleanups run before the next run and on dispose"
+        );
+    }
+
+    #[test]
+    fn capture_dependencies_does_not_track() {
+        create_scope_immediate(|ctx| {
+            let a = ctx.create_signal(0);
+            let b = ctx.create_signal(0);
+            let runs = ctx.create_ref(Cell::new(0));
+            let captured = ctx.create_ref(RefCell::new(None));
+            ctx.create_effect(|| {
+                runs.set(runs.get() + 1);
+                let (_, dependencies) = capture_dependencies(|| {
+                    a.track();
+                    b.track();
+                });
+                // Only `a` is tracked by the effect.
+                a.track();
+                *captured.borrow_mut() = Some(dependencies);
+            });
+            b.set(1);
+            assert_eq!(runs.get(), 1);
+            a.set(1);
+            assert_eq!(runs.get(), 2);
+
+            let dependencies = captured.take().unwrap();
+            let tracked = ctx.create_ref(Cell::new(0));
+            ctx.create_effect(move || {
+                dependencies.track();
+                tracked.set(tracked.get() + 1);
+            });
+            b.set(2);
+            assert_eq!(tracked.get(), 2);
+        });
+    }
+
+    #[test]
+    fn effect_fallible() {
+        create_scope_immediate(|ctx| {
+            let state = ctx.create_signal(1);
+            let values = ctx.create_ref(RefCell::new(Vec::new()));
+            let errors = ctx.create_ref(RefCell::new(Vec::new()));
+            ctx.create_effect_fallible(
+                || {
+                    let value = *state.get();
+                    if value < 0 {
+                        return Err(format!("negative: {value}"));
+                    }
+                    values.borrow_mut().push(value);
+                    Ok(())
+                },
+                |err| errors.borrow_mut().push(err),
+            );
+            assert_eq!(*values.borrow(), [1]);
+
+            state.set(-1);
+            assert_eq!(*errors.borrow(), ["negative: -1"]);
+
+            // Still tracked after an error.
+            state.set(2);
+            state.set(-3);
+            state.set(4);
+            assert_eq!(*values.borrow(), [1, 2, 4]);
+            assert_eq!(*errors.borrow(), ["negative: -1", "negative: -3"]);
+        });
+    }
+
     #[test]
     fn effect_scoped_subscribing_to_own_signal() {
         create_scope_immediate(|ctx| {
@@ -422,6 +1588,91 @@ mod tests {
         });
     }
 
+    #[test]
+    fn effect_scoped_disposes_previous_scope_first() {
+        create_scope_immediate(|ctx| {
+            let trigger = ctx.create_signal(());
+            let log = ctx.create_ref(RefCell::new(Vec::new()));
+            let runs = ctx.create_ref(Cell::new(0));
+            ctx.create_effect_scoped(move |ctx| {
+                trigger.track();
+                runs.set(runs.get() + 1);
+                let run = runs.get();
+                log.borrow_mut().push(format!("run {run}"));
+                ctx.create_effect(move || {
+                    trigger.track();
+                    log.borrow_mut().push(format!("nested {run}"));
+                });
+                ctx.on_cleanup(move || log.borrow_mut().push(format!("cleanup {run}")));
+            });
+
+            trigger.set(());
+            trigger.set(());
+            // The nested effect of the previous run is disposed before it can be triggered again.
+            assert_eq!(
+                *log.borrow(),
+                [
+                    "run 1",
+                    "nested 1",
+                    "cleanup 1",
+                    "run 2",
+                    "nested 2",
+                    "cleanup 2",
+                    "run 3",
+                    "nested 3",
+                ]
+            );
+        });
+    }
+
+    #[test]
+    fn effect_scoped_nested_self_subscriptions() {
+        create_scope_immediate(|ctx| {
+            let trigger = ctx.create_signal(0);
+            let shared = ctx.create_signal(0);
+            let outer_runs = ctx.create_ref(Cell::new(0));
+            let nested_runs = ctx.create_ref(Cell::new(0));
+            let cleanups = ctx.create_ref(Cell::new(0));
+
+            ctx.create_effect_scoped(|ctx| {
+                outer_runs.set(outer_runs.get() + 1);
+                let value = *trigger.get();
+                let local = ctx.create_signal(0);
+                ctx.create_effect(|| {
+                    nested_runs.set(nested_runs.get() + 1);
+                    // The nested effect subscribes to a signal that it also writes...
+                    local.set(*local.get() + 1);
+                    // ...and to a signal written by the outer effect.
+                    shared.track();
+                });
+                // The outer effect writes to a signal the nested effect depends on and subscribes
+                // to a signal of the child scope, which is disposed on the next run.
+                shared.set(value);
+                let own = ctx.create_signal(());
+                own.track();
+                ctx.on_cleanup(|| cleanups.set(cleanups.get() + 1));
+            });
+            // The nested effect is re-run once every time it runs because it updates its own
+            // dependency. It runs when it is created and when the outer effect writes to `shared`.
+            assert_eq!(outer_runs.get(), 1);
+            assert_eq!(nested_runs.get(), 4);
+            assert_eq!(cleanups.get(), 0);
+
+            for i in 1..=10 {
+                trigger.set(i);
+                assert_eq!(outer_runs.get(), i as usize + 1);
+                assert_eq!(cleanups.get(), i as usize);
+                assert_eq!(nested_runs.get(), (i as usize + 1) * 4);
+                assert_eq!(*shared.get(), i);
+            }
+
+            // Only the nested effect of the last run is still alive and runs twice.
+            shared.set(-1);
+            assert_eq!(outer_runs.get(), 11);
+            assert_eq!(nested_runs.get(), 11 * 4 + 2);
+        });
+    }
+
     #[test]
     fn effect_do_not_subscribe_to_destroyed_signal() {
         create_scope_immediate(|ctx| {
@@ -437,4 +1688,102 @@ mod tests {
             trigger.set(());
         });
     }
+
+    #[test]
+    fn untrack_inside_effect() {
+        create_scope_immediate(|ctx| {
+            let state = ctx.create_signal(0);
+            let untracked = ctx.create_signal(0);
+            let inner = ctx.create_signal(0);
+
+            let outer_counter = ctx.create_signal(0);
+            let inner_counter = ctx.create_signal(0);
+            ctx.create_effect_scoped(move |ctx| {
+                outer_counter.set(*outer_counter.get_untracked() + 1);
+                untrack(|| {
+                    untracked.track();
+                    // The effect created inside `untrack` still tracks its own dependencies.
+                    ctx.create_effect(move || {
+                        inner_counter.set(*inner_counter.get_untracked() + 1);
+                        inner.track();
+                    });
+                });
+                state.track();
+            });
+            assert_eq!(*outer_counter.get(), 1);
+            assert_eq!(*inner_counter.get(), 1);
+
+            untracked.set(1);
+            assert_eq!(*outer_counter.get(), 1);
+
+            inner.set(1);
+            assert_eq!(*outer_counter.get(), 1);
+            assert_eq!(*inner_counter.get(), 2);
+
+            state.set(1);
+            assert_eq!(*outer_counter.get(), 2);
+            assert_eq!(*inner_counter.get(), 3);
+        });
+    }
+
+    #[test]
+    fn untrack_restores_effects_on_panic() {
+        create_scope_immediate(|ctx| {
+            let state = ctx.create_signal(0);
+
+            let counter = ctx.create_signal(0);
+            ctx.create_effect(|| {
+                counter.set(*counter.get_untracked() + 1);
+                let result = std::panic::catch_unwind(|| untrack(|| panic!("untracked panic")));
+                assert!(result.is_err());
+                // The effect is still tracking after the panic.
+                state.track();
+            });
+            assert_eq!(*counter.get(), 1);
+
+            state.set(1);
+            assert_eq!(*counter.get(), 2);
+        });
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn effect_dependency_limit() {
+        let warnings = Rc::new(RefCell::new(Vec::new()));
+        set_effect_dependency_limit(Some(10));
+        on_effect_dependency_limit_exceeded(Some(Rc::new({
+            let warnings = Rc::clone(&warnings);
+            move |exceeded: &DependencyLimitExceeded| {
+                assert_eq!(exceeded.location.file(), file!());
+                assert_eq!(exceeded.limit, 10);
+                warnings.borrow_mut().push(exceeded.dependencies);
+            }
+        })));
+
+        create_scope_immediate(|ctx| {
+            let signals = ctx.create_ref((0..20).map(|_| ctx.create_signal(0)).collect::<Vec<_>>());
+            let count = ctx.create_signal(5);
+            ctx.create_effect(|| {
+                for signal in &signals[..*count.get()] {
+                    signal.track();
+                }
+            });
+            assert!(warnings.borrow().is_empty());
+
+            count.set(20);
+            // The effect also depends on `count`.
+            assert_eq!(*warnings.borrow(), [21]);
+
+            // Only warn when going over the limit.
+            count.set(15);
+            assert_eq!(*warnings.borrow(), [21]);
+
+            count.set(5);
+            count.set(12);
+            assert_eq!(*warnings.borrow(), [21, 13]);
+        });
+
+        set_effect_dependency_limit(None);
+        on_effect_dependency_limit_exceeded(None);
+    }
 }
@@ -0,0 +1,225 @@
+//! Signals with undo and redo.
+
+use std::collections::VecDeque;
+
+use crate::*;
+
+/// A [`Signal`] that keeps a bounded history of its previous values so that changes can be undone
+/// and redone.
+///
+/// Created with [`Scope::create_history_signal`]. The current value can be read through the
+/// [`ReadSignal`] methods, which are available via [`Deref`].
+pub struct HistorySignal<T> {
+    signal: Signal<T>,
+    /// Previous values, with the most recent one at the back.
+    undo: RefCell<VecDeque<T>>,
+    /// Values that were undone, with the most recently undone one at the back.
+    redo: RefCell<Vec<T>>,
+    /// The maximum number of values in `undo`.
+    capacity: usize,
+}
+
+impl<T: Clone> HistorySignal<T> {
+    /// Set the current value of the state.
+    ///
+    /// The previous value is added to the undo history, evicting the oldest value if the history
+    /// is full, and the redo history is cleared.
+    #[track_caller]
+    pub fn set(&self, value: T) {
+        self.push_undo();
+        self.redo.borrow_mut().clear();
+        self.signal.set(value);
+    }
+
+    /// Restore the previous value of the state. Does nothing if there is nothing to undo.
+    ///
+    /// The current value is added to the redo history.
+    #[track_caller]
+    pub fn undo(&self) {
+        let previous = self.undo.borrow_mut().pop_back();
+        if let Some(previous) = previous {
+            self.redo
+                .borrow_mut()
+                .push((*self.signal.get_untracked()).clone());
+            self.signal.set(previous);
+        }
+    }
+
+    /// Restore the last value that was undone. Does nothing if there is nothing to redo.
+    ///
+    /// The current value is added to the undo history.
+    #[track_caller]
+    pub fn redo(&self) {
+        let next = self.redo.borrow_mut().pop();
+        if let Some(next) = next {
+            self.push_undo();
+            self.signal.set(next);
+        }
+    }
+
+    /// Adds the current value to the undo history.
+    fn push_undo(&self) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut undo = self.undo.borrow_mut();
+        while undo.len() >= self.capacity {
+            undo.pop_front();
+        }
+        undo.push_back((*self.signal.get_untracked()).clone());
+    }
+}
+
+impl<T> HistorySignal<T> {
+    /// Returns `true` if there is a previous value to [`undo`](Self::undo) to.
+    ///
+    /// This tracks the signal so that it can be used inside effects, for instance to disable an
+    /// undo button.
+    pub fn can_undo(&self) -> bool {
+        self.signal.track();
+        !self.undo.borrow().is_empty()
+    }
+
+    /// Returns `true` if there is an undone value to [`redo`](Self::redo).
+    ///
+    /// This tracks the signal so that it can be used inside effects, for instance to disable a
+    /// redo button.
+    pub fn can_redo(&self) -> bool {
+        self.signal.track();
+        !self.redo.borrow().is_empty()
+    }
+}
+
+impl<T> Deref for HistorySignal<T> {
+    type Target = ReadSignal<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.signal
+    }
+}
+
+impl<'a> Scope<'a> {
+    /// Create a new [`HistorySignal`] with the specified initial value. At most `capacity`
+    /// previous values are kept for [`undo`](HistorySignal::undo).
+    ///
+    /// # Example
+    /// ```
+    /// # use sycamore_reactive::*;
+    /// # create_scope_immediate(|ctx| {
+    /// let text = ctx.create_history_signal(String::new(), 100);
+    /// text.set("Hello".to_string());
+    /// text.set("Hello World".to_string());
+    ///
+    /// text.undo();
+    /// assert_eq!(*text.get(), "Hello");
+    /// text.redo();
+    /// assert_eq!(*text.get(), "Hello World");
+    /// # });
+    /// ```
+    pub fn create_history_signal<T: Clone + 'a>(
+        &'a self,
+        value: T,
+        capacity: usize,
+    ) -> &'a HistorySignal<T> {
+        self.create_ref(HistorySignal {
+            signal: Signal::new(value),
+            // The history grows as values are pushed and is bounded by `push_undo`. Nothing is
+            // allocated up front since `capacity` is only an upper bound.
+            undo: RefCell::new(VecDeque::new()),
+            redo: RefCell::new(Vec::new()),
+            capacity,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history_signal() {
+        create_scope_immediate(|ctx| {
+            let state = ctx.create_history_signal(0, 10);
+            assert!(!state.can_undo());
+            assert!(!state.can_redo());
+
+            state.set(1);
+            state.set(2);
+            state.set(3);
+            assert_eq!(*state.get(), 3);
+            assert!(state.can_undo());
+            assert!(!state.can_redo());
+
+            state.undo();
+            assert_eq!(*state.get(), 2);
+            state.undo();
+            assert_eq!(*state.get(), 1);
+            assert!(state.can_undo());
+            assert!(state.can_redo());
+
+            state.redo();
+            assert_eq!(*state.get(), 2);
+            assert!(state.can_redo());
+
+            // Setting a new value clears the redo history.
+            state.set(4);
+            assert!(!state.can_redo());
+            state.redo();
+            assert_eq!(*state.get(), 4);
+
+            state.undo();
+            state.undo();
+            state.undo();
+            assert_eq!(*state.get(), 0);
+            assert!(!state.can_undo());
+            state.undo();
+            assert_eq!(*state.get(), 0);
+        });
+    }
+
+    #[test]
+    fn history_signal_capacity() {
+        create_scope_immediate(|ctx| {
+            let state = ctx.create_history_signal(0, 2);
+            state.set(1);
+            state.set(2);
+            state.set(3);
+
+            state.undo();
+            state.undo();
+            assert_eq!(*state.get(), 1);
+            assert!(!state.can_undo(), "0 was evicted from the history");
+        });
+    }
+
+    #[test]
+    fn history_signal_large_capacity() {
+        create_scope_immediate(|ctx| {
+            // The capacity is only an upper bound and is not allocated up front.
+            let state = ctx.create_history_signal(0, usize::MAX);
+            state.set(1);
+            state.undo();
+            assert_eq!(*state.get(), 0);
+        });
+    }
+
+    #[test]
+    fn history_signal_notifies_once() {
+        create_scope_immediate(|ctx| {
+            let state = ctx.create_history_signal(0, 10);
+            let counter = ctx.create_signal(0);
+            ctx.create_effect(|| {
+                state.track();
+                counter.set(*counter.get_untracked() + 1);
+            });
+            assert_eq!(*counter.get(), 1);
+
+            state.set(1);
+            assert_eq!(*counter.get(), 2);
+            state.undo();
+            assert_eq!(*counter.get(), 3);
+            state.redo();
+            assert_eq!(*counter.get(), 4);
+        });
+    }
+}
@@ -2,12 +2,117 @@
 
 use std::cell::RefCell;
 use std::collections::HashMap;
+use std::fmt;
 use std::hash::Hash;
 use std::mem::{self, MaybeUninit};
 use std::rc::Rc;
 
 use crate::*;
 
+/// Counters describing the work done by [`map_keyed`](Scope::map_keyed) to reconcile a list, as
+/// reported by [`map_keyed_with_stats`](Scope::map_keyed_with_stats).
+///
+/// Every item of the new list is counted exactly once as either `created`, `moved` or `reused`.
+/// An unexpectedly high number of created or moved items usually means the keys are not stable.
+///
+/// The counters are only maintained when the `profiling` feature is enabled.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ReconcileStats {
+    /// The number of times the list was reconciled.
+    pub runs: usize,
+    /// The number of items that were mapped in a new scope.
+    pub created: usize,
+    /// The number of items whose scope was disposed because their key was removed.
+    pub removed: usize,
+    /// The number of items that were kept but changed index.
+    pub moved: usize,
+    /// The number of items that were kept at the same index.
+    pub reused: usize,
+}
+
+#[cfg(feature = "profiling")]
+impl ReconcileStats {
+    fn add(&mut self, other: Self) {
+        self.runs += other.runs;
+        self.created += other.created;
+        self.removed += other.removed;
+        self.moved += other.moved;
+        self.reused += other.reused;
+    }
+}
+
+/// Renames the keys of the items of a list mapped with
+/// [`map_keyed_with_renames`](Scope::map_keyed_with_renames), so that an item whose key changed is
+/// treated as the same item instead of being removed and mapped again.
+///
+/// This is useful for optimistic updates: an item can be added to the list with a temporary key
+/// while it is being saved, and get the key assigned by the server once it is confirmed, without
+/// re-creating its view and losing its state. If the item is rolled back instead, it is simply
+/// removed from the list.
+pub struct KeyRenames<K>(RefCell<HashMap<K, K>>);
+
+impl<K: Eq + Hash> KeyRenames<K> {
+    /// Creates an empty set of renames.
+    pub fn new() -> Self {
+        Self(RefCell::new(HashMap::new()))
+    }
+
+    /// Treats the item with the key `from` as the same item as the one with the key `to` the next
+    /// time the list is updated. The renames are forgotten after every update, whether the items
+    /// were found or not, so this should be called right before updating the list.
+    ///
+    /// `to` should not be the key of another item of the list.
+    pub fn rename(&self, from: K, to: K) {
+        self.0.borrow_mut().insert(from, to);
+    }
+}
+
+impl<K: Eq + Hash> Default for KeyRenames<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K> fmt::Debug for KeyRenames<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyRenames")
+            .field("len", &self.0.borrow().len())
+            .finish()
+    }
+}
+
+/// Adds to a [`ReconcileStats`] counter. Compiles to nothing without the `profiling` feature.
+macro_rules! count {
+    ($stats:ident.$field:ident += $n:expr) => {
+        #[cfg(feature = "profiling")]
+        {
+            $stats.$field += $n;
+        }
+    };
+}
+
+/// Checks that `keys` does not contain the same key for two distinct items of `list`. Duplicate
+/// keys are a common mistake which would otherwise silently cause items to be dropped or
+/// duplicated. The same item can still appear several times in the list.
+///
+/// In debug builds, a duplicate key panics. In release builds, a warning is printed instead and the
+/// list is still reconciled, although the items with the duplicate key might be mapped incorrectly.
+fn check_unique_keys<T: PartialEq, K: Eq + Hash + fmt::Debug>(list: &[T], keys: &[Rc<K>]) {
+    let mut indices = HashMap::with_capacity(list.len());
+    for (i, (item, key)) in list.iter().zip(keys).enumerate() {
+        if let Some(first) = indices.insert(&**key, i).filter(|&j| list[j] != *item) {
+            let msg = format!(
+                "duplicate key {key:?} returned by `key_fn` for the items at indices {first} and {i}"
+            );
+            if cfg!(debug_assertions) {
+                panic!("{msg}");
+            }
+            eprintln!("warning: {msg}");
+            return;
+        }
+    }
+}
+
 impl<'a> Scope<'a> {
     /// Function that maps a `Vec` to another `Vec` via a map function. The mapped `Vec` is lazy
     /// computed, meaning that it's value will only be updated when requested. Modifications to the
@@ -15,12 +120,32 @@ impl<'a> Scope<'a> {
     ///
     /// This function is the underlying utility behind `Keyed`.
     ///
+    /// Each item is mapped inside its own child scope. Items whose key is still present after an
+    /// update are only moved: their scope (including any signals, effects and cleanup callbacks
+    /// created inside `map_fn`) is kept alive and `map_fn` is not called again. Scopes are only
+    /// disposed when their key is removed from the list.
+    ///
+    /// Adding items to the front of the list, as in a feed where new entries appear at the top,
+    /// is special cased: the new items are mapped and the existing ones are shifted without being
+    /// diffed.
+    ///
     /// # Params
     /// * `list` - The list to be mapped. The list must be a [`ReadSignal`] (obtained from a
     ///   [`Signal`]) and therefore reactive.
     /// * `map_fn` - A closure that maps from the input type to the output type.
     /// * `key_fn` - A closure that returns an _unique_ key to each entry.
     ///
+    /// # Key purity
+    /// Keys are cached alongside the items and `key_fn` is only called for items that were not in
+    /// the previous list at the same index, counted either from the start or from the end of the
+    /// list. Appending, prepending, inserting or removing items therefore only computes the keys
+    /// of the new items. This assumes that `key_fn` is a pure function of the item: two items
+    /// that are equal must always have the same key.
+    ///
+    /// # Panics
+    /// In debug builds, panics if `key_fn` returns the same key for two items of the list that are
+    /// not equal.
+    ///
     ///  _Credits: Based on TypeScript implementation in <https://github.com/solidjs/solid>_
     pub fn map_keyed<T, K, U>(
         &'a self,
@@ -29,14 +154,100 @@ impl<'a> Scope<'a> {
         key_fn: impl Fn(&T) -> K + 'a,
     ) -> &'a ReadSignal<Vec<U>>
     where
-        T: Eq + Clone + 'a,
-        K: Eq + Hash,
+        T: PartialEq + Clone + 'a,
+        K: Eq + Hash + fmt::Debug + 'a,
+        U: Clone + 'a,
+    {
+        self._map_keyed(list, map_fn, key_fn, None, None)
+    }
+
+    /// Same as [`map_keyed`](Self::map_keyed) but also accumulates [`ReconcileStats`] into
+    /// `stats` every time the list is diffed.
+    ///
+    /// The stats are only recorded when the `profiling` feature is enabled. Otherwise, `stats` is
+    /// never updated.
+    pub fn map_keyed_with_stats<T, K, U>(
+        &'a self,
+        list: &'a ReadSignal<Vec<T>>,
+        map_fn: impl for<'child_lifetime> Fn(BoundedScopeRef<'child_lifetime, 'a>, T) -> U + 'a,
+        key_fn: impl Fn(&T) -> K + 'a,
+        stats: &'a Signal<ReconcileStats>,
+    ) -> &'a ReadSignal<Vec<U>>
+    where
+        T: PartialEq + Clone + 'a,
+        K: Eq + Hash + fmt::Debug + 'a,
+        U: Clone + 'a,
+    {
+        self._map_keyed(list, map_fn, key_fn, Some(stats), None)
+    }
+
+    /// Same as [`map_keyed`](Self::map_keyed) but the keys of the items can be changed with
+    /// `renames` without re-creating their scopes. See [`KeyRenames`].
+    ///
+    /// # Example
+    /// ```
+    /// # use std::rc::Rc;
+    /// # use sycamore_reactive::*;
+    /// # create_scope_immediate(|ctx| {
+    /// #[derive(Clone, PartialEq)]
+    /// struct Todo {
+    ///     id: i64,
+    ///     text: &'static str,
+    /// }
+    ///
+    /// let todos = ctx.create_signal(Vec::<Todo>::new());
+    /// let renames = ctx.create_ref(KeyRenames::new());
+    /// let items = ctx.map_keyed_with_renames(
+    ///     todos,
+    ///     |_, todo| Rc::new(todo.text),
+    ///     |todo| todo.id,
+    ///     renames,
+    /// );
+    ///
+    /// // Add the item optimistically with a temporary id...
+    /// todos.set(vec![Todo { id: -1, text: "Buy milk" }]);
+    /// let item = Rc::clone(&items.get()[0]);
+    ///
+    /// // ...and replace it once the server assigned it an id.
+    /// renames.rename(-1, 42);
+    /// todos.set(vec![Todo { id: 42, text: "Buy milk" }]);
+    /// assert!(Rc::ptr_eq(&item, &items.get()[0]));
+    /// # });
+    /// ```
+    pub fn map_keyed_with_renames<T, K, U>(
+        &'a self,
+        list: &'a ReadSignal<Vec<T>>,
+        map_fn: impl for<'child_lifetime> Fn(BoundedScopeRef<'child_lifetime, 'a>, T) -> U + 'a,
+        key_fn: impl Fn(&T) -> K + 'a,
+        renames: &'a KeyRenames<K>,
+    ) -> &'a ReadSignal<Vec<U>>
+    where
+        T: PartialEq + Clone + 'a,
+        K: Eq + Hash + fmt::Debug + 'a,
+        U: Clone + 'a,
+    {
+        self._map_keyed(list, map_fn, key_fn, None, Some(renames))
+    }
+
+    #[cfg_attr(not(feature = "profiling"), allow(unused_variables))]
+    fn _map_keyed<T, K, U>(
+        &'a self,
+        list: &'a ReadSignal<Vec<T>>,
+        map_fn: impl for<'child_lifetime> Fn(BoundedScopeRef<'child_lifetime, 'a>, T) -> U + 'a,
+        key_fn: impl Fn(&T) -> K + 'a,
+        stats: Option<&'a Signal<ReconcileStats>>,
+        renames: Option<&'a KeyRenames<K>>,
+    ) -> &'a ReadSignal<Vec<U>>
+    where
+        T: PartialEq + Clone + 'a,
+        K: Eq + Hash + fmt::Debug + 'a,
         U: Clone + 'a,
     {
         let map_fn = Rc::new(map_fn);
 
         // Previous state used for diffing.
         let mut items = Rc::new(Vec::new());
+        let mut keys: Vec<Rc<K>> = Vec::new();
         let mut mapped: Vec<U> = Vec::new();
         let mut disposers: Vec<Option<ScopeDisposer<'a>>> = Vec::new();
 
@@ -45,14 +256,48 @@ impl<'a> Scope<'a> {
         // Diff and update signal each time list is updated.
         self.create_effect(move || {
             let new_items = list.get();
+            // Rename the cached keys first, so that renamed items are found in the new list.
+            if let Some(renames) = renames {
+                let mut renames = renames.0.take();
+                if !renames.is_empty() {
+                    for key in &mut keys {
+                        if let Some(to) = renames.remove(&**key) {
+                            *key = Rc::new(to);
+                        }
+                    }
+                }
+            }
+            // Reuse the cached key of an equal item at the same index, aligned either to the start
+            // or to the end of the list.
+            let new_keys: Vec<Rc<K>> = new_items
+                .iter()
+                .enumerate()
+                .map(|(j, item)| {
+                    let from_end = (j + items.len()).checked_sub(new_items.len());
+                    [Some(j), from_end]
+                        .into_iter()
+                        .flatten()
+                        .find(|&i| items.get(i) == Some(item))
+                        .map(|i| Rc::clone(&keys[i]))
+                        .unwrap_or_else(|| Rc::new(key_fn(item)))
+                })
+                .collect();
+            check_unique_keys(&new_items, &new_keys);
+            #[cfg(feature = "profiling")]
+            let mut run = ReconcileStats {
+                runs: 1,
+                ..Default::default()
+            };
             if new_items.is_empty() {
                 // Fast path for removing all items.
+                count!(run.removed += disposers.len());
                 for dis in mem::take(&mut disposers) {
                     unsafe { dis.unwrap().dispose(); }
                 }
                 mapped = Vec::new();
             } else if items.is_empty() {
                 // Fast path for new create.
+                count!(run.created += new_items.len());
                 // TODO: do not clone T
                 for new_item in new_items.iter().cloned() {
                     let tmp = Rc::new(RefCell::new(None));
@@ -68,6 +313,22 @@ impl<'a> Scope<'a> {
                     mapped.push(tmp.borrow().clone().unwrap());
                     disposers.push(Some(new_disposer));
                 }
+            } else if let Some(prepended) = find_prepend(&items, &new_items) {
+                // Fast path for items prepended to the list. Only the new items are mapped, the
+                // other items are shifted without looking up their keys.
+                count!(run.created += prepended);
+                count!(run.moved += items.len());
+                let mut new_mapped = Vec::with_capacity(prepended);
+                let mut new_disposers = Vec::with_capacity(prepended);
+                for new_item in new_items[..prepended].iter().cloned() {
+                    let mut value = None;
+                    let new_disposer =
+                        self.create_child_scope(|ctx| value = Some(map_fn(ctx, new_item)));
+                    new_mapped.push(value.unwrap());
+                    new_disposers.push(Some(new_disposer));
+                }
+                mapped.splice(0..0, new_mapped);
+                disposers.splice(0..0, new_disposers);
             } else {
                 debug_assert!(
                     !new_items.is_empty() && !items.is_empty(),
@@ -90,6 +351,7 @@ impl<'a> Scope<'a> {
                     .zip(new_items.iter())
                     .position(|(a, b)| a != b)
                     .unwrap_or(min_len);
+                count!(run.reused += start);
                 debug_assert!(
                     (items.get(start).is_none() && new_items.get(start).is_none())
                         || (items.get(start) != new_items.get(start)),
@@ -104,6 +366,11 @@ impl<'a> Scope<'a> {
                 while end > start && new_end > start && items[end - 1] == new_items[new_end - 1] {
                     end -= 1;
                     new_end -= 1;
+                    if end == new_end {
+                        count!(run.reused += 1);
+                    } else {
+                        count!(run.moved += 1);
+                    }
                     temp[new_end] = Some(mapped[end].clone());
                     temp_disposers[new_end] = disposers[end].take();
                 }
@@ -125,24 +392,30 @@ impl<'a> Scope<'a> {
                 // always None.
                 let mut new_indices_next = vec![None; new_end - start];
                 for j in (start..new_end).rev() {
-                    let item = &new_items[j];
-                    let i = new_indices.get(&key_fn(item));
+                    let key = &*new_keys[j];
+                    let i = new_indices.get(key);
                     new_indices_next[j - start] = i.copied();
-                    new_indices.insert(key_fn(item), j);
+                    new_indices.insert(key, j);
                 }
 
                 // 1) Step through old items and see if they can be found in new set; if so, mark
                 // them as moved.
                 for i in start..end {
-                    let item = &items[i];
-                    if let Some(j) = new_indices.get(&key_fn(item)).copied() {
+                    let key = &*keys[i];
+                    if let Some(j) = new_indices.get(key).copied() {
                         // Moved. j is index of item in new_items.
+                        if i == j {
+                            count!(run.reused += 1);
+                        } else {
+                            count!(run.moved += 1);
+                        }
                         temp[j] = Some(mapped[i].clone());
                         temp_disposers[j] = disposers[i].take();
                         new_indices_next[j - start]
-                            .and_then(|j| new_indices.insert(key_fn(item), j));
+                            .and_then(|j| new_indices.insert(key, j));
                     } else {
                         // Create new.
+                        count!(run.removed += 1);
                         unsafe { disposers[i].take().unwrap().dispose(); }
                     }
                 }
@@ -162,6 +435,7 @@ impl<'a> Scope<'a> {
                         }
                     } else {
                         // Create new value.
+                        count!(run.created += 1);
                         let tmp = Rc::new(RefCell::new(None));
                         let new_disposer = self.create_child_scope({
                             let tmp = Rc::clone(&tmp);
@@ -191,10 +465,18 @@ impl<'a> Scope<'a> {
 
             // 4) Save a copy of the mapped items for the next update.
             items = Rc::clone(&new_items);
-            debug_assert!([items.len(), mapped.len(), disposers.len()]
+            keys = new_keys;
+            debug_assert!([items.len(), keys.len(), mapped.len(), disposers.len()]
                 .iter()
                 .all(|l| *l == new_items.len()));
 
+            #[cfg(feature = "profiling")]
+            if let Some(stats) = stats {
+                let mut total = *stats.get_untracked();
+                total.add(run);
+                stats.set(total);
+            }
+
             // 5) Update signal to trigger updates.
             signal.set(mapped.clone());
         });
@@ -220,6 +502,37 @@ impl<'a> Scope<'a> {
         list: &'a ReadSignal<Vec<T>>,
         map_fn: impl for<'child_lifetime> Fn(BoundedScopeRef<'child_lifetime, 'a>, T) -> U + 'a,
     ) -> &'a ReadSignal<Vec<U>>
+    where
+        T: PartialEq + Clone,
+        U: Clone + 'a,
+    {
+        self._map_indexed(list, map_fn, false)
+    }
+
+    /// Same as [`map_indexed`](Self::map_indexed) but detects when items are only inserted or
+    /// removed at a single position. In that case, the mapped values of the other items are
+    /// shifted instead of being recomputed for every index after the insertion or removal.
+    ///
+    /// For any other change, the input is diffed by index like with
+    /// [`map_indexed`](Self::map_indexed).
+    pub fn map_indexed_detect_shifts<T, U>(
+        &'a self,
+        list: &'a ReadSignal<Vec<T>>,
+        map_fn: impl for<'child_lifetime> Fn(BoundedScopeRef<'child_lifetime, 'a>, T) -> U + 'a,
+    ) -> &'a ReadSignal<Vec<U>>
+    where
+        T: PartialEq + Clone,
+        U: Clone + 'a,
+    {
+        self._map_indexed(list, map_fn, true)
+    }
+
+    fn _map_indexed<T, U>(
+        &'a self,
+        list: &'a ReadSignal<Vec<T>>,
+        map_fn: impl for<'child_lifetime> Fn(BoundedScopeRef<'child_lifetime, 'a>, T) -> U + 'a,
+        detect_shifts: bool,
+    ) -> &'a ReadSignal<Vec<U>>
     where
         T: PartialEq + Clone,
         U: Clone + 'a,
@@ -246,6 +559,35 @@ impl<'a> Scope<'a> {
                 }
                 items = Rc::new(Vec::new());
                 mapped = Vec::new();
+            } else if let Some((start, removed, inserted)) = detect_shifts
+                .then(|| find_shift(&items, &new_items))
+                .flatten()
+            {
+                // Fast path for items inserted or removed at a single position. The other items
+                // are unchanged so their mapped values are shifted instead of recomputed.
+                for disposer in disposers.drain(start..start + removed) {
+                    unsafe {
+                        disposer.dispose();
+                    }
+                }
+                mapped.drain(start..start + removed);
+
+                let mut new_mapped = Vec::with_capacity(inserted);
+                let mut new_disposers = Vec::with_capacity(inserted);
+                for new_item in new_items[start..start + inserted].iter().cloned() {
+                    let mut value = None;
+                    let new_disposer =
+                        self.create_child_scope(|ctx| value = Some(map_fn(ctx, new_item)));
+                    new_mapped.push(value.unwrap());
+                    new_disposers.push(new_disposer);
+                }
+                mapped.splice(start..start, new_mapped);
+                disposers.splice(start..start, new_disposers);
+
+                items = Rc::clone(&new_items);
+                debug_assert!([items.len(), mapped.len(), disposers.len()]
+                    .iter()
+                    .all(|l| *l == new_items.len()));
             } else {
                 // Pre-allocate space needed
                 if new_items.len() > items.len() {
@@ -317,6 +659,28 @@ impl<'a> Scope<'a> {
     }
 }
 
+/// Checks if `new` is `old` with items only inserted or only removed at a single position. Returns
+/// the position and the number of removed and inserted items.
+fn find_shift<T: PartialEq>(old: &[T], new: &[T]) -> Option<(usize, usize, usize)> {
+    let start = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    if old.len() < new.len() {
+        let inserted = new.len() - old.len();
+        (old[start..] == new[start + inserted..]).then_some((start, 0, inserted))
+    } else if old.len() > new.len() {
+        let removed = old.len() - new.len();
+        (old[start + removed..] == new[start..]).then_some((start, removed, 0))
+    } else {
+        None
+    }
+}
+
+/// Checks if `new` is `old` with at least one item added at the front. Returns the number of
+/// added items.
+fn find_prepend<T: PartialEq>(old: &[T], new: &[T]) -> Option<usize> {
+    let prepended = new.len().checked_sub(old.len())?;
+    (prepended > 0 && old == &new[prepended..]).then_some(prepended)
+}
+
 #[cfg(test)]
 mod tests {
     use std::cell::Cell;
@@ -392,6 +756,118 @@ mod tests {
         });
     }
 
+    #[test]
+    fn keyed_preserve_scopes_on_move() {
+        create_scope_immediate(|ctx| {
+            let a = ctx.create_signal(vec![1, 2, 3, 4]);
+            let created = Rc::new(Cell::new(0));
+            let disposed = Rc::new(Cell::new(0));
+            let mapped = ctx.map_keyed(
+                a,
+                {
+                    let created = Rc::clone(&created);
+                    let disposed = Rc::clone(&disposed);
+                    move |ctx, x| {
+                        created.set(created.get() + 1);
+                        let disposed = Rc::clone(&disposed);
+                        ctx.on_cleanup(move || disposed.set(disposed.get() + 1));
+                        // Item-local state that should survive moves.
+                        (x, create_rc_signal(false))
+                    }
+                },
+                |x| *x,
+            );
+            let states = |mapped: &[(i32, RcSignal<bool>)], x: i32| {
+                mapped.iter().find(|(y, _)| *y == x).unwrap().1.clone()
+            };
+            let initial = (*mapped.get()).clone();
+            states(&initial, 2).set(true);
+
+            for order in [
+                vec![4, 3, 2, 1],
+                vec![2, 1, 3, 4],
+                vec![1, 3, 4, 2],
+                vec![3, 1, 2, 4],
+            ] {
+                a.set(order.clone());
+                let current = mapped.get();
+                assert_eq!(current.iter().map(|(x, _)| *x).collect::<Vec<_>>(), order);
+                for x in 1..=4 {
+                    assert!(
+                        std::ptr::eq(&*states(&current, x), &*states(&initial, x)),
+                        "item {x} should keep its state after moving"
+                    );
+                }
+                assert!(*states(&current, 2).get());
+            }
+            assert_eq!(created.get(), 4, "no scope should be created for a move");
+            assert_eq!(disposed.get(), 0, "no scope should be disposed for a move");
+        });
+    }
+
+    #[cfg(feature = "profiling")]
+    #[test]
+    fn keyed_reconcile_stats() {
+        create_scope_immediate(|ctx| {
+            let a = ctx.create_signal(vec![1, 2, 3, 4, 5]);
+            let stats = ctx.create_signal(ReconcileStats::default());
+            let mapped = ctx.map_keyed_with_stats(a, |_, x| x, |x| *x, stats);
+            assert_eq!(
+                *stats.get(),
+                ReconcileStats {
+                    runs: 1,
+                    created: 5,
+                    ..Default::default()
+                }
+            );
+
+            // Swap the first and last items.
+            a.set(vec![5, 2, 3, 4, 1]);
+            assert_eq!(*mapped.get(), vec![5, 2, 3, 4, 1]);
+            assert_eq!(
+                *stats.get(),
+                ReconcileStats {
+                    runs: 2,
+                    created: 5,
+                    removed: 0,
+                    moved: 2,
+                    reused: 3,
+                }
+            );
+
+            // Shuffle so that every item changes index.
+            a.set(vec![3, 1, 4, 5, 2]);
+            assert_eq!(*mapped.get(), vec![3, 1, 4, 5, 2]);
+            assert_eq!(
+                *stats.get(),
+                ReconcileStats {
+                    runs: 3,
+                    created: 5,
+                    removed: 0,
+                    moved: 7,
+                    reused: 3,
+                }
+            );
+
+            // Replace the tail.
+            a.set(vec![3, 1, 6]);
+            assert_eq!(*mapped.get(), vec![3, 1, 6]);
+            assert_eq!(
+                *stats.get(),
+                ReconcileStats {
+                    runs: 4,
+                    created: 6,
+                    removed: 3,
+                    moved: 7,
+                    reused: 5,
+                }
+            );
+
+            a.set(Vec::new());
+            assert_eq!(stats.get().removed, 6);
+        });
+    }
+
     #[test]
     fn keyed_call_cleanup_on_remove() {
         create_scope_immediate(|ctx| {
@@ -448,6 +924,134 @@ mod tests {
         });
     }
 
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic = "duplicate key 1 returned by `key_fn` for the items at indices 0 and 2"]
+    fn keyed_duplicate_keys_should_panic() {
+        create_scope_immediate(|ctx| {
+            let a = ctx.create_signal(vec![1, 2]);
+            let _mapped = ctx.map_keyed(a, |_, x| x, |x| x % 3);
+
+            // 1 and 4 have the same key.
+            a.set(vec![1, 2, 4]);
+        });
+    }
+
+    #[test]
+    fn keyed_renames() {
+        create_scope_immediate(|ctx| {
+            let a = ctx.create_signal(vec![(1, "a"), (2, "b")]);
+            let renames = ctx.create_ref(KeyRenames::new());
+            let created = ctx.create_ref(Cell::new(0));
+            let disposed = ctx.create_ref(Cell::new(0));
+            let mapped = ctx.map_keyed_with_renames(
+                a,
+                |ctx, (_, text)| {
+                    created.set(created.get() + 1);
+                    ctx.on_cleanup(|| disposed.set(disposed.get() + 1));
+                    Rc::new(Cell::new(text))
+                },
+                |(id, _)| *id,
+                renames,
+            );
+            assert_eq!(created.get(), 2);
+
+            // Insert an item with a temporary key.
+            a.set(vec![(1, "a"), (-1, "c"), (2, "b")]);
+            assert_eq!(created.get(), 3);
+            let optimistic = Rc::clone(&mapped.get()[1]);
+            optimistic.set("c (pending)");
+
+            // Confirm the item: its scope is kept.
+            renames.rename(-1, 3);
+            a.set(vec![(1, "a"), (3, "c"), (2, "b")]);
+            assert_eq!(created.get(), 3);
+            assert_eq!(disposed.get(), 0);
+            assert!(Rc::ptr_eq(&mapped.get()[1], &optimistic));
+            assert_eq!(mapped.get()[1].get(), "c (pending)");
+
+            // The renames are only applied once.
+            a.set(vec![(3, "c"), (1, "a"), (2, "b")]);
+            assert!(Rc::ptr_eq(&mapped.get()[0], &optimistic));
+            assert_eq!(created.get(), 3);
+
+            // Roll back another item.
+            a.set(vec![(3, "c"), (1, "a"), (2, "b"), (-2, "d")]);
+            assert_eq!(created.get(), 4);
+            a.set(vec![(3, "c"), (1, "a"), (2, "b")]);
+            assert_eq!(disposed.get(), 1);
+        });
+    }
+
+    #[test]
+    fn keyed_prepend() {
+        create_scope_immediate(|ctx| {
+            let a = ctx.create_signal(vec![3, 4]);
+            let mapped_items = ctx.create_ref(RefCell::new(Vec::new()));
+            let disposed = ctx.create_ref(Cell::new(0));
+            let mapped = ctx.map_keyed(
+                a,
+                |ctx, x| {
+                    mapped_items.borrow_mut().push(x);
+                    ctx.on_cleanup(|| disposed.set(disposed.get() + 1));
+                    x * 2
+                },
+                |x| *x,
+            );
+            assert_eq!(*mapped.get(), [6, 8]);
+
+            a.set(vec![2, 3, 4]);
+            assert_eq!(*mapped.get(), [4, 6, 8]);
+            a.set(vec![0, 1, 2, 3, 4]);
+            assert_eq!(*mapped.get(), [0, 2, 4, 6, 8]);
+            assert_eq!(*mapped_items.borrow(), [3, 4, 2, 0, 1]);
+            assert_eq!(disposed.get(), 0, "existing items should not be rebuilt");
+
+            // Not a prepend: falls back to the general diff.
+            a.set(vec![5, 0, 1, 3, 4]);
+            assert_eq!(*mapped.get(), [10, 0, 2, 6, 8]);
+            assert_eq!(*mapped_items.borrow(), [3, 4, 2, 0, 1, 5]);
+            assert_eq!(disposed.get(), 1);
+        });
+    }
+
+    #[test]
+    fn keyed_caches_keys() {
+        create_scope_immediate(|ctx| {
+            let calls = ctx.create_ref(RefCell::new(Vec::new()));
+            let a = ctx.create_signal(vec![1, 2, 3]);
+            let mapped = ctx.map_keyed(
+                a,
+                |_, x| x * 2,
+                |x| {
+                    calls.borrow_mut().push(*x);
+                    *x
+                },
+            );
+            assert_eq!(*mapped.get(), vec![2, 4, 6]);
+            assert_eq!(mem::take(&mut *calls.borrow_mut()), vec![1, 2, 3]);
+
+            // Append.
+            a.set(vec![1, 2, 3, 4]);
+            assert_eq!(*mapped.get(), vec![2, 4, 6, 8]);
+            assert_eq!(mem::take(&mut *calls.borrow_mut()), vec![4]);
+
+            // Prepend.
+            a.set(vec![0, 1, 2, 3, 4]);
+            assert_eq!(*mapped.get(), vec![0, 2, 4, 6, 8]);
+            assert_eq!(mem::take(&mut *calls.borrow_mut()), vec![0]);
+
+            // Replace and remove.
+            a.set(vec![0, 5, 2, 4]);
+            assert_eq!(*mapped.get(), vec![0, 10, 4, 8]);
+            assert_eq!(mem::take(&mut *calls.borrow_mut()), vec![5]);
+
+            // Unchanged list.
+            a.set(vec![0, 5, 2, 4]);
+            assert!(calls.borrow().is_empty());
+        });
+    }
+
     #[test]
     fn indexed() {
         create_scope_immediate(|ctx| {
@@ -520,6 +1124,45 @@ mod tests {
         });
     }
 
+    #[test]
+    fn indexed_detect_shifts() {
+        create_scope_immediate(|ctx| {
+            let a = ctx.create_signal(vec![1, 2, 3]);
+            let counter = Rc::new(Cell::new(0));
+            let disposed = Rc::new(Cell::new(0));
+            let mapped = ctx.map_indexed_detect_shifts(a, {
+                let counter = Rc::clone(&counter);
+                let disposed = Rc::clone(&disposed);
+                move |ctx, _| {
+                    let disposed = Rc::clone(&disposed);
+                    ctx.on_cleanup(move || disposed.set(disposed.get() + 1));
+                    counter.set(counter.get() + 1);
+                    counter.get()
+                }
+            });
+            assert_eq!(*mapped.get(), vec![1, 2, 3]);
+
+            // Inserting at the front only maps the new item.
+            a.set(vec![0, 1, 2, 3]);
+            assert_eq!(*mapped.get(), vec![4, 1, 2, 3]);
+
+            // Inserting in the middle.
+            a.set(vec![0, 1, 5, 6, 2, 3]);
+            assert_eq!(*mapped.get(), vec![4, 1, 5, 6, 2, 3]);
+            assert_eq!(disposed.get(), 0);
+
+            // Removing from the middle.
+            a.set(vec![0, 1, 2, 3]);
+            assert_eq!(*mapped.get(), vec![4, 1, 2, 3]);
+            assert_eq!(disposed.get(), 2);
+
+            // Falls back to diffing by index.
+            a.set(vec![0, 7, 2, 8]);
+            assert_eq!(*mapped.get(), vec![4, 7, 2, 8]);
+            assert_eq!(disposed.get(), 4);
+        });
+    }
+
     #[test]
     fn indexed_call_cleanup_on_remove() {
         create_scope_immediate(|ctx| {
@@ -3,17 +3,25 @@
 #![warn(missing_docs)]
 
 mod arena;
+mod cancel;
 mod context;
 mod effect;
+mod history;
 mod iter;
 mod memo;
+mod outbox;
 mod signal;
 
+pub use cancel::*;
 pub use effect::*;
+pub use history::*;
+pub use iter::*;
+pub use memo::*;
+pub use outbox::*;
 pub use signal::*;
 
 use std::any::{Any, TypeId};
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 use std::marker::PhantomData;
 use std::mem;
@@ -24,6 +32,33 @@ use arena::*;
 use indexmap::IndexMap;
 use slotmap::{DefaultKey, SlotMap};
 
+/// The default value for [`max_scope_depth`].
+pub const DEFAULT_MAX_SCOPE_DEPTH: u32 = 1024;
+
+thread_local! {
+    /// The maximum nesting depth of child scopes, or `None` to use [`DEFAULT_MAX_SCOPE_DEPTH`].
+    /// See [`set_max_scope_depth`].
+    static MAX_SCOPE_DEPTH: Cell<Option<u32>> = Default::default();
+    /// The disposals that were deferred because an effect of the scope was running. See
+    /// [`dispose_when_idle`].
+    static DEFERRED_DISPOSALS: RefCell<Vec<DeferredDisposal>> = Default::default();
+}
+
+/// Returns the maximum nesting depth of child scopes on the current thread.
+pub fn max_scope_depth() -> u32 {
+    MAX_SCOPE_DEPTH.with(|max| max.get().unwrap_or(DEFAULT_MAX_SCOPE_DEPTH))
+}
+
+/// Sets the maximum nesting depth of child scopes on the current thread. Defaults to
+/// [`DEFAULT_MAX_SCOPE_DEPTH`].
+///
+/// Creating a child scope nested deeper than this limit with [`Scope::create_child_scope`] panics.
+/// This turns unbounded recursion in a component tree into a clear error message instead of a stack
+/// overflow. Increase the limit if your tree is legitimately this deep.
+pub fn set_max_scope_depth(max: u32) {
+    MAX_SCOPE_DEPTH.with(|x| x.set(Some(max)));
+}
+
 /// A wrapper type around a lifetime that forces the lifetime to be invariant.
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 struct InvariantLifetime<'id>(PhantomData<&'id mut &'id ()>);
@@ -43,8 +78,15 @@ struct ScopeInner<'a> {
     /// Contexts that are allocated on the current [`Scope`].
     /// See the [`mod@context`] module.
     ///
-    /// The raw pointer is owned by this field.
+    /// The values are `*const T` pointers to the provided references, which live at least as long
+    /// as the [`Scope`]. They are stored as pointers so that `T` can be unsized.
     contexts: HashMap<TypeId, &'a dyn Any>,
+    /// A token that is cancelled when the [`Scope`] is disposed. Lazily created by
+    /// [`Scope::cancellation_token`].
+    cancellation_token: Option<CancellationToken>,
+    /// The call sites of the effects created with [`Scope::create_effect_persistent`] inside of
+    /// another effect, along with the effect that was running when they were created.
+    persistent_effects: Vec<(WeakEffectCallback, &'static std::panic::Location<'static>)>,
     // Make sure that 'a is invariant.
     _phantom: InvariantLifetime<'a>,
 }
@@ -67,10 +109,13 @@ pub struct Scope<'a> {
     inner: RefCell<ScopeInner<'a>>,
     /// An arena allocator for allocating refs and signals.
     arena: ScopeArena<'a>,
-    /// A pointer to the parent scope.
+    /// A pointer to the parent scope. This is a [`Cell`] because the scope can be moved to another
+    /// parent with [`ScopeDisposer::reparent`].
     /// # Safety
     /// The parent scope does not actually have the right lifetime.
-    parent: Option<*const Scope<'a>>,
+    parent: Cell<Option<*const Scope<'a>>>,
+    /// The number of ancestors of this scope. The root scope has a depth of `0`.
+    depth: Cell<u32>,
 }
 
 impl<'a> Scope<'a> {
@@ -88,12 +133,80 @@ impl<'a> Scope<'a> {
                 cleanups: Default::default(),
                 child_scopes: Default::default(),
                 contexts: Default::default(),
+                cancellation_token: Default::default(),
+                persistent_effects: Default::default(),
                 _phantom: Default::default(),
             }),
             arena: Default::default(),
-            parent: None,
+            parent: Default::default(),
+            depth: Default::default(),
         }
     }
+
+    /// Returns a pointer to the scope that owns this scope, or `None` if this is a root scope.
+    ///
+    /// The owner is the scope on which this scope was created with
+    /// [`create_child_scope`](Self::create_child_scope), unless it was moved to another scope with
+    /// [`ScopeDisposer::reparent`]. The pointer is only meant to identify the owner, e.g. by
+    /// comparing it with [`std::ptr::eq`].
+    pub fn owner(&self) -> Option<*const Scope<'a>> {
+        self.parent.get()
+    }
+
+    /// Sets the depth of this scope and updates the depths of all its descendants.
+    fn set_depth(&self, depth: u32) {
+        self.depth.set(depth);
+        for &child in self.inner.borrow().child_scopes.values() {
+            // SAFETY: The child scopes are alive as long as they are in `child_scopes`.
+            unsafe { (*child).set_depth(depth + 1) };
+        }
+    }
+
+    /// Returns `true` if one of the effects of this scope or of its descendants is running.
+    fn has_running_effect(&self) -> bool {
+        let inner = match self.inner.try_borrow() {
+            Ok(inner) => inner,
+            // The scope is being modified, e.g. disposed.
+            Err(_) => return true,
+        };
+        // The state of an effect is taken out while it is running.
+        inner
+            .effects
+            .iter()
+            .any(|effect| effect.try_borrow().map_or(true, |effect| effect.is_none()))
+            || inner
+                .child_scopes
+                .values()
+                // SAFETY: The child scopes are alive as long as they are in `child_scopes`.
+                .any(|&child| unsafe { (*child).has_running_effect() })
+    }
+
+    /// Returns the number of levels of child scopes below this scope.
+    fn height(&self) -> u32 {
+        self.inner
+            .borrow()
+            .child_scopes
+            .values()
+            // SAFETY: The child scopes are alive as long as they are in `child_scopes`.
+            .map(|&child| unsafe { (*child).height() } + 1)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// Panics if a scope nested at `depth` would exceed [`max_scope_depth`]. `action` describes what
+/// the caller is doing for the panic message.
+#[track_caller]
+fn check_scope_depth(depth: u32, action: &str) {
+    let max_depth = max_scope_depth();
+    if depth > max_depth {
+        panic!(
+            "maximum scope depth of {max_depth} exceeded when {action} at {}. This is most likely \
+             caused by unbounded recursion in a component tree. If the tree is legitimately this \
+             deep, increase the limit with `set_max_scope_depth`.",
+            std::panic::Location::caller()
+        );
+    }
 }
 
 /// A reference to a [`Scope`].
@@ -122,11 +235,17 @@ impl<'a, 'bound> Deref for BoundedScopeRef<'a, 'bound> {
 /// A handle that allows cleaning up a [`Scope`].
 pub struct ScopeDisposer<'a> {
     f: Box<dyn FnOnce() + 'a>,
+    /// The parent scope and the key of the scope in the parent's `child_scopes`, if this disposes
+    /// a child scope.
+    child: Option<(&'a Scope<'a>, DefaultKey)>,
 }
 
 impl<'a> ScopeDisposer<'a> {
     fn new(f: impl FnOnce() + 'a) -> Self {
-        Self { f: Box::new(f) }
+        Self {
+            f: Box::new(f),
+            child: None,
+        }
     }
 
     /// Cleanup the resources owned by the [`Scope`].
@@ -142,7 +261,8 @@ impl<'a> ScopeDisposer<'a> {
     ///
     /// # Drop order
     ///
-    /// Fields are dropped in the following order:
+    /// The scope's [`CancellationToken`] (if any) is cancelled first. Fields are then dropped in
+    /// the following order:
     /// * `child_scopes` - Run child scope drop first.
     /// * `effects`
     /// * `cleanups`
@@ -153,6 +273,186 @@ impl<'a> ScopeDisposer<'a> {
     pub unsafe fn dispose(self) {
         (self.f)();
     }
+
+    /// Moves the child scope to `new_parent` without disposing it. Returns the disposer for the
+    /// child scope in its new parent.
+    ///
+    /// Everything in the child scope keeps running: effects are not re-run and signals keep their
+    /// values. Context lookups from the child scope now resolve against `new_parent` and the child
+    /// scope is disposed when `new_parent` is disposed instead of when its former parent is.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the disposer was not created by [`Scope::create_child_scope`], or if
+    /// the child scope or any of its descendants would be nested deeper than [`max_scope_depth`]
+    /// under `new_parent`.
+    ///
+    /// # Safety
+    ///
+    /// The lifetimes of the two parents are not related. Anything that the child scope borrows
+    /// from its former parent (or any of its ancestors), e.g. signals captured in effects, must
+    /// outlive `new_parent`. This is always the case when `new_parent` is a descendant of the
+    /// scope that owns the borrowed data.
+    ///
+    /// Like [`dispose`](Self::dispose), `reparent` should not be called inside the
+    /// `create_child_scope` closure of the child scope.
+    #[track_caller]
+    pub unsafe fn reparent<'b>(self, new_parent: &'b Scope<'b>) -> ScopeDisposer<'b> {
+        let (parent, key) = self
+            .child
+            .expect("only the disposer of a child scope can be re-parented");
+        let child = *parent
+            .inner
+            .borrow()
+            .child_scopes
+            .get(key)
+            .expect("child scope should not be disposed");
+        check_scope_depth(
+            new_parent.depth.get() + 1 + (*child).height(),
+            "moving a child scope",
+        );
+        let child = parent
+            .inner
+            .borrow_mut()
+            .child_scopes
+            .remove(key)
+            .expect("child scope should not be disposed");
+        // SAFETY: The caller guarantees that the child scope does not outlive what it borrows.
+        let child = child.cast::<Scope<'b>>();
+        (*child).parent.set(Some(new_parent));
+        (*child).set_depth(new_parent.depth.get() + 1);
+
+        let key = new_parent.inner.borrow_mut().child_scopes.insert(child);
+        new_parent.child_disposer(key)
+    }
+}
+
+/// A scope disposal that was deferred by [`dispose_when_idle`].
+struct DeferredDisposal {
+    /// The scope to dispose. It is only accessed while `alive` is `true`.
+    scope: *const Scope<'static>,
+    /// Set to `false` when the scope is disposed in the meantime, e.g. along with its parent.
+    alive: Rc<Cell<bool>>,
+    dispose: Box<dyn FnOnce()>,
+}
+
+/// Calls `dispose`, which disposes `scope`, unless one of the effects of `scope` or of its
+/// descendants is running. Disposing the scope would then free the data that the effect is still
+/// borrowing, so the disposal is deferred until the effects have returned. It is skipped if the
+/// scope is disposed by other means in the meantime.
+///
+/// # Safety
+///
+/// `dispose` must not be called inside the `create_child_scope` closure of `scope`.
+unsafe fn dispose_when_idle<'a>(scope: &Scope<'a>, dispose: impl FnOnce() + 'a) {
+    if !scope.has_running_effect() {
+        dispose();
+        return;
+    }
+    let alive = Rc::new(Cell::new(true));
+    scope.on_cleanup({
+        let alive = alive.clone();
+        move || alive.set(false)
+    });
+    let dispose: Box<dyn FnOnce() + 'a> = Box::new(dispose);
+    DEFERRED_DISPOSALS.with(|disposals| {
+        disposals.borrow_mut().push(DeferredDisposal {
+            // SAFETY: The scope and what `dispose` borrows are only accessed while the scope is
+            // alive, which implies that its parent scopes are alive as well.
+            scope: (scope as *const Scope<'a>).cast(),
+            alive,
+            dispose: std::mem::transmute::<Box<dyn FnOnce() + 'a>, Box<dyn FnOnce()>>(dispose),
+        });
+    });
+}
+
+/// Runs the deferred disposals of the scopes that do not have a running effect anymore. This is
+/// called every time an effect has finished running.
+pub(crate) fn run_deferred_disposals() {
+    let ready: Vec<_> = DEFERRED_DISPOSALS.with(|disposals| {
+        let mut disposals = disposals.borrow_mut();
+        if disposals.is_empty() {
+            return Vec::new();
+        }
+        let (ready, pending) = mem::take(&mut *disposals)
+            .into_iter()
+            .partition(|disposal| {
+                // SAFETY: The scope is still alive.
+                !disposal.alive.get() || unsafe { !(*disposal.scope).has_running_effect() }
+            });
+        *disposals = pending;
+        ready
+    });
+    for disposal in ready {
+        // A disposal could have disposed the scope of another one.
+        if disposal.alive.get() {
+            (disposal.dispose)();
+        }
+    }
+}
+
+/// A [`ScopeDisposer`] that can be disposed without `unsafe`.
+///
+/// Created with [`Scope::create_child_scope_with_safe`]. Since it is only handed out after the
+/// child scope closure has returned, it can never be disposed inside of it. If it is disposed
+/// while one of the effects of the child scope is running, e.g. by the effect itself, the child
+/// scope is only disposed once the effect has returned.
+#[must_use = "the child scope is only disposed when the parent scope is, unless `dispose` is called"]
+pub struct SafeScopeDisposer<'a>(ScopeDisposer<'a>);
+
+impl<'a> SafeScopeDisposer<'a> {
+    /// Cleanup the resources owned by the child scope. For more details, see
+    /// [`ScopeDisposer::dispose`].
+    ///
+    /// The disposer is consumed so the child scope cannot be disposed twice.
+    pub fn dispose(self) {
+        let (parent, key) = self
+            .0
+            .child
+            .expect("the disposer was created by create_child_scope");
+        let child = parent.inner.borrow().child_scopes.get(key).copied();
+        // SAFETY: The disposer is only created after the `create_child_scope_with_safe` closure
+        // has returned. The child scope is alive as long as it is in `child_scopes`.
+        unsafe {
+            match child {
+                Some(child) => dispose_when_idle(&*child, move || self.0.dispose()),
+                // The child scope was already disposed along with its parent.
+                None => self.0.dispose(),
+            }
+        }
+    }
+}
+
+/// A child scope that was detached from its parent with [`Scope::detach_child`].
+///
+/// The scope is not owned by any scope while it is detached: it is not disposed when its former
+/// parent is. It can be attached again with [`Scope::reattach`]. Dropping a [`DetachedScope`]
+/// disposes the scope. If one of the effects of the scope is running, e.g. because the
+/// [`DetachedScope`] is dropped by the effect itself, the scope is only disposed once the effect
+/// has returned.
+#[must_use = "dropping a detached scope disposes it"]
+pub struct DetachedScope<'a> {
+    scope: *mut Scope<'a>,
+}
+
+impl<'a> DetachedScope<'a> {
+    /// Cleanup the resources owned by the detached scope. For more details, see
+    /// [`ScopeDisposer::dispose`].
+    ///
+    /// This is the same as dropping the [`DetachedScope`].
+    pub fn dispose(self) {
+        drop(self);
+    }
+}
+
+impl Drop for DetachedScope<'_> {
+    fn drop(&mut self) {
+        let scope = self.scope;
+        // SAFETY: The pointer was allocated in Scope::create_child_scope and is owned by `self`.
+        // The scope could only be detached after its `create_child_scope` closure returned.
+        // `dispose_when_idle` makes sure that none of its effects are running when it is freed.
+        unsafe { dispose_when_idle(&*scope, move || drop(Box::from_raw(scope))) };
+    }
 }
 
 /// Creates a reactive scope.
@@ -244,6 +544,7 @@ impl<'a> Scope<'a> {
     ///     outer = Some(signal);
     /// });
     /// ```
+    #[track_caller]
     pub fn create_signal<T>(&'a self, value: T) -> &'a Signal<T> {
         let signal = Signal::new(value);
         self.arena.alloc(signal)
@@ -324,14 +625,25 @@ impl<'a> Scope<'a> {
     /// //   ^^^^^ -> and remains accessible outside the closure.
     /// # });
     /// ```
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the child scope would be nested deeper than [`max_scope_depth`].
+    #[track_caller]
     pub fn create_child_scope<F>(&'a self, f: F) -> ScopeDisposer<'a>
     where
         F: for<'child_lifetime> FnOnce(BoundedScopeRef<'child_lifetime, 'a>),
     {
-        let mut child = Scope::new();
+        let depth = self.depth.get() + 1;
+        check_scope_depth(depth, "creating a child scope");
+
+        let child = Scope::new();
+        child.depth.set(depth);
         // SAFETY: The only fields that are accessed on self from child is `context` which does not
         // have any lifetime annotations.
-        child.parent = Some(unsafe { std::mem::transmute(self as *const _) });
+        child
+            .parent
+            .set(Some(unsafe { std::mem::transmute(self as *const _) }));
         let boxed = Box::new(child);
         let ptr = Box::into_raw(boxed);
 
@@ -350,14 +662,192 @@ impl<'a> Scope<'a> {
         f(BoundedScopeRef::new(unsafe { &*ptr }));
         //                                    ^^^ -> `ptr` is still accessible here after
         // the call to f.
-        ScopeDisposer::new(move || unsafe {
-            let ctx = self.inner.borrow_mut().child_scopes.remove(key).unwrap();
-            // SAFETY: Safe because ptr created using Box::into_raw and closure cannot live longer
-            // than 'a.
-            let ctx = Box::from_raw(ctx);
-            // SAFETY: Outside of call to f.
-            ctx.dispose();
-        })
+        self.child_disposer(key)
+    }
+
+    /// Create a child scope and return the value returned by `f` together with a
+    /// [`SafeScopeDisposer`] for the child scope.
+    ///
+    /// The returned value cannot borrow anything from the child scope since `T` does not depend on
+    /// `'child_lifetime`. It can, however, borrow from this scope.
+    ///
+    /// # Example
+    /// ```
+    /// # use sycamore_reactive::*;
+    /// # create_scope_immediate(|ctx| {
+    /// let state = ctx.create_signal(1);
+    /// let (doubled, disposer) = ctx.create_child_scope_with_safe(|_| ctx.create_memo(|| *state.get() * 2));
+    /// assert_eq!(*doubled.get(), 2);
+    /// disposer.dispose();
+    /// # });
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the child scope would be nested deeper than [`max_scope_depth`].
+    #[track_caller]
+    pub fn create_child_scope_with_safe<T>(
+        &'a self,
+        f: impl for<'child_lifetime> FnOnce(BoundedScopeRef<'child_lifetime, 'a>) -> T,
+    ) -> (T, SafeScopeDisposer<'a>) {
+        let mut ret = None;
+        let disposer = self.create_child_scope(|ctx| ret = Some(f(ctx)));
+        (ret.unwrap(), SafeScopeDisposer(disposer))
+    }
+
+    /// Runs `f`, which typically creates many child scopes at once (e.g. when rendering a long list
+    /// for the first time), and defers the effects that are triggered in the meantime until `f`
+    /// returns, so that effects that depend on all the children run once instead of once per
+    /// child. See [`batch`].
+    ///
+    /// # Example
+    /// ```
+    /// # use sycamore_reactive::*;
+    /// # create_scope_immediate(|ctx| {
+    /// let total = ctx.create_signal(0);
+    /// ctx.create_effect(|| println!("total = {}", total.get()));
+    ///
+    /// // Only prints "total = 500" once.
+    /// ctx.batch_children(|| {
+    ///     for _ in 0..500 {
+    ///         let _ = ctx.create_child_scope(|_| total.set(*total.get_untracked() + 1));
+    ///     }
+    /// });
+    /// # });
+    /// ```
+    pub fn batch_children(&'a self, f: impl FnOnce()) {
+        batch(f);
+    }
+
+    /// Disposes all the child scopes of this scope, without disposing the scope itself. The
+    /// effects, cleanup callbacks, signals and contexts of the scope are kept.
+    ///
+    /// This is useful to reset a whole subtree, e.g. when a router renders a new page. The
+    /// [`ScopeDisposer`]s of the disposed child scopes do nothing when they are called afterwards.
+    /// Child scopes that are created while the child scopes are disposed, e.g. by a cleanup
+    /// callback, are kept.
+    ///
+    /// # Example
+    /// ```
+    /// # use sycamore_reactive::*;
+    /// # create_scope_immediate(|ctx| {
+    /// let _ = ctx.create_child_scope(|ctx| {
+    ///     ctx.on_cleanup(|| println!("child disposed"));
+    /// });
+    /// // Prints "child disposed".
+    /// unsafe { ctx.dispose_children() };
+    /// # });
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// Like [`ScopeDisposer::dispose`], this should not be called from inside one of the child
+    /// scopes, e.g. from an effect created in a child scope.
+    pub unsafe fn dispose_children(&self) {
+        let keys: Vec<_> = self.inner.borrow().child_scopes.keys().collect();
+        for key in keys {
+            // The child scope might already have been disposed by the cleanup callbacks of a
+            // previous child scope.
+            let child = self.inner.borrow_mut().child_scopes.remove(key);
+            if let Some(child) = child {
+                // SAFETY: These pointers were allocated in Self::create_child_scope.
+                Box::from_raw(child).dispose();
+            }
+        }
+    }
+
+    /// Detaches a child scope of this scope, so that it is not disposed when this scope is. This
+    /// can be used to keep a subtree alive while it is not displayed, e.g. to preserve the state
+    /// of a component when navigating away from it, and to attach it again later with
+    /// [`reattach`](Self::reattach) instead of creating it from scratch.
+    ///
+    /// Everything in the detached scope keeps running, including its effects. Context lookups
+    /// still resolve against this scope. The [`DetachedScope`] cannot outlive `'a` and the scope
+    /// is disposed when it is dropped, so everything it borrows stays alive for as long as it
+    /// runs.
+    ///
+    /// # Example
+    /// ```
+    /// # use sycamore_reactive::*;
+    /// # create_scope_immediate(|ctx| {
+    /// let page = ctx.create_child_scope(|ctx| {
+    ///     let _count = ctx.create_signal(0);
+    /// });
+    /// // Keep the page alive while it is hidden.
+    /// let detached = ctx.detach_child(page);
+    /// // ...
+    /// let page = ctx.reattach(detached);
+    /// # });
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `disposer` was not created by
+    /// [`create_child_scope`](Self::create_child_scope) on this scope, or if the child scope was
+    /// already disposed.
+    pub fn detach_child(&'a self, disposer: ScopeDisposer<'a>) -> DetachedScope<'a> {
+        let (parent, key) = disposer
+            .child
+            .expect("only the disposer of a child scope can be detached");
+        assert!(
+            std::ptr::eq(parent, self),
+            "the child scope does not belong to this scope"
+        );
+        let scope = self
+            .inner
+            .borrow_mut()
+            .child_scopes
+            .remove(key)
+            .expect("child scope should not be disposed");
+        DetachedScope { scope }
+    }
+
+    /// Attaches a scope that was detached with [`detach_child`](Self::detach_child) as a child
+    /// scope of this scope. Returns the disposer for the child scope.
+    ///
+    /// The scope is not re-created: its signals keep their values and its effects do not re-run.
+    /// Context lookups from the scope now resolve against this scope.
+    ///
+    /// The scope can be attached to any scope that does not outlive what it borrows, i.e. a scope
+    /// whose lifetime is outlived by `'b`, such as a descendant of its former parent.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the scope or any of its descendants would be nested deeper than
+    /// [`max_scope_depth`].
+    #[track_caller]
+    pub fn reattach<'b: 'a>(&'a self, detached: DetachedScope<'b>) -> ScopeDisposer<'a> {
+        // SAFETY: The detached scope is alive as long as `detached` is.
+        let height = unsafe { (*detached.scope).height() };
+        check_scope_depth(self.depth.get() + 1 + height, "attaching a child scope");
+        let scope = mem::ManuallyDrop::new(detached).scope;
+        // SAFETY: `'b: 'a` guarantees that everything the scope borrows outlives `self`. The
+        // scope is owned by `detached` until it is inserted in `child_scopes`.
+        let scope = scope.cast::<Scope<'a>>();
+        unsafe {
+            (*scope).parent.set(Some(self));
+            (*scope).set_depth(self.depth.get() + 1);
+        }
+        let key = self.inner.borrow_mut().child_scopes.insert(scope);
+        self.child_disposer(key)
+    }
+
+    /// Creates the [`ScopeDisposer`] for the child scope stored under `key` in `child_scopes`.
+    fn child_disposer(&'a self, key: DefaultKey) -> ScopeDisposer<'a> {
+        ScopeDisposer {
+            f: Box::new(move || unsafe {
+                // The child scope might already have been disposed by `dispose_children`.
+                let ctx = self.inner.borrow_mut().child_scopes.remove(key);
+                if let Some(ctx) = ctx {
+                    // SAFETY: Safe because ptr created using Box::into_raw and closure cannot live
+                    // longer than 'a.
+                    let ctx = Box::from_raw(ctx);
+                    // SAFETY: Outside of call to f.
+                    ctx.dispose();
+                }
+            }),
+            child: Some((self, key)),
+        }
     }
 
     /// Cleanup the resources owned by the [`Scope`]. For more details, see
@@ -368,6 +858,10 @@ impl<'a> Scope<'a> {
     /// Dropping a [`Scope`] will automatically call [`dispose`](Self::dispose).
     pub(crate) unsafe fn dispose(&self) {
         let mut inner = self.inner.borrow_mut();
+        // Notify async tasks that the scope is going away.
+        if let Some(token) = inner.cancellation_token.take() {
+            token.cancel();
+        }
         // Drop child contexts.
         for &i in mem::take(&mut inner.child_scopes).values() {
             // SAFETY: These pointers were allocated in Self::create_child_scope.
@@ -438,9 +932,35 @@ pub fn on<'a, U, const N: usize>(
     }
 }
 
+/// Like [`on`], but for a list of signals of the same type that can be arbitrarily long. The
+/// signals are tracked with [`track_all`].
+///
+/// # Example
+/// ```
+/// # use sycamore_reactive::*;
+/// # create_scope_immediate(|ctx| {
+/// let fields: Vec<&ReadSignal<String>> =
+///     (0..50).map(|_| &**ctx.create_signal(String::new())).collect();
+///
+/// ctx.create_effect(on_all(fields, || {
+///     println!("A field changed");
+/// }));
+/// # });
+/// ```
+pub fn on_all<'a, T: 'a, U>(
+    dependencies: impl AsRef<[&'a ReadSignal<T>]> + 'a,
+    mut f: impl FnMut() -> U + 'a,
+) -> impl FnMut() -> U + 'a {
+    move || {
+        track_all(dependencies.as_ref());
+        #[allow(clippy::redundant_closure)] // Clippy false-positive
+        untrack(|| f())
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::{create_scope, create_scope_immediate};
+    use crate::*;
 
     #[test]
     fn refs() {
@@ -520,6 +1040,81 @@ mod tests {
         });
     }
 
+    fn nest_scopes(ctx: ScopeRef, remaining: u32) {
+        if remaining > 0 {
+            let _ = ctx.create_child_scope(|ctx| nest_scopes(*ctx, remaining - 1));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "maximum scope depth of 8 exceeded")]
+    fn scope_depth_guard() {
+        set_max_scope_depth(8);
+        create_scope_immediate(|ctx| nest_scopes(ctx, 9));
+    }
+
+    #[test]
+    fn scope_depth_guard_reports_caller_location() {
+        set_max_scope_depth(0);
+        let line = Cell::new(0);
+        let err = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            create_scope_immediate(|ctx| {
+                let _ = (line.set(line!()), ctx.create_child_scope(|_| {}));
+            })
+        }))
+        .unwrap_err();
+        let message = err.downcast_ref::<String>().unwrap();
+        let location = format!("{}:{}:", file!(), line.get());
+        assert!(
+            message.contains(&location),
+            "panic message should point to {location}, got: {message}"
+        );
+    }
+
+    #[test]
+    fn scope_depth_guard_allows_limit() {
+        set_max_scope_depth(8);
+        create_scope_immediate(|ctx| {
+            nest_scopes(ctx, 8);
+            // Disposed siblings should not count towards the depth.
+            for _ in 0..16 {
+                let disposer = ctx.create_child_scope(|ctx| nest_scopes(*ctx, 7));
+                unsafe {
+                    disposer.dispose();
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn scope_depth_matches_nesting() {
+        create_scope_immediate(|ctx| {
+            assert_eq!(ctx.scope_depth(), 0);
+            let _ = ctx.create_child_scope(|child| {
+                assert_eq!(child.scope_depth(), 1);
+                let _ = child.create_child_scope(|grandchild| {
+                    assert_eq!(grandchild.scope_depth(), 2);
+                });
+            });
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "maximum scope depth of 2 exceeded when attaching a child scope")]
+    fn scope_depth_guard_on_reattach() {
+        set_max_scope_depth(2);
+        create_scope_immediate(|ctx| {
+            // The detached scope has a child scope so it needs two levels.
+            let child = ctx.create_child_scope(|ctx| {
+                let _ = ctx.create_child_scope(|_| {});
+            });
+            let detached = ctx.detach_child(child);
+            let _ = ctx.create_child_scope(|new_parent| {
+                let _ = new_parent.reattach(detached);
+            });
+        });
+    }
+
     #[test]
     fn can_store_disposer_in_own_signal() {
         create_scope_immediate(|ctx| {
@@ -528,4 +1123,316 @@ mod tests {
             signal.set(Some(disposer));
         });
     }
+
+    #[test]
+    fn create_child_scope_with_safe() {
+        create_scope_immediate(|ctx| {
+            let state = ctx.create_signal(1);
+            let disposed = ctx.create_ref(Cell::new(false));
+            let (doubled, disposer) = ctx.create_child_scope_with_safe(|child| {
+                child.on_cleanup(|| disposed.set(true));
+                ctx.create_memo(|| *state.get() * 2)
+            });
+            assert_eq!(*doubled.get(), 2);
+            state.set(2);
+            assert_eq!(*doubled.get(), 4);
+
+            assert!(!disposed.get());
+            disposer.dispose();
+            assert!(disposed.get());
+
+            // The memo belongs to the parent scope so it outlives the child scope.
+            state.set(3);
+            assert_eq!(*doubled.get(), 6);
+        });
+    }
+
+    #[test]
+    fn dispose_safe_disposer_inside_own_effect() {
+        create_scope_immediate(|ctx| {
+            let trigger = ctx.create_signal(());
+            let disposer = ctx.create_ref(RefCell::new(None));
+            let runs = ctx.create_ref(Cell::new(0));
+            let sum = ctx.create_ref(Cell::new(0));
+            let cleanups = ctx.create_ref(Cell::new(0));
+
+            let ((), child) = ctx.create_child_scope_with_safe(|child| {
+                let data = child.create_ref(vec![1, 2, 3]);
+                child.create_effect(move || {
+                    trigger.track();
+                    runs.set(runs.get() + 1);
+                    let disposer: Option<SafeScopeDisposer> = disposer.borrow_mut().take();
+                    if let Some(disposer) = disposer {
+                        disposer.dispose();
+                        // The scope is only disposed once the effect has returned.
+                        assert_eq!(cleanups.get(), 0);
+                        sum.set(data.iter().sum());
+                    }
+                });
+                child.on_cleanup(|| cleanups.set(cleanups.get() + 1));
+            });
+            *disposer.borrow_mut() = Some(child);
+
+            trigger.set(());
+            assert_eq!(runs.get(), 2);
+            assert_eq!(sum.get(), 6);
+            assert_eq!(cleanups.get(), 1);
+
+            trigger.set(());
+            assert_eq!(runs.get(), 2, "the effect should be disposed");
+        });
+    }
+
+    #[test]
+    fn batch_children() {
+        create_scope_immediate(|ctx| {
+            let total = ctx.create_signal(0);
+            let runs = ctx.create_ref(Cell::new(0));
+            ctx.create_effect(|| {
+                total.track();
+                runs.set(runs.get() + 1);
+            });
+
+            ctx.batch_children(|| {
+                for _ in 0..100 {
+                    let _ = ctx.create_child_scope(|ctx| {
+                        ctx.create_effect(|| total.set(*total.get_untracked() + 1));
+                    });
+                }
+                assert_eq!(runs.get(), 1);
+            });
+            assert_eq!(*total.get(), 100);
+            assert_eq!(runs.get(), 2);
+        });
+    }
+
+    #[test]
+    fn dispose_children() {
+        create_scope_immediate(|ctx| {
+            let trigger = ctx.create_signal(0);
+            let parent_runs = ctx.create_ref(Cell::new(0));
+            let child_runs = ctx.create_ref(Cell::new(0));
+            let cleanups = ctx.create_ref(Cell::new(0));
+            ctx.create_effect(|| {
+                trigger.track();
+                parent_runs.set(parent_runs.get() + 1);
+            });
+
+            let mut disposers = Vec::new();
+            for _ in 0..3 {
+                disposers.push(ctx.create_child_scope(|ctx| {
+                    ctx.create_effect(|| {
+                        trigger.track();
+                        child_runs.set(child_runs.get() + 1);
+                    });
+                    ctx.on_cleanup(|| cleanups.set(cleanups.get() + 1));
+                    // Nested child scopes are disposed too.
+                    let _ = ctx.create_child_scope(|ctx| {
+                        ctx.on_cleanup(|| cleanups.set(cleanups.get() + 1));
+                    });
+                }));
+            }
+            trigger.set(1);
+            assert_eq!(parent_runs.get(), 2);
+            assert_eq!(child_runs.get(), 6);
+
+            unsafe { ctx.dispose_children() };
+            assert_eq!(cleanups.get(), 6);
+            trigger.set(2);
+            assert_eq!(parent_runs.get(), 3, "the parent effect should still run");
+            assert_eq!(child_runs.get(), 6, "the child effects should be stopped");
+
+            // The disposers of the child scopes do nothing anymore.
+            for disposer in disposers {
+                unsafe { disposer.dispose() };
+            }
+            assert_eq!(cleanups.get(), 6);
+
+            // New child scopes can still be created.
+            let _ = ctx.create_child_scope(|ctx| {
+                ctx.create_effect(|| {
+                    trigger.track();
+                    child_runs.set(child_runs.get() + 1);
+                });
+            });
+            assert_eq!(child_runs.get(), 7);
+        });
+    }
+
+    #[test]
+    fn detach_and_reattach_child_scope() {
+        create_scope_immediate(|ctx| {
+            let trigger = ctx.create_signal(());
+            let inits = ctx.create_ref(Cell::new(0));
+            let observed = ctx.create_ref(Cell::new(0));
+            let cleanups = ctx.create_ref(Cell::new(0));
+
+            let child = ctx.create_child_scope(|ctx| {
+                inits.set(inits.get() + 1);
+                let count = ctx.create_signal(0);
+                ctx.create_effect(move || {
+                    trigger.track();
+                    count.set(*count.get_untracked() + 1);
+                    observed.set(*count.get_untracked());
+                });
+                ctx.on_cleanup(|| cleanups.set(cleanups.get() + 1));
+            });
+            assert_eq!(observed.get(), 1);
+
+            // Hide the child: disposing the children of the parent does not dispose it.
+            let detached = ctx.detach_child(child);
+            unsafe { ctx.dispose_children() };
+            assert_eq!(cleanups.get(), 0);
+            trigger.set(());
+            assert_eq!(observed.get(), 2);
+
+            // Show it again without re-initializing it.
+            let child = ctx.reattach(detached);
+            trigger.set(());
+            assert_eq!(observed.get(), 3, "the state should survive");
+            assert_eq!(inits.get(), 1);
+            assert_eq!(cleanups.get(), 0);
+
+            unsafe { child.dispose() };
+            assert_eq!(cleanups.get(), 1);
+            trigger.set(());
+            assert_eq!(observed.get(), 3);
+        });
+    }
+
+    #[test]
+    fn dropping_detached_scope_disposes_it() {
+        create_scope_immediate(|ctx| {
+            let trigger = ctx.create_signal(());
+            let runs = ctx.create_ref(Cell::new(0));
+            let cleanups = ctx.create_ref(Cell::new(0));
+
+            let child = ctx.create_child_scope(|ctx| {
+                ctx.create_effect(|| {
+                    trigger.track();
+                    runs.set(runs.get() + 1);
+                });
+                ctx.on_cleanup(|| cleanups.set(cleanups.get() + 1));
+            });
+            assert_eq!(runs.get(), 1);
+
+            drop(ctx.detach_child(child));
+            assert_eq!(cleanups.get(), 1);
+            trigger.set(());
+            assert_eq!(
+                runs.get(),
+                1,
+                "the effects of the scope should not run anymore"
+            );
+        });
+    }
+
+    #[test]
+    fn drop_detached_scope_inside_own_effect() {
+        create_scope_immediate(|ctx| {
+            let trigger = ctx.create_signal(());
+            let detached = ctx.create_ref(RefCell::new(None));
+            let runs = ctx.create_ref(Cell::new(0));
+            let sum = ctx.create_ref(Cell::new(0));
+            let cleanups = ctx.create_ref(Cell::new(0));
+
+            let child = ctx.create_child_scope(|child| {
+                let data = child.create_ref(vec![1, 2, 3]);
+                child.create_effect(move || {
+                    trigger.track();
+                    runs.set(runs.get() + 1);
+                    let detached: Option<DetachedScope> = detached.borrow_mut().take();
+                    if detached.is_some() {
+                        drop(detached);
+                        // The scope is only disposed once the effect has returned.
+                        assert_eq!(cleanups.get(), 0);
+                        sum.set(data.iter().sum());
+                    }
+                });
+                child.on_cleanup(|| cleanups.set(cleanups.get() + 1));
+            });
+            *detached.borrow_mut() = Some(ctx.detach_child(child));
+
+            trigger.set(());
+            assert_eq!(runs.get(), 2);
+            assert_eq!(sum.get(), 6);
+            assert_eq!(cleanups.get(), 1);
+
+            trigger.set(());
+            assert_eq!(runs.get(), 2, "the effect should be disposed");
+        });
+    }
+
+    #[test]
+    fn reattach_to_descendant_scope() {
+        create_scope_immediate(|ctx| {
+            let trigger = ctx.create_signal(());
+            let log = ctx.create_ref(RefCell::new(Vec::new()));
+
+            let child = ctx.create_child_scope(|child| {
+                let child = *child;
+                child.create_effect(move || {
+                    trigger.track();
+                    log.borrow_mut()
+                        .push(child.try_use_context::<&str>().copied());
+                });
+            });
+            let detached = ctx.detach_child(child);
+
+            let new_parent = ctx.create_child_scope(|new_parent| {
+                new_parent.provide_context("new");
+                let _ = new_parent.reattach(detached);
+            });
+            trigger.set(());
+            assert_eq!(*log.borrow(), [None, Some("new")]);
+
+            unsafe { new_parent.dispose() };
+            trigger.set(());
+            assert_eq!(log.borrow().len(), 2);
+        });
+    }
+
+    #[test]
+    fn reparent_child_scope() {
+        create_scope_immediate(|ctx| {
+            let trigger = ctx.create_signal(());
+            let log = ctx.create_ref(RefCell::new(Vec::new()));
+            let owner = ctx.create_ref(Cell::new(None));
+            let disposed = ctx.create_ref(Cell::new(false));
+
+            let new_parent = ctx.create_child_scope(|new_parent| {
+                new_parent.provide_context("new");
+                let old_parent = ctx.create_child_scope(|old_parent| {
+                    old_parent.provide_context("old");
+                    let child = old_parent.create_child_scope(|child| {
+                        let child = *child;
+                        child.create_effect(move || {
+                            trigger.track();
+                            log.borrow_mut().push(*child.use_context::<&str>());
+                            owner.set(child.owner().map(|owner| owner.cast::<()>()));
+                        });
+                        child.on_cleanup(|| disposed.set(true));
+                    });
+                    // SAFETY: The child scope only borrows from the root scope.
+                    let _ = unsafe { child.reparent(*new_parent) };
+                    assert_eq!(*log.borrow(), ["old"], "effects should not be re-run");
+                });
+
+                trigger.set(());
+                assert_eq!(*log.borrow(), ["old", "new"]);
+                assert_eq!(owner.get(), Some(*new_parent as *const _ as *const ()));
+
+                // The child scope is no longer owned by its former parent.
+                unsafe { old_parent.dispose() };
+                assert!(!disposed.get());
+                trigger.set(());
+                assert_eq!(*log.borrow(), ["old", "new", "new"]);
+            });
+
+            unsafe { new_parent.dispose() };
+            assert!(disposed.get());
+            trigger.set(());
+            assert_eq!(log.borrow().len(), 3);
+        });
+    }
 }
@@ -1,9 +1,98 @@
 //! Derived and computed data.
 
 use std::cell::Cell;
+use std::collections::HashSet;
+use std::hash::Hash;
+use std::panic::Location;
 
 use crate::*;
 
+/// The maximum number of distinct values kept by the intern table of
+/// [`Scope::create_interned_memo`]. The table is cleared when it is full.
+const MAX_INTERNED_VALUES: usize = 64;
+
+/// Registers cleanup callbacks for [`Scope::create_memo_effect_cleanup`] and
+/// [`Scope::create_effect_with_cleanup`].
+pub struct CleanupRegistrar<'a, 'bound: 'a>(pub(crate) BoundedScopeRef<'a, 'bound>);
+
+impl<'a, 'bound> CleanupRegistrar<'a, 'bound> {
+    /// Adds a callback that is called before the computation is re-run, or when the scope is
+    /// disposed.
+    pub fn on_cleanup(&self, f: impl FnOnce() + 'bound) {
+        self.0.on_cleanup(f);
+    }
+}
+
+/// A middleware for [`Scope::create_reducer_with_middleware`].
+///
+/// It is called with the state, the dispatched message and a `next` function that passes the
+/// message on to the next middleware, or to the reducer for the last middleware. A middleware can
+/// run code before and after calling `next`, replace the message, or drop it by not calling `next`.
+pub type ReducerMiddleware<'a, U, Msg> = Box<dyn Fn(&ReadSignal<U>, Msg, &dyn Fn(Msg)) + 'a>;
+
+/// Passes `msg` through `middleware` and then to `reduce`.
+fn dispatch_through<U, Msg>(
+    state: &Signal<U>,
+    reduce: &dyn Fn(&U, Msg) -> U,
+    middleware: &[ReducerMiddleware<'_, U, Msg>],
+    msg: Msg,
+) {
+    match middleware.split_first() {
+        Some((first, rest)) => first(state, msg, &|msg| {
+            dispatch_through(state, reduce, rest, msg)
+        }),
+        None => state.set(reduce(&state.get_untracked(), msg)),
+    }
+}
+
+/// The value of a [`Scope::create_vec_selector`]: a `Vec` along with the indices of the items that
+/// changed in the last update.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VecDiff<T> {
+    /// The current items.
+    pub items: Vec<T>,
+    /// The indices of the items that are different from the items at the same indices before the
+    /// last update, in increasing order. This includes the indices of added and removed items
+    /// when the length changed. All the indices are included for the initial value.
+    pub changed: Vec<usize>,
+}
+
+/// Returns the indices at which `old` and `new` differ.
+fn changed_indices<T: PartialEq>(old: &[T], new: &[T]) -> Vec<usize> {
+    (0..old.len().max(new.len()))
+        .filter(|&i| old.get(i) != new.get(i))
+        .collect()
+}
+
+/// A derived value that can also be written to.
+///
+/// Created with [`Scope::create_writable_derived`]. The current value can be read through the
+/// [`ReadSignal`] methods, which are available via [`Deref`].
+pub struct WritableDerived<'a, T> {
+    value: &'a ReadSignal<T>,
+    set: Box<dyn Fn(T) + 'a>,
+}
+
+impl<'a, T: PartialEq> WritableDerived<'a, T> {
+    /// Write a new value by calling the `set` closure, which updates the sources that the value is
+    /// derived from. The value is then recomputed from the updated sources.
+    ///
+    /// Does nothing if `value` is equal to the current value.
+    pub fn set(&self, value: T) {
+        if value != *self.value.get_untracked() {
+            (self.set)(value);
+        }
+    }
+}
+
+impl<'a, T> Deref for WritableDerived<'a, T> {
+    type Target = ReadSignal<T>;
+
+    fn deref(&self) -> &Self::Target {
+        self.value
+    }
+}
+
 impl<'a> Scope<'a> {
     /// Creates a memoized computation from some signals.
     /// The output is derived from all the signals that are used within the memo closure.
@@ -32,6 +121,9 @@ impl<'a> Scope<'a> {
     /// Memos, on the other hand, are eagerly evaluated and will only run the computation when one
     /// of its dependencies change.
     ///
+    /// An effect never observes a stale memo: if it reads a memo whose dependencies have changed
+    /// but that has not been recomputed yet, the memo is recomputed first.
+    ///
     /// Memos also incur a slightly higher performance penalty than simple derived signals.
     ///
     /// # Example
@@ -46,15 +138,176 @@ impl<'a> Scope<'a> {
     /// assert_eq!(*double.get(), 2);
     /// # });
     /// ```
+    ///
+    /// # Capturing owned values
+    /// The closure only needs to live as long as the scope, so values that are owned by the caller,
+    /// such as component props that are passed by value, can be moved into it with a `move`
+    /// closure. Signals are references, so they are copied into the closure instead.
+    /// ```
+    /// # use sycamore_reactive::*;
+    /// fn greeting<'a>(
+    ///     ctx: ScopeRef<'a>,
+    ///     name: String,
+    ///     excited: &'a ReadSignal<bool>,
+    /// ) -> &'a ReadSignal<String> {
+    ///     ctx.create_memo(move || {
+    ///         let punctuation = if *excited.get() { "!" } else { "." };
+    ///         format!("Hello {name}{punctuation}")
+    ///     })
+    /// }
+    /// # create_scope_immediate(|ctx| {
+    /// let excited = ctx.create_signal(false);
+    /// let greeting = greeting(ctx, "World".to_string(), excited);
+    /// assert_eq!(*greeting.get(), "Hello World.");
+    /// excited.set(true);
+    /// assert_eq!(*greeting.get(), "Hello World!");
+    /// # });
+    /// ```
+    #[track_caller]
     pub fn create_memo<U: 'a>(&'a self, f: impl FnMut() -> U + 'a) -> &'a ReadSignal<U> {
         self.create_selector_with(f, |_, _| false)
     }
 
+    /// Creates a memoized computation that can also be recomputed manually.
+    ///
+    /// Returns the memo and an `invalidate` function. Calling `invalidate` re-runs the computation
+    /// and notifies dependents, just like when one of the tracked signals is updated. Dependencies
+    /// are collected again when the computation re-runs.
+    ///
+    /// This is useful when the computation reads state that is not reactive, such as a value that
+    /// is updated outside of sycamore.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::cell::Cell;
+    /// # use sycamore_reactive::*;
+    /// # create_scope_immediate(|ctx| {
+    /// let external = ctx.create_ref(Cell::new(1));
+    /// let (double, invalidate) = ctx.create_memo_invalidatable(|| external.get() * 2);
+    /// assert_eq!(*double.get(), 2);
+    ///
+    /// external.set(2);
+    /// assert_eq!(*double.get(), 2); // The memo does not know about the update yet.
+    /// invalidate();
+    /// assert_eq!(*double.get(), 4);
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn create_memo_invalidatable<U: 'a>(
+        &'a self,
+        mut f: impl FnMut() -> U + 'a,
+    ) -> (&'a ReadSignal<U>, impl Fn() + 'a) {
+        let trigger = self.create_signal(());
+        let memo = self.create_memo(move || {
+            trigger.track();
+            f()
+        });
+        let invalidate = move || trigger.set(());
+
+        (memo, invalidate)
+    }
+
+    /// Creates a memoized computation that also keeps track of how many times it was computed.
+    ///
+    /// Returns the memo and a generation signal. The generation is incremented every time the
+    /// computation runs, starting at `1` for the initial computation, and is left untouched when
+    /// the memo is read or when no dependency was updated. This is useful for checking that
+    /// updates are skipped as expected, for instance by an upstream
+    /// [`create_selector`](Self::create_selector).
+    ///
+    /// # Example
+    /// ```
+    /// # use sycamore_reactive::*;
+    /// # create_scope_immediate(|ctx| {
+    /// let state = ctx.create_signal(0);
+    /// let (double, generation) = ctx.create_memo_with_generation(|| *state.get() * 2);
+    /// assert_eq!(*generation.get(), 1);
+    ///
+    /// let _ = double.get();
+    /// assert_eq!(*generation.get(), 1);
+    /// state.set(1);
+    /// assert_eq!(*generation.get(), 2);
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn create_memo_with_generation<U: 'a>(
+        &'a self,
+        mut f: impl FnMut() -> U + 'a,
+    ) -> (&'a ReadSignal<U>, &'a ReadSignal<u64>) {
+        let generation = self.create_signal(0);
+        let location = Location::caller();
+        let memo = self.create_memo(move || {
+            let new = f();
+            generation.inherit_height();
+            generation.set_at(*generation.get_untracked() + 1, location);
+            new
+        });
+
+        (memo, generation)
+    }
+
+    /// Creates a memoized computation that can also perform side effects and clean them up.
+    ///
+    /// The closure receives a [`CleanupRegistrar`] for registering cleanup callbacks for the
+    /// side effects it performs. Every time one of the tracked signals is updated, the cleanup
+    /// callbacks from the previous run are called first, then the closure is re-run and finally
+    /// the new value is stored in the memo. The cleanup callbacks from the last run are called when
+    /// the scope is disposed.
+    ///
+    /// # Example
+    /// ```
+    /// # use sycamore_reactive::*;
+    /// # create_scope_immediate(|ctx| {
+    /// let interval = ctx.create_signal(1000);
+    /// let description = ctx.create_memo_effect_cleanup(|cleanup| {
+    ///     let interval = *interval.get();
+    ///     // Start a timer that ticks every `interval` milliseconds...
+    ///     cleanup.on_cleanup(|| {
+    ///         // ...and stop it before the next one is started.
+    ///     });
+    ///     format!("ticking every {interval}ms")
+    /// });
+    /// assert_eq!(*description.get(), "ticking every 1000ms");
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn create_memo_effect_cleanup<U: 'a>(
+        &'a self,
+        mut f: impl for<'child_lifetime> FnMut(CleanupRegistrar<'child_lifetime, 'a>) -> U + 'a,
+    ) -> &'a ReadSignal<U> {
+        let signal: Rc<Cell<Option<&Signal<U>>>> = Default::default();
+        let location = Location::caller();
+
+        // The previous child scope, and therefore the cleanup callbacks registered in it, is
+        // disposed before the closure is re-run.
+        self.create_effect_scoped({
+            let signal = signal.clone();
+            move |ctx| {
+                let new = f(CleanupRegistrar(ctx));
+                if let Some(signal) = signal.get() {
+                    signal.inherit_height();
+                    signal.set_at(new, location);
+                } else {
+                    let new = self.create_signal(new);
+                    new.inherit_height();
+                    // Recompute the memo if it is read while it is queued to be recomputed.
+                    new.inherit_source();
+                    signal.set(Some(new));
+                }
+            }
+        });
+
+        signal.get().unwrap()
+    }
+
     /// Creates a memoized value from some signals.
     /// Unlike [`create_memo`](Self::create_memo), this function will not notify dependents of a
     /// change if the output is the same. That is why the output of the function must implement
     /// [`PartialEq`].
     ///
+    /// Because dependents are not notified, nothing downstream of the selector is re-run either,
+    /// including memos derived from it and their own dependents.
+    ///
     /// To specify a custom comparison function, use
     /// [`create_selector_with`](Self::create_selector_with).
     ///
@@ -70,6 +323,7 @@ impl<'a> Scope<'a> {
     /// assert_eq!(*double.get(), 2);
     /// # });
     /// ```
+    #[track_caller]
     pub fn create_selector<U: PartialEq + 'a>(
         &'a self,
         f: impl FnMut() -> U + 'a,
@@ -86,24 +340,33 @@ impl<'a> Scope<'a> {
     ///
     /// To use the type's [`PartialEq`] implementation instead of a custom function, use
     /// [`create_selector`](Self::create_selector).
+    #[track_caller]
     pub fn create_selector_with<U: 'a>(
         &'a self,
         mut f: impl FnMut() -> U + 'a,
         eq_f: impl Fn(&U, &U) -> bool + 'a,
     ) -> &'a ReadSignal<U> {
         let signal: Rc<Cell<Option<&Signal<U>>>> = Default::default();
+        let location = Location::caller();
 
         self.create_effect({
             let signal = signal.clone();
             move || {
                 let new = f();
                 if let Some(signal) = signal.get() {
+                    // Update the height before notifying dependents so that they are queued
+                    // after any memo this one now depends on.
+                    signal.inherit_height();
                     // Check if new value is different from old value.
                     if !eq_f(&new, &*signal.get_untracked()) {
-                        signal.set(new)
+                        signal.set_at(new, location)
                     }
                 } else {
-                    signal.set(Some(self.create_signal(new)))
+                    let new = self.create_signal(new);
+                    new.inherit_height();
+                    // Recompute the memo if it is read while it is queued to be recomputed.
+                    new.inherit_source();
+                    signal.set(Some(new))
                 }
             }
         });
@@ -111,6 +374,182 @@ impl<'a> Scope<'a> {
         signal.get().unwrap()
     }
 
+    /// Creates a memoized computation whose results are interned, so that equal values share the
+    /// same [`Rc`].
+    ///
+    /// This is useful for memos that keep computing one of a small set of values, such as a status
+    /// label, since only the first occurrence of each value is kept and cloning the result does
+    /// not allocate. Equal results can also be compared cheaply with [`Rc::ptr_eq`]. Like
+    /// [`create_memo`](Self::create_memo), dependents are notified every time the memo is
+    /// recomputed.
+    ///
+    /// The intern table belongs to the memo and is dropped along with the scope. It holds at most
+    /// 64 distinct values and is cleared when it is full, so memos with many distinct results do
+    /// not keep them alive forever, but do not benefit from interning either.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::rc::Rc;
+    /// # use sycamore_reactive::*;
+    /// # create_scope_immediate(|ctx| {
+    /// let count = ctx.create_signal(0);
+    /// let label = ctx.create_interned_memo(|| {
+    ///     if *count.get() == 0 { "empty".to_string() } else { "non-empty".to_string() }
+    /// });
+    ///
+    /// count.set(1);
+    /// let first = label.get_untracked().as_ref().clone();
+    /// count.set(2);
+    /// assert!(Rc::ptr_eq(&first, &*label.get_untracked()));
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn create_interned_memo<T: Hash + Eq + 'a>(
+        &'a self,
+        mut f: impl FnMut() -> T + 'a,
+    ) -> &'a ReadSignal<Rc<T>> {
+        let mut interned = HashSet::<Rc<T>>::new();
+        self.create_memo(move || {
+            let value = f();
+            if let Some(value) = interned.get(&value) {
+                return Rc::clone(value);
+            }
+            if interned.len() >= MAX_INTERNED_VALUES {
+                interned.clear();
+            }
+            let value = Rc::new(value);
+            interned.insert(Rc::clone(&value));
+            value
+        })
+    }
+
+    /// Creates a memoized optional value from some signals.
+    /// Unlike [`create_memo`](Self::create_memo), this function will not notify dependents if the
+    /// output is `None` and was already `None` before. This prevents downstream computations from
+    /// re-running when there is no value to derive from.
+    ///
+    /// A `Some` output always notifies dependents. To also skip notifying dependents when the
+    /// `Some` payload is unchanged, use [`create_memo_opt_with`](Self::create_memo_opt_with).
+    ///
+    /// # Example
+    /// ```
+    /// # use sycamore_reactive::*;
+    /// # create_scope_immediate(|ctx| {
+    /// let state = ctx.create_signal(-1);
+    /// let sqrt = ctx.create_memo_opt(|| (*state.get() >= 0).then(|| (*state.get() as f64).sqrt()));
+    ///
+    /// assert_eq!(*sqrt.get(), None);
+    /// state.set(-4); // Does not notify dependents of `sqrt`.
+    /// assert_eq!(*sqrt.get(), None);
+    /// state.set(4);
+    /// assert_eq!(*sqrt.get(), Some(2.0));
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn create_memo_opt<U: 'a>(
+        &'a self,
+        f: impl FnMut() -> Option<U> + 'a,
+    ) -> &'a ReadSignal<Option<U>> {
+        self.create_selector_with(f, |a, b| a.is_none() && b.is_none())
+    }
+
+    /// Creates a memoized optional value from some signals.
+    /// Like [`create_memo_opt`](Self::create_memo_opt), this function will not notify dependents
+    /// if the output is `None` and was already `None` before. Additionally, dependents are not
+    /// notified if both the old and new values are `Some` and `eq_f` returns `true` for their
+    /// payloads.
+    #[track_caller]
+    pub fn create_memo_opt_with<U: 'a>(
+        &'a self,
+        f: impl FnMut() -> Option<U> + 'a,
+        eq_f: impl Fn(&U, &U) -> bool + 'a,
+    ) -> &'a ReadSignal<Option<U>> {
+        self.create_selector_with(f, move |a, b| match (a, b) {
+            (None, None) => true,
+            (Some(a), Some(b)) => eq_f(a, b),
+            _ => false,
+        })
+    }
+
+    /// Creates a memoized `Vec` from some signals, along with the indices of the items that
+    /// changed in each update. See [`VecDiff`].
+    ///
+    /// Like [`create_selector`](Self::create_selector), dependents are only notified if at least
+    /// one item changed. They can then use [`VecDiff::changed`] to only process the items that
+    /// changed instead of the whole list.
+    ///
+    /// # Example
+    /// ```
+    /// # use sycamore_reactive::*;
+    /// # create_scope_immediate(|ctx| {
+    /// let state = ctx.create_signal(vec![1, 2, 3]);
+    /// let doubled = ctx.create_vec_selector(|| state.get().iter().map(|x| x * 2).collect());
+    /// assert_eq!(doubled.get().changed, [0, 1, 2]);
+    ///
+    /// state.set(vec![1, 5, 3]);
+    /// assert_eq!(doubled.get().items, [2, 10, 6]);
+    /// assert_eq!(doubled.get().changed, [1]);
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn create_vec_selector<T: PartialEq + 'a>(
+        &'a self,
+        mut f: impl FnMut() -> Vec<T> + 'a,
+    ) -> &'a ReadSignal<VecDiff<T>> {
+        let output: Rc<Cell<Option<&'a ReadSignal<VecDiff<T>>>>> = Default::default();
+        let signal = self.create_selector_with(
+            {
+                let output = Rc::clone(&output);
+                move || {
+                    let items = f();
+                    let changed = match output.get() {
+                        Some(output) => changed_indices(&output.get_untracked().items, &items),
+                        None => (0..items.len()).collect(),
+                    };
+                    VecDiff { items, changed }
+                }
+            },
+            |new, _| new.changed.is_empty(),
+        );
+        output.set(Some(signal));
+        signal
+    }
+
+    /// Creates a derived value that can also be written to.
+    ///
+    /// Reading the value computes it from its sources with `get`, like
+    /// [`create_selector`](Self::create_selector). Writing it with [`WritableDerived::set`] calls
+    /// `set`, which should update the sources accordingly. Writes that do not change the value,
+    /// and source updates that do not change the derived value, are skipped so that `get` and `set`
+    /// cannot trigger each other in a loop.
+    ///
+    /// # Example
+    /// ```
+    /// # use sycamore_reactive::*;
+    /// # create_scope_immediate(|ctx| {
+    /// let celsius = ctx.create_signal(100.0);
+    /// let fahrenheit = ctx.create_writable_derived(
+    ///     || *celsius.get() * 9.0 / 5.0 + 32.0,
+    ///     |f| celsius.set((f - 32.0) * 5.0 / 9.0),
+    /// );
+    /// assert_eq!(*fahrenheit.get(), 212.0);
+    ///
+    /// fahrenheit.set(32.0);
+    /// assert_eq!(*celsius.get(), 0.0);
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn create_writable_derived<T: PartialEq + 'a>(
+        &'a self,
+        get: impl Fn() -> T + 'a,
+        set: impl Fn(T) + 'a,
+    ) -> &'a WritableDerived<'a, T> {
+        self.create_ref(WritableDerived {
+            value: self.create_selector(get),
+            set: Box::new(set),
+        })
+    }
+
     /// An alternative to [`create_signal`](Self::create_signal) that uses a reducer to get the next
     /// value.
     ///
@@ -158,6 +597,49 @@ impl<'a> Scope<'a> {
 
         (&*memo, dispatcher)
     }
+
+    /// Same as [`create_reducer`](Self::create_reducer) but every message is first passed through
+    /// `middleware`, in order, before reaching the reducer. See [`ReducerMiddleware`].
+    ///
+    /// This is useful for logging or validating messages.
+    ///
+    /// # Example
+    /// ```
+    /// # use sycamore_reactive::*;
+    /// # create_scope_immediate(|ctx| {
+    /// let logger: ReducerMiddleware<i32, i32> = Box::new(|state, msg, next| {
+    ///     println!("Before: {}, adding {msg}", state.get_untracked());
+    ///     next(msg);
+    ///     println!("After: {}", state.get_untracked());
+    /// });
+    /// let non_negative: ReducerMiddleware<i32, i32> = Box::new(|_, msg, next| {
+    ///     if msg >= 0 {
+    ///         next(msg);
+    ///     }
+    /// });
+    /// let (state, dispatch) = ctx.create_reducer_with_middleware(
+    ///     0,
+    ///     |state, msg| state + msg,
+    ///     vec![logger, non_negative],
+    /// );
+    ///
+    /// dispatch(1);
+    /// dispatch(-1);
+    /// assert_eq!(*state.get(), 1);
+    /// # });
+    /// ```
+    pub fn create_reducer_with_middleware<U: 'a, Msg: 'a>(
+        &'a self,
+        initial: U,
+        reduce: impl Fn(&U, Msg) -> U + 'a,
+        middleware: Vec<ReducerMiddleware<'a, U, Msg>>,
+    ) -> (&'a ReadSignal<U>, impl Fn(Msg) + 'a) {
+        let state = self.create_signal(initial);
+
+        let dispatcher = move |msg| dispatch_through(state, &reduce, &middleware, msg);
+
+        (state, dispatcher)
+    }
 }
 
 #[cfg(test)]
@@ -198,6 +680,117 @@ mod tests {
         });
     }
 
+    /// The cached value is shared by all readers instead of being recomputed for each of them.
+    #[test]
+    fn memo_run_once_with_many_readers() {
+        create_scope_immediate(|ctx| {
+            let state = ctx.create_signal(0);
+
+            let runs = ctx.create_ref(Cell::new(0));
+            let double = ctx.create_memo(|| {
+                runs.set(runs.get() + 1);
+                *state.get() * 2
+            });
+
+            let sum = ctx.create_ref(Cell::new(0));
+            for _ in 0..100 {
+                ctx.create_effect(|| sum.set(sum.get() + *double.get()));
+            }
+            assert_eq!(runs.get(), 1);
+
+            state.set(1);
+            assert_eq!(runs.get(), 2);
+            assert_eq!(sum.get(), 200);
+        });
+    }
+
+    /// A memo can capture owned data, e.g. props passed by value to a component, along with
+    /// signals.
+    #[test]
+    fn memo_with_owned_capture() {
+        fn component<'a>(
+            ctx: ScopeRef<'a>,
+            prop: String,
+            count: &'a ReadSignal<usize>,
+            runs: &'a Cell<usize>,
+        ) -> &'a ReadSignal<String> {
+            ctx.create_memo(move || {
+                runs.set(runs.get() + 1);
+                prop.repeat(*count.get())
+            })
+        }
+
+        create_scope_immediate(|ctx| {
+            let count = ctx.create_signal(1);
+            let runs = ctx.create_ref(Cell::new(0));
+            let repeated = component(ctx, "ab".to_string(), count, runs);
+            assert_eq!(*repeated.get(), "ab");
+            assert_eq!(runs.get(), 1);
+
+            count.set(3);
+            assert_eq!(*repeated.get(), "ababab");
+            assert_eq!(runs.get(), 2);
+        });
+    }
+
+    /// An effect that is queued before a memo it reads (here because it only starts reading the
+    /// memo after the update) still observes the fresh value.
+    #[cfg(feature = "devtools")]
+    #[test]
+    fn memo_changes_report_creation_location() {
+        create_scope_immediate(|ctx| {
+            let state = ctx.create_signal(0);
+            let (line, double) = (line!(), ctx.create_memo(|| *state.get() * 2));
+            let (selector_line, selector) = (line!(), ctx.create_selector(|| *state.get() / 2));
+
+            state.set(2);
+            let location = double.recent_changes()[0].location;
+            assert_eq!((location.file(), location.line()), (file!(), line));
+            let location = selector.recent_changes()[0].location;
+            assert_eq!((location.file(), location.line()), (file!(), selector_line));
+        });
+    }
+
+    #[test]
+    fn memo_read_while_dirty_is_fresh() {
+        create_scope_immediate(|ctx| {
+            let state = ctx.create_signal(0);
+            let runs = ctx.create_ref(Cell::new(0));
+            let double = ctx.create_memo(|| {
+                runs.set(runs.get() + 1);
+                *state.get() * 2
+            });
+            let seen = ctx.create_ref(RefCell::new(Vec::new()));
+            ctx.create_effect(|| {
+                if *state.get() > 0 {
+                    seen.borrow_mut().push(*double.get());
+                }
+            });
+
+            state.set(1);
+            assert_eq!(*seen.borrow(), [2]);
+            assert_eq!(runs.get(), 2, "the memo should not be recomputed twice");
+        });
+    }
+
+    #[test]
+    fn memo_chain_read_while_dirty_is_fresh() {
+        create_scope_immediate(|ctx| {
+            let state = ctx.create_signal(0);
+            let double = ctx.create_memo(|| *state.get() * 2);
+            let plus_one = ctx.create_memo(|| *double.get() + 1);
+            let seen = ctx.create_ref(RefCell::new(Vec::new()));
+            ctx.create_effect(|| {
+                if *state.get() > 0 {
+                    seen.borrow_mut().push(*plus_one.get());
+                }
+            });
+
+            state.set(1);
+            assert_eq!(*seen.borrow(), [3]);
+        });
+    }
+
     #[test]
     fn dependency_on_memo() {
         create_scope_immediate(|ctx| {
@@ -225,6 +818,100 @@ mod tests {
         });
     }
 
+    #[test]
+    fn memo_invalidatable() {
+        create_scope_immediate(|ctx| {
+            let external = ctx.create_ref(Cell::new(1));
+            let state = ctx.create_signal(false);
+            let counter = ctx.create_signal(0);
+            let (memo, invalidate) = ctx.create_memo_invalidatable(|| {
+                counter.set(*counter.get_untracked() + 1);
+                if *state.get() {
+                    -external.get()
+                } else {
+                    external.get()
+                }
+            });
+            assert_eq!(*memo.get(), 1);
+
+            external.set(2);
+            assert_eq!(*memo.get(), 1, "external state is not tracked");
+            invalidate();
+            assert_eq!(*memo.get(), 2);
+            assert_eq!(*counter.get(), 2);
+
+            // Dependencies are still tracked after invalidating.
+            state.set(true);
+            assert_eq!(*memo.get(), -2);
+            assert_eq!(*counter.get(), 3);
+        });
+    }
+
+    #[test]
+    fn memo_invalidate_notifies_dependents() {
+        create_scope_immediate(|ctx| {
+            let external = ctx.create_ref(Cell::new(1));
+            let (memo, invalidate) = ctx.create_memo_invalidatable(|| external.get());
+            let double = ctx.create_memo(|| *memo.get() * 2);
+
+            external.set(3);
+            invalidate();
+            assert_eq!(*double.get(), 6);
+        });
+    }
+
+    #[test]
+    fn memo_generation() {
+        create_scope_immediate(|ctx| {
+            let state = ctx.create_signal(0);
+            let parity = ctx.create_selector(|| *state.get() % 2);
+            let (label, generation) =
+                ctx.create_memo_with_generation(|| format!("parity: {}", parity.get()));
+            assert_eq!(*label.get(), "parity: 0");
+            assert_eq!(*generation.get(), 1);
+
+            // The selector does not propagate the update.
+            state.set(2);
+            assert_eq!(*generation.get(), 1);
+
+            state.set(3);
+            assert_eq!(*label.get(), "parity: 1");
+            assert_eq!(*generation.get(), 2);
+        });
+    }
+
+    #[test]
+    fn memo_effect_cleanup() {
+        create_scope_immediate(|ctx| {
+            let state = ctx.create_signal(1);
+            let log = ctx.create_ref(RefCell::new(Vec::new()));
+            let disposer = ctx.create_child_scope(|ctx| {
+                let double = ctx.create_memo_effect_cleanup(move |cleanup| {
+                    let value = *state.get();
+                    log.borrow_mut().push(format!("run {value}"));
+                    cleanup.on_cleanup(move || log.borrow_mut().push(format!("cleanup {value}")));
+                    value * 2
+                });
+                assert_eq!(*double.get(), 2);
+
+                state.set(2);
+                assert_eq!(*double.get(), 4);
+                state.set(3);
+                assert_eq!(*double.get(), 6);
+            });
+            assert_eq!(
+                *log.borrow(),
+                ["run 1", "cleanup 1", "run 2", "cleanup 2", "run 3"]
+            );
+
+            unsafe {
+                disposer.dispose();
+            }
+            assert_eq!(log.borrow().last().unwrap(), "cleanup 3");
+            assert_eq!(log.borrow().len(), 6);
+        });
+    }
+
     #[test]
     fn selector() {
         create_scope_immediate(|ctx| {
@@ -250,6 +937,223 @@ mod tests {
         });
     }
 
+    #[test]
+    fn vec_selector() {
+        create_scope_immediate(|ctx| {
+            let state = ctx.create_signal(vec![1, 2, 3]);
+            let evens = ctx.create_selector(|| {
+                state
+                    .get()
+                    .iter()
+                    .filter(|x| *x % 2 == 0)
+                    .copied()
+                    .collect::<Vec<_>>()
+            });
+            let diff = ctx.create_vec_selector(|| state.get().iter().map(|x| x % 2).collect());
+            let runs = ctx.create_ref(Cell::new(0));
+            let changes = ctx.create_ref(RefCell::new(Vec::new()));
+            ctx.create_effect(|| {
+                evens.track();
+                runs.set(runs.get() + 1);
+            });
+            ctx.create_effect(|| changes.borrow_mut().push(diff.get().changed.clone()));
+            assert_eq!(runs.get(), 1);
+            assert_eq!(*changes.borrow(), [vec![0, 1, 2]]);
+
+            // The recomputed vectors are equal to the previous ones.
+            state.set(vec![3, 2, 1]);
+            assert_eq!(*evens.get(), [2]);
+            assert_eq!(runs.get(), 1, "equal vector should not notify dependents");
+            assert_eq!(changes.borrow().len(), 1);
+
+            state.set(vec![3, 2, 4, 6]);
+            assert_eq!(*evens.get(), [2, 4, 6]);
+            assert_eq!(runs.get(), 2);
+            assert_eq!(diff.get().items, [1, 0, 0, 0]);
+            assert_eq!(*changes.borrow(), [vec![0, 1, 2], vec![2, 3]]);
+
+            state.set(vec![1]);
+            assert_eq!(
+                *changes.borrow(),
+                [vec![0, 1, 2], vec![2, 3], vec![1, 2, 3]]
+            );
+        });
+    }
+
+    #[test]
+    fn selector_stops_propagation_in_chain() {
+        create_scope_immediate(|ctx| {
+            let state = ctx.create_signal(0);
+            let parity = ctx.create_selector(|| *state.get() % 2);
+
+            let memo_runs = ctx.create_ref(Cell::new(0));
+            let label = ctx.create_memo(|| {
+                memo_runs.set(memo_runs.get() + 1);
+                if *parity.get() == 0 {
+                    "even"
+                } else {
+                    "odd"
+                }
+            });
+
+            let effect_runs = ctx.create_ref(Cell::new(0));
+            ctx.create_effect(|| {
+                label.track();
+                effect_runs.set(effect_runs.get() + 1);
+            });
+            assert_eq!((memo_runs.get(), effect_runs.get()), (1, 1));
+
+            // The output of the selector is unchanged so nothing downstream runs.
+            state.set(2);
+            state.set(4);
+            assert_eq!((memo_runs.get(), effect_runs.get()), (1, 1));
+
+            state.set(5);
+            assert_eq!(*label.get(), "odd");
+            assert_eq!((memo_runs.get(), effect_runs.get()), (2, 2));
+        });
+    }
+
+    #[test]
+    fn interned_memo() {
+        create_scope_immediate(|ctx| {
+            let state = ctx.create_signal(0);
+            let status = ctx.create_interned_memo(|| match *state.get() % 3 {
+                0 => "idle".to_string(),
+                1 => "loading".to_string(),
+                _ => "done".to_string(),
+            });
+
+            let mut seen = Vec::new();
+            for i in 0..3 {
+                state.set(i);
+                seen.push(status.get_untracked().as_ref().clone());
+            }
+            assert_eq!(*seen[0], "idle");
+            assert_eq!(*seen[1], "loading");
+            assert_eq!(*seen[2], "done");
+
+            for i in 3..9 {
+                state.set(i);
+                let status = status.get_untracked();
+                assert!(Rc::ptr_eq(&*status, &seen[i as usize % 3]));
+            }
+        });
+    }
+
+    #[test]
+    fn interned_memo_table_is_bounded() {
+        create_scope_immediate(|ctx| {
+            let state = ctx.create_signal(0);
+            let interned = ctx.create_interned_memo(|| *state.get());
+            let first = interned.get_untracked().as_ref().clone();
+
+            state.set(1);
+            state.set(0);
+            assert!(Rc::ptr_eq(&first, &*interned.get_untracked()));
+
+            for i in 1..=MAX_INTERNED_VALUES {
+                state.set(i);
+            }
+            state.set(0);
+            assert!(!Rc::ptr_eq(&first, &*interned.get_untracked()));
+        });
+    }
+
+    #[test]
+    fn memo_opt() {
+        create_scope_immediate(|ctx| {
+            let state = ctx.create_signal(0);
+            let first_even = ctx.create_memo_opt(|| (*state.get() % 2 == 0).then(|| *state.get()));
+
+            let counter = ctx.create_signal(0);
+            ctx.create_effect(|| {
+                counter.set(*counter.get_untracked() + 1);
+                first_even.track();
+            });
+            assert_eq!(*first_even.get(), Some(0));
+            assert_eq!(*counter.get(), 1);
+
+            state.set(1);
+            assert_eq!(*first_even.get(), None);
+            assert_eq!(*counter.get(), 2);
+
+            state.set(3); // A different state that also produces `None`.
+            assert_eq!(*first_even.get(), None);
+            assert_eq!(
+                *counter.get(),
+                2,
+                "None -> None should not trigger the effect"
+            );
+
+            state.set(2);
+            assert_eq!(*first_even.get(), Some(2));
+            assert_eq!(*counter.get(), 3);
+        });
+    }
+
+    #[test]
+    fn memo_opt_with() {
+        create_scope_immediate(|ctx| {
+            let state = ctx.create_signal(Some(1));
+            let doubled = ctx.create_memo_opt_with(|| state.get().map(|x| x * 2), |a, b| a == b);
+
+            let counter = ctx.create_signal(0);
+            ctx.create_effect(|| {
+                counter.set(*counter.get_untracked() + 1);
+                doubled.track();
+            });
+            assert_eq!(*counter.get(), 1);
+
+            state.set(Some(1));
+            assert_eq!(
+                *counter.get(),
+                1,
+                "equal payloads should not trigger the effect"
+            );
+
+            state.set(None);
+            assert_eq!(*counter.get(), 2);
+            state.set(None);
+            assert_eq!(*counter.get(), 2);
+
+            state.set(Some(2));
+            assert_eq!(*doubled.get(), Some(4));
+            assert_eq!(*counter.get(), 3);
+        });
+    }
+
+    #[test]
+    fn writable_derived() {
+        create_scope_immediate(|ctx| {
+            let items = [
+                ctx.create_signal(false),
+                ctx.create_signal(false),
+                ctx.create_signal(false),
+            ];
+            let select_all = ctx.create_writable_derived(
+                move || items.iter().all(|item| *item.get()),
+                move |checked| items.iter().for_each(|item| item.set(checked)),
+            );
+            assert!(!*select_all.get());
+
+            // Writing updates the sources.
+            select_all.set(true);
+            assert!(items.iter().all(|item| *item.get()));
+            assert!(*select_all.get());
+
+            // Updating the sources updates the value.
+            items[1].set(false);
+            assert!(!*select_all.get());
+            items[1].set(true);
+            assert!(*select_all.get());
+
+            select_all.set(false);
+            assert!(items.iter().all(|item| !*item.get()));
+            assert!(!*select_all.get());
+        });
+    }
+
     #[test]
     fn reducer() {
         create_scope_immediate(|ctx| {
@@ -295,4 +1199,32 @@ mod tests {
             assert_eq!(*doubled.get(), 0);
         });
     }
+
+    #[test]
+    fn reducer_with_middleware() {
+        create_scope_immediate(|ctx| {
+            let log = ctx.create_ref(RefCell::new(Vec::new()));
+            let logger: ReducerMiddleware<i32, i32> = Box::new(|state, msg, next| {
+                log.borrow_mut()
+                    .push(format!("before {} {msg}", state.get_untracked()));
+                next(msg);
+                log.borrow_mut()
+                    .push(format!("after {}", state.get_untracked()));
+            });
+            let double: ReducerMiddleware<i32, i32> = Box::new(|_, msg, next| next(msg * 2));
+            let (state, dispatch) = ctx.create_reducer_with_middleware(
+                0,
+                |state, msg| state + msg,
+                vec![logger, double],
+            );
+
+            dispatch(1);
+            dispatch(2);
+            assert_eq!(*state.get(), 6);
+            assert_eq!(
+                *log.borrow(),
+                ["before 0 1", "after 2", "before 2 2", "after 6"]
+            );
+        });
+    }
 }
@@ -0,0 +1,110 @@
+//! Typed messages sent from child components to their parents.
+
+use std::fmt;
+
+use crate::*;
+
+/// The sending half of an outbox created with [`Scope::create_outbox`].
+///
+/// The emitter is cheaply cloneable and `'static`, so it can be passed down as a prop or provided
+/// to all the descendants of a scope with [`Scope::provide_context`].
+pub struct Emitter<M>(RcSignal<Option<M>>);
+
+impl<M> Emitter<M> {
+    /// Send `message` to the outbox. The effects that read the outbox are run with the message
+    /// before this method returns, unless effects are deferred, e.g. with [`batch`].
+    #[track_caller]
+    pub fn emit(&self, message: M) {
+        self.0.set(Some(message));
+    }
+}
+
+impl<M> Clone for Emitter<M> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<M> fmt::Debug for Emitter<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Emitter").finish_non_exhaustive()
+    }
+}
+
+impl<'a> Scope<'a> {
+    /// Create an outbox for messages of type `M`. Returns an [`Emitter`] for sending messages and
+    /// a signal that holds the last message that was sent, or `None` if no message was sent yet.
+    ///
+    /// This lets a child component notify its parent without having to take a callback with a
+    /// specific signature. The parent keeps the signal and subscribes to it with an effect, while
+    /// the emitter is passed down to the children, for instance through a context.
+    ///
+    /// Every message notifies the subscribers of the signal, even if it is equal to the previous
+    /// one. Note however that the signal only holds the last message: if several messages are
+    /// sent while effects are deferred, e.g. inside of [`batch`], the effects only see the last
+    /// one.
+    ///
+    /// # Example
+    /// ```
+    /// # use sycamore_reactive::*;
+    /// enum Msg {
+    ///     Increment,
+    ///     Reset,
+    /// }
+    ///
+    /// # create_scope_immediate(|ctx| {
+    /// let (emitter, messages) = ctx.create_outbox::<Msg>();
+    /// let count = ctx.create_signal(0);
+    /// ctx.create_effect(|| match *messages.get() {
+    ///     Some(Msg::Increment) => count.set(*count.get_untracked() + 1),
+    ///     Some(Msg::Reset) => count.set(0),
+    ///     None => {}
+    /// });
+    /// ctx.provide_context(emitter);
+    ///
+    /// // In a child component:
+    /// let emitter = ctx.use_context::<Emitter<Msg>>();
+    /// emitter.emit(Msg::Increment);
+    /// emitter.emit(Msg::Increment);
+    /// assert_eq!(*count.get(), 2);
+    /// # });
+    /// ```
+    pub fn create_outbox<M>(&'a self) -> (Emitter<M>, &'a ReadSignal<Option<M>>) {
+        let signal = self.create_ref(create_rc_signal(None));
+        (Emitter(signal.clone()), signal)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn outbox_messages_from_child_scope() {
+        create_scope_immediate(|ctx| {
+            let (emitter, messages) = ctx.create_outbox::<&'static str>();
+            let received = ctx.create_ref(RefCell::new(Vec::new()));
+            ctx.create_effect(|| {
+                if let Some(message) = *messages.get() {
+                    received.borrow_mut().push(message);
+                }
+            });
+            ctx.provide_context(emitter);
+
+            let disposer = ctx.create_child_scope(|ctx| {
+                let emitter = ctx.use_context::<Emitter<&'static str>>();
+                emitter.emit("first");
+                emitter.emit("second");
+                emitter.emit("second");
+            });
+            assert_eq!(*received.borrow(), ["first", "second", "second"]);
+            assert_eq!(*messages.get(), Some("second"));
+
+            unsafe {
+                disposer.dispose();
+            }
+            ctx.use_context::<Emitter<&'static str>>().emit("third");
+            assert_eq!(*received.borrow(), ["first", "second", "second", "third"]);
+        });
+    }
+}
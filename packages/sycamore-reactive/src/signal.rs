@@ -1,16 +1,259 @@
 //! Signals - The building blocks of reactivity.
 
+use std::cell::{Cell, Ref, RefMut};
+use std::cmp::Reverse;
+#[cfg(feature = "devtools")]
+use std::collections::VecDeque;
+use std::collections::{BinaryHeap, HashSet};
 use std::fmt::{Debug, Display, Formatter};
 use std::hash::Hash;
 use std::ops::Deref;
+use std::panic::Location;
 
-use crate::effect::EFFECTS;
+use crate::effect::{
+    current_effect_height, current_effect_state, effect_dependencies, EffectSchedule,
+    WeakEffectState, EFFECTS,
+};
 use crate::*;
 
-type WeakEffectCallback = Weak<RefCell<dyn FnMut()>>;
+pub(crate) type WeakEffectCallback = Weak<RefCell<dyn FnMut()>>;
 type EffectCallbackPtr = *const RefCell<dyn FnMut()>;
 
-pub(crate) type SignalEmitterInner = RefCell<IndexMap<EffectCallbackPtr, WeakEffectCallback>>;
+thread_local! {
+    /// Global counter that is incremented every time the subscribers of a signal are triggered.
+    ///
+    /// The counter wraps around on overflow. Ticks are therefore compared with
+    /// [`tick_is_after`] rather than with `>`.
+    static TRIGGER_TICK: Cell<u64> = Default::default();
+}
+
+#[cfg(test)]
+thread_local! {
+    /// The number of calls to [`SignalEmitter::subscribe`] and [`SignalEmitter::unsubscribe`].
+    pub(crate) static SUBSCRIPTION_CHANGES: Cell<usize> = Default::default();
+}
+
+/// Returns the current value of the global trigger counter.
+pub(crate) fn current_trigger_tick() -> u64 {
+    TRIGGER_TICK.with(|tick| tick.get())
+}
+
+/// Returns `true` if `tick` is after `other`, taking into account that [`TRIGGER_TICK`] wraps
+/// around. The ticks are assumed to be less than `u64::MAX / 2` ticks apart.
+fn tick_is_after(tick: u64, other: u64) -> bool {
+    (tick.wrapping_sub(other) as i64) > 0
+}
+
+thread_local! {
+    /// The effects that are waiting to be run by the propagation in progress.
+    static PROPAGATION: RefCell<Propagation> = Default::default();
+    /// See [`set_effect_scheduler`].
+    static EFFECT_SCHEDULER: RefCell<Option<EffectScheduler>> = Default::default();
+}
+
+/// Decides when the effects queued by signal updates are run. See [`set_effect_scheduler`].
+///
+/// This is implemented for all the functions taking the callback, so a closure can be used
+/// directly. Implement it on a type to integrate the reactive runtime with an external loop, such
+/// as the frame loop of a game engine or of a native UI host.
+pub trait Scheduler {
+    /// Called when effects are queued and no run is pending. `run` runs all the queued effects,
+    /// including the ones that are queued after this call. It should be called exactly once,
+    /// whenever the scheduler sees fit. Until then, this method is not called again.
+    fn schedule(&self, run: Box<dyn FnOnce()>);
+}
+
+impl<F: Fn(Box<dyn FnOnce()>)> Scheduler for F {
+    fn schedule(&self, run: Box<dyn FnOnce()>) {
+        self(run)
+    }
+}
+
+/// A [`Scheduler`] that defers calling a callback, for instance to a microtask. See
+/// [`set_effect_scheduler`].
+pub type EffectScheduler = Rc<dyn Scheduler>;
+
+/// Sets the scheduler used to run effects on the current thread, or `None` to run effects
+/// synchronously, which is the default.
+///
+/// By default, the effects that depend on a signal are run before [`Signal::set`] returns. With a
+/// scheduler, the effects are queued instead, and `scheduler` is called with a callback that runs
+/// all the queued effects. Until the callback is called, further updates only add effects to the
+/// queue. This coalesces all the updates made in the meantime, so that every effect runs at most
+/// once.
+///
+/// The scheduler can be a closure, as below, or any type implementing [`Scheduler`], e.g. to run
+/// the effects once per frame of an external loop.
+///
+/// Note that memos are also effects: their values are only updated once the queued effects are
+/// run. Effects are still run synchronously when they are created.
+///
+/// # Example
+/// ```
+/// # use std::cell::RefCell;
+/// # use std::rc::Rc;
+/// # use sycamore_reactive::*;
+/// let pending = Rc::new(RefCell::new(Vec::<Box<dyn FnOnce()>>::new()));
+/// set_effect_scheduler(Some(Rc::new({
+///     let pending = Rc::clone(&pending);
+///     move |f| pending.borrow_mut().push(f)
+/// })));
+///
+/// create_scope_immediate(|ctx| {
+///     let state = ctx.create_signal(0);
+///     let double = ctx.create_memo(|| *state.get() * 2);
+///
+///     state.set(1);
+///     state.set(2);
+///     assert_eq!(*double.get(), 0); // Not updated yet.
+///
+///     for f in pending.take() {
+///         f();
+///     }
+///     assert_eq!(*double.get(), 4);
+/// });
+/// # set_effect_scheduler(None);
+/// ```
+pub fn set_effect_scheduler(scheduler: Option<EffectScheduler>) {
+    EFFECT_SCHEDULER.with(|x| *x.borrow_mut() = scheduler);
+}
+
+/// The state of a synchronous propagation of signal updates to effects.
+///
+/// Instead of running effects as soon as a signal is updated, the effects are queued and run in
+/// order of height by the outermost [`trigger_subscribers`](SignalEmitter::trigger_subscribers)
+/// call. Since an effect always has a greater height than the memos it depends on, this ensures
+/// that an effect only runs once all of its upstream memos have settled, even in diamond-shaped
+/// dependency graphs.
+///
+/// Effects created with [`Scope::create_effect_phased`] are run after all the other effects, with
+/// the [`Read`](EffectPhase::Read) effects first.
+///
+/// If an [`EffectScheduler`] is set, the queue is drained by the scheduled callback instead.
+#[derive(Default)]
+struct Propagation {
+    /// Whether the queue is currently being drained.
+    running: bool,
+    /// Whether running the queue has been scheduled with the [`EffectScheduler`].
+    scheduled: bool,
+    queue: BinaryHeap<Reverse<QueuedSubscriber>>,
+    /// The subscribers in `queue`, used to avoid running an effect twice in the same propagation.
+    queued: HashSet<EffectCallbackPtr>,
+    /// Incremented for every queued subscriber to keep the queue order stable.
+    seq: u64,
+}
+
+/// A [`Subscriber`] in the [`Propagation`] queue.
+struct QueuedSubscriber {
+    subscriber: Subscriber,
+    /// The height of the effect when it was queued. The height in the [`EffectSchedule`] is not
+    /// used directly so that the order does not change while the subscriber is in the queue.
+    height: u32,
+    seq: u64,
+}
+
+impl QueuedSubscriber {
+    fn order(&self) -> (Option<EffectPhase>, u32, i32, u64) {
+        let schedule = &self.subscriber.schedule;
+        (schedule.phase, self.height, schedule.priority, self.seq)
+    }
+}
+
+impl PartialEq for QueuedSubscriber {
+    fn eq(&self, other: &Self) -> bool {
+        self.order() == other.order()
+    }
+}
+impl Eq for QueuedSubscriber {}
+impl PartialOrd for QueuedSubscriber {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for QueuedSubscriber {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.order().cmp(&other.order())
+    }
+}
+
+/// Stops the propagation when dropped, even if an effect panicked.
+struct PropagationGuard;
+
+impl Drop for PropagationGuard {
+    fn drop(&mut self) {
+        PROPAGATION.with(|propagation| {
+            let mut propagation = propagation.borrow_mut();
+            propagation.running = false;
+            propagation.queue.clear();
+            propagation.queued.clear();
+        });
+    }
+}
+
+/// An effect that is subscribed to a [`SignalEmitter`].
+#[derive(Clone)]
+pub(crate) struct Subscriber {
+    callback: WeakEffectCallback,
+    schedule: Rc<EffectSchedule>,
+}
+
+impl Subscriber {
+    /// Returns `true` if the effect has been destroyed or if it is currently running. Such
+    /// subscribers should not be called.
+    fn is_inactive(&self) -> bool {
+        match self.callback.upgrade() {
+            Some(callback) => callback.try_borrow_mut().is_err(),
+            None => true,
+        }
+    }
+}
+
+pub(crate) struct SignalEmitterInner {
+    subscribers: RefCell<IndexMap<EffectCallbackPtr, Subscriber>>,
+    /// The value of the global trigger counter when the subscribers were last triggered, or when
+    /// the emitter was created if they have never been triggered.
+    last_triggered: Cell<u64>,
+    /// See [`SignalEmitter::height`].
+    height: Cell<u32>,
+    /// The effect that computes the value of the signal, for memos. See
+    /// [`SignalEmitter::refresh`].
+    source: RefCell<Option<(WeakEffectCallback, WeakEffectState)>>,
+    /// See [`ReadSignal::set_debug_name`].
+    #[cfg(feature = "devtools")]
+    debug_name: Cell<Option<&'static str>>,
+}
+
+impl Default for SignalEmitterInner {
+    fn default() -> Self {
+        Self {
+            subscribers: Default::default(),
+            // Not `0`, which might be after the current tick if the counter wrapped around.
+            last_triggered: Cell::new(current_trigger_tick()),
+            height: Default::default(),
+            source: Default::default(),
+            #[cfg(feature = "devtools")]
+            debug_name: Default::default(),
+        }
+    }
+}
+
+#[cfg(feature = "devtools")]
+impl Drop for SignalEmitterInner {
+    fn drop(&mut self) {
+        // The thread local is not accessible anymore if the signal is dropped when the thread
+        // exits.
+        let callback = SIGNAL_DROPPED_CALLBACK
+            .try_with(|callback| callback.borrow().clone())
+            .ok()
+            .flatten();
+        if let Some(callback) = callback {
+            callback(
+                SignalId(self as *const Self as usize),
+                self.debug_name.get(),
+            );
+        }
+    }
+}
 
 /// A struct for managing subscriptions to signals.
 #[derive(Default, Clone)]
@@ -32,14 +275,113 @@ impl SignalEmitter {
 
     /// Adds a callback to the subscriber list. If the callback is already a subscriber, does
     /// nothing.
-    pub(crate) fn subscribe(&self, cb: WeakEffectCallback) {
-        self.0.borrow_mut().insert(cb.as_ptr(), cb);
+    pub(crate) fn subscribe(&self, cb: WeakEffectCallback, schedule: Rc<EffectSchedule>) {
+        #[cfg(test)]
+        SUBSCRIPTION_CHANGES.with(|changes| changes.set(changes.get() + 1));
+        self.0.subscribers.borrow_mut().insert(
+            cb.as_ptr(),
+            Subscriber {
+                callback: cb,
+                schedule,
+            },
+        );
+    }
+
+    /// The height of the signal in the dependency graph. Signals that are not derived from other
+    /// signals have a height of `0`. The output of a memo has the same height as the effect
+    /// computing it, which is one more than the greatest height of its dependencies.
+    pub(crate) fn height(&self) -> u32 {
+        self.0.height.get()
+    }
+
+    /// Sets the height of the signal to the height of the effect that is currently running. This
+    /// should be called by memos after computing their value.
+    pub(crate) fn inherit_height(&self) {
+        if let Some(height) = current_effect_height() {
+            self.0.height.set(height);
+        }
+    }
+
+    /// Makes the effect that is currently running the source of the signal. This should be called
+    /// by memos when creating their signal. See [`refresh`](Self::refresh).
+    pub(crate) fn inherit_source(&self) {
+        *self.0.source.borrow_mut() = current_effect_callback().zip(current_effect_state());
+    }
+
+    /// Runs the source of the signal now if it is queued in the current propagation, so that an
+    /// effect never reads the value of a memo while it is stale. The memos that the source depends
+    /// on are refreshed first, since recomputing them can queue the source. Does nothing if no
+    /// effect is running, if the signal has no source or if the source is already running.
+    pub(crate) fn refresh(&self) {
+        if self.0.source.borrow().is_none() {
+            return;
+        }
+        if EFFECTS.with(|effects| effects.borrow().is_empty()) {
+            return;
+        }
+        // Nothing can be stale if no effect is queued.
+        if PROPAGATION.with(|propagation| propagation.borrow().queued.is_empty()) {
+            return;
+        }
+        self.refresh_with(&mut HashSet::new());
+    }
+
+    /// Implementation of [`refresh`](Self::refresh). `visited` contains the signals that were
+    /// already refreshed, so that each memo is only visited once.
+    fn refresh_with(&self, visited: &mut HashSet<*const SignalEmitterInner>) {
+        if !visited.insert(Rc::as_ptr(&self.0)) {
+            return;
+        }
+        let (source, state) = match &*self.0.source.borrow() {
+            Some((source, state)) => (source.upgrade(), state.clone()),
+            None => return,
+        };
+        let source = match source {
+            Some(source) => source,
+            None => return,
+        };
+        for dependency in effect_dependencies(&state) {
+            dependency.refresh_with(visited);
+        }
+        let callback = source.try_borrow_mut();
+        if let Ok(mut callback) = callback {
+            let queued = PROPAGATION.with(|propagation| {
+                let ptr: EffectCallbackPtr = Rc::as_ptr(&source);
+                propagation.borrow_mut().queued.remove(&ptr)
+            });
+            if queued {
+                callback();
+            }
+        }
     }
 
     /// Removes a callback from the subscriber list. If the callback is not a subscriber, does
     /// nothing.
     pub(crate) fn unsubscribe(&self, cb: EffectCallbackPtr) {
-        self.0.borrow_mut().remove(&cb);
+        #[cfg(test)]
+        SUBSCRIPTION_CHANGES.with(|changes| changes.set(changes.get() + 1));
+        self.0.subscribers.borrow_mut().remove(&cb);
+    }
+
+    /// Returns the identifier of the signal that this emitter belongs to.
+    #[cfg(feature = "devtools")]
+    pub(crate) fn id(&self) -> SignalId {
+        SignalId(Rc::as_ptr(&self.0) as usize)
+    }
+
+    /// Removes all the subscribers that have already been destroyed.
+    #[cfg(feature = "devtools")]
+    fn prune_dead_subscribers(&self) {
+        self.0
+            .subscribers
+            .borrow_mut()
+            .retain(|_, subscriber| subscriber.callback.strong_count() > 0);
+    }
+
+    /// Returns `true` if the subscribers have been triggered after the global trigger counter had
+    /// the value `tick`.
+    pub(crate) fn triggered_since(&self, tick: u64) -> bool {
+        tick_is_after(self.0.last_triggered.get(), tick)
     }
 
     /// Track the current signal in the effect scope.
@@ -58,25 +400,279 @@ impl SignalEmitter {
     /// not be automatically triggered. In the general case, however, it is preferable to use
     /// [`Signal::set()`] instead.
     pub fn trigger_subscribers(&self) {
+        let tick = TRIGGER_TICK.with(|tick| {
+            let next = tick.get().wrapping_add(1);
+            tick.set(next);
+            next
+        });
+        self.0.last_triggered.set(tick);
         // Clone subscribers to prevent modifying list when calling callbacks.
-        let subscribers = self.0.borrow().clone();
-        // Subscriber order is reversed because effects attach subscribers at the end of the
-        // effect scope. This will ensure that outer effects re-execute before inner effects,
-        // preventing inner effects from running twice.
-        for subscriber in subscribers.values().rev() {
-            // subscriber might have already been destroyed in the case of nested effects
-            if let Some(callback) = subscriber.upgrade() {
-                // Call the callback.
-                callback.borrow_mut()();
+        // Effects that are running are skipped. They are re-run after they complete if needed.
+        let mut subscribers: Vec<_> = self
+            .0
+            .subscribers
+            .borrow()
+            .values()
+            .filter(|subscriber| !subscriber.is_inactive())
+            .cloned()
+            .collect();
+        // Run subscribers with a lower priority first. Among subscribers with the same priority,
+        // the effects that finished running last are called first. Since an outer effect finishes
+        // running after the inner effects it creates, this ensures that outer effects re-execute
+        // before inner effects, preventing inner effects from running twice.
+        subscribers.sort_by_key(|subscriber| {
+            (
+                subscriber.schedule.priority,
+                Reverse(subscriber.schedule.last_run.get()),
+            )
+        });
+
+        PROPAGATION.with(|propagation| {
+            let mut propagation = propagation.borrow_mut();
+            for subscriber in subscribers {
+                if propagation.queued.insert(subscriber.callback.as_ptr()) {
+                    #[cfg(feature = "devtools")]
+                    subscriber.schedule.trigger.set(Some(self.id()));
+                    let height = subscriber.schedule.height.get();
+                    let seq = propagation.seq;
+                    propagation.seq += 1;
+                    propagation.queue.push(Reverse(QueuedSubscriber {
+                        subscriber,
+                        height,
+                        seq,
+                    }));
+                }
             }
+        });
+        flush_queued_effects();
+    }
+}
+
+/// Runs the effects in the [`Propagation`] queue, or schedules running them if an
+/// [`EffectScheduler`] is set.
+fn flush_queued_effects() {
+    // Otherwise, the outermost call or the scheduled callback runs the queued effects.
+    let idle = PROPAGATION.with(|propagation| {
+        let propagation = propagation.borrow();
+        !propagation.running && !propagation.scheduled && !propagation.queue.is_empty()
+    });
+    if !idle {
+        return;
+    }
+
+    match EFFECT_SCHEDULER.with(|scheduler| scheduler.borrow().clone()) {
+        Some(scheduler) => {
+            PROPAGATION.with(|propagation| propagation.borrow_mut().scheduled = true);
+            scheduler.schedule(Box::new(|| {
+                PROPAGATION.with(|propagation| propagation.borrow_mut().scheduled = false);
+                run_queued_effects();
+            }));
         }
+        None => run_queued_effects(),
+    }
+}
+
+/// Runs `f` and defers the effects that are triggered by the signal updates inside of it until
+/// `f` returns. Every effect then runs at most once, no matter how many of its dependencies were
+/// updated.
+///
+/// Writes are coalesced: if the same signal is set several times inside of `f`, its dependents
+/// only observe the last value. The same applies to the signal updates made by an effect that is
+/// re-run because of a signal update, since the queued effects are only run once it completes.
+///
+/// Note that memos are also effects: their values are only updated once `f` returns. Effects are
+/// still run when they are created.
+///
+/// If `f` panics, the deferred effects are discarded.
+///
+/// # Example
+/// ```
+/// # use sycamore_reactive::*;
+/// # create_scope_immediate(|ctx| {
+/// let a = ctx.create_signal(0);
+/// let b = ctx.create_signal(0);
+/// ctx.create_effect(|| println!("a + b = {}", *a.get() + *b.get()));
+///
+/// // Only prints "a + b = 3" once.
+/// batch(|| {
+///     a.set(1);
+///     b.set(2);
+/// });
+/// # });
+/// ```
+pub fn batch<T>(f: impl FnOnce() -> T) -> T {
+    let running =
+        PROPAGATION.with(|propagation| mem::replace(&mut propagation.borrow_mut().running, true));
+    if running {
+        // The outer batch or propagation runs the effects.
+        return f();
+    }
+
+    let guard = PropagationGuard;
+    let ret = f();
+    // Keep the queue.
+    mem::forget(guard);
+    PROPAGATION.with(|propagation| propagation.borrow_mut().running = false);
+    flush_queued_effects();
+    ret
+}
+
+/// Runs the effects in the [`Propagation`] queue, unless they are already being run.
+fn run_queued_effects() {
+    let running =
+        PROPAGATION.with(|propagation| mem::replace(&mut propagation.borrow_mut().running, true));
+    if running {
+        return;
     }
+
+    let _guard = PropagationGuard;
+    while let Some((Reverse(QueuedSubscriber { subscriber, .. }), queued)) =
+        PROPAGATION.with(|propagation| {
+            let mut propagation = propagation.borrow_mut();
+            let Reverse(next) = propagation.queue.pop()?;
+            // The subscriber is no longer queued if it was already run by `SignalEmitter::refresh`.
+            let queued = propagation
+                .queued
+                .remove(&next.subscriber.callback.as_ptr());
+            Some((Reverse(next), queued))
+        })
+    {
+        if !queued {
+            continue;
+        }
+        // subscriber might have already been destroyed in the case of nested effects
+        if let Some(callback) = subscriber.callback.upgrade() {
+            // Call the callback, unless the effect is running.
+            if let Ok(mut callback) = callback.try_borrow_mut() {
+                callback();
+            }
+        }
+    }
+}
+
+/// Creates a [`ReadSignal`] that contains the values of all the signals as a tuple. The returned
+/// signal is updated whenever any of the signals is updated.
+///
+/// The first argument is the [`Scope`] on which the derived signal is created. The remaining
+/// arguments are references to the signals to combine. The values of the signals must implement
+/// [`Clone`]. For only two signals, [`ReadSignal::zip`] can be used instead.
+///
+/// # Example
+/// ```rust
+/// # use sycamore_reactive::*;
+/// # create_scope_immediate(|ctx| {
+/// let a = ctx.create_signal(1);
+/// let b = ctx.create_signal("b");
+/// let c = ctx.create_signal(3.0);
+/// let combined = combine!(ctx, a, b, c);
+/// assert_eq!(*combined.get(), (1, "b", 3.0));
+///
+/// b.set("B");
+/// assert_eq!(*combined.get(), (1, "B", 3.0));
+/// # });
+/// ```
+#[macro_export]
+macro_rules! combine {
+    ($ctx:expr, $($signal:expr),+ $(,)?) => {
+        $ctx.create_memo(move || ($((*$signal.get()).clone(),)+))
+    };
+}
+
+/// The maximum number of [`ChangeRecord`]s that are kept for each signal.
+#[cfg(feature = "devtools")]
+const CHANGE_LOG_CAPACITY: usize = 16;
+
+#[cfg(feature = "devtools")]
+thread_local! {
+    /// Global counter used for ordering [`ChangeRecord`]s.
+    static CHANGE_TICK: Cell<u64> = Default::default();
+    /// See [`on_signal_dropped`].
+    static SIGNAL_DROPPED_CALLBACK: RefCell<Option<SignalDroppedCallback>> = Default::default();
+}
+
+/// A function that is called with the identifier and the debug name of every signal that is
+/// dropped. See [`on_signal_dropped`].
+///
+/// _This API requires the following crate features to be activated: `devtools`_
+#[cfg(feature = "devtools")]
+pub type SignalDroppedCallback = Rc<dyn Fn(SignalId, Option<&'static str>)>;
+
+/// Sets the function that is called when a signal on the current thread is dropped, or `None` to
+/// remove it.
+///
+/// The callback receives the [`SignalId`] of the signal, which is the same as the one returned by
+/// [`ReadSignal::id`], and its name if one was set with [`ReadSignal::set_debug_name`]. Comparing
+/// the ids of the signals that are created with the ids that are reported as dropped makes it
+/// possible to find signals that are leaked.
+///
+/// The callback is called while the signal is being dropped, usually while a scope is being
+/// disposed. It must therefore not access signals, effects or scopes.
+///
+/// # Example
+/// ```
+/// # use std::cell::RefCell;
+/// # use std::rc::Rc;
+/// # use sycamore_reactive::*;
+/// let dropped = Rc::new(RefCell::new(Vec::new()));
+/// on_signal_dropped(Some(Rc::new({
+///     let dropped = Rc::clone(&dropped);
+///     move |_id, name| dropped.borrow_mut().push(name)
+/// })));
+///
+/// create_scope_immediate(|ctx| {
+///     ctx.create_signal(0).set_debug_name("count");
+/// });
+/// assert_eq!(*dropped.borrow(), [Some("count")]);
+/// # on_signal_dropped(None);
+/// ```
+///
+/// _This API requires the following crate features to be activated: `devtools`_
+#[cfg(feature = "devtools")]
+pub fn on_signal_dropped(callback: Option<SignalDroppedCallback>) {
+    SIGNAL_DROPPED_CALLBACK.with(|x| *x.borrow_mut() = callback);
 }
 
+/// A record of a single update to a signal. Obtained from [`ReadSignal::recent_changes`].
+///
+/// _This API requires the following crate features to be activated: `devtools`_
+#[cfg(feature = "devtools")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChangeRecord {
+    /// The source location of the call that updated the signal.
+    pub location: &'static Location<'static>,
+    /// A logical timestamp. This is a global counter that is incremented on every signal update,
+    /// making it possible to order changes across different signals. A logical clock is used
+    /// instead of a wall clock because [`std::time::Instant`] is not available on
+    /// `wasm32-unknown-unknown`.
+    pub tick: u64,
+}
+
+/// An opaque identifier for an effect that is subscribed to a signal. Obtained from
+/// [`ReadSignal::dependents`].
+///
+/// Two [`DependentId`]s are equal if and only if they identify the same effect.
+///
+/// _This API requires the following crate features to be activated: `devtools`_
+#[cfg(feature = "devtools")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DependentId(usize);
+
+/// An opaque identifier for a signal. Obtained from [`ReadSignal::id`] and [`current_trigger`].
+///
+/// Two [`SignalId`]s are equal if and only if they identify the same signal.
+///
+/// _This API requires the following crate features to be activated: `devtools`_
+#[cfg(feature = "devtools")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SignalId(usize);
+
 /// A read-only [`Signal`].
 pub struct ReadSignal<T> {
     value: RefCell<Rc<T>>,
     emitter: SignalEmitter,
+    /// The last [`CHANGE_LOG_CAPACITY`] updates to this signal.
+    #[cfg(feature = "devtools")]
+    changes: RefCell<VecDeque<ChangeRecord>>,
 }
 
 impl<T> ReadSignal<T> {
@@ -95,9 +691,11 @@ impl<T> ReadSignal<T> {
     /// # });
     /// ```
     #[must_use = "to only subscribe the signal without using the value, use .track() instead"]
+    #[track_caller]
     pub fn get(&self) -> Rc<T> {
+        self.emitter.refresh();
         self.emitter.track();
-        self.value.borrow().clone()
+        self.borrow_value().clone()
     }
 
     /// Get the current value of the state, without tracking this as a dependency if inside a
@@ -118,8 +716,113 @@ impl<T> ReadSignal<T> {
     /// # });
     /// ```
     #[must_use = "discarding the returned value does nothing"]
+    #[track_caller]
     pub fn get_untracked(&self) -> Rc<T> {
-        self.value.borrow().clone()
+        self.emitter.refresh();
+        self.borrow_value().clone()
+    }
+
+    /// Calls `f` with a reference to the current value of the state, without tracking this as a
+    /// dependency if inside a reactive context.
+    ///
+    /// This is useful for computing something from the value without cloning it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use sycamore_reactive::*;
+    /// # create_scope_immediate(|ctx| {
+    /// let state = ctx.create_signal(vec![1, 2, 3]);
+    /// let len = ctx.create_memo(|| state.with_untracked(|v| v.len()));
+    /// assert_eq!(*len.get(), 3);
+    ///
+    /// state.set(vec![1]);
+    /// // len should still be old value because state was untracked
+    /// assert_eq!(*len.get(), 3);
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn with_untracked<U>(&self, f: impl FnOnce(&T) -> U) -> U {
+        // Clone the `Rc` so that `f` is allowed to update the signal.
+        let value = self.get_untracked();
+        f(&value)
+    }
+
+    /// Read the current value of the state without tracking it as a dependency. This is the same
+    /// as [`get_untracked`](Self::get_untracked).
+    ///
+    /// Use this to read signals outside of reactive contexts, for instance in event handlers, to
+    /// make it clear that the read is not meant to subscribe to the signal. Inside an effect or a
+    /// memo, use [`get`](Self::get) to re-run it when the signal changes.
+    ///
+    /// # Example
+    /// ```
+    /// # use sycamore_reactive::*;
+    /// # create_scope_immediate(|ctx| {
+    /// let count = ctx.create_signal(0);
+    /// let on_click = || count.set(*count.peek() + 1);
+    ///
+    /// on_click();
+    /// assert_eq!(*count.peek(), 1);
+    /// # });
+    /// ```
+    #[must_use = "discarding the returned value does nothing"]
+    #[track_caller]
+    pub fn peek(&self) -> Rc<T> {
+        self.get_untracked()
+    }
+
+    /// Read the current value of the state without tracking it as a dependency. This is the same
+    /// as [`get_untracked`](Self::get_untracked).
+    ///
+    /// Use this inside an effect or a memo to read the latest value of a signal whenever _other_
+    /// signals change, without re-running when this one changes. See also
+    /// [`sample_on`](Self::sample_on).
+    ///
+    /// # Example
+    /// ```
+    /// # use sycamore_reactive::*;
+    /// # create_scope_immediate(|ctx| {
+    /// let clicks = ctx.create_signal(0);
+    /// let text = ctx.create_signal("a");
+    /// let last = ctx.create_memo(|| (*clicks.get(), *text.sample()));
+    /// assert_eq!(*last.get(), (0, "a"));
+    ///
+    /// text.set("b");
+    /// assert_eq!(*last.get(), (0, "a"));
+    /// clicks.set(1);
+    /// assert_eq!(*last.get(), (1, "b"));
+    /// # });
+    /// ```
+    #[must_use = "discarding the returned value does nothing"]
+    #[track_caller]
+    pub fn sample(&self) -> Rc<T> {
+        self.get_untracked()
+    }
+
+    /// Creates an effect that calls `f` with the values of `self` and `other` every time `self`
+    /// changes. Only `self` is tracked: `other` is read with [`sample`](Self::sample), so updating
+    /// it does not call `f` but the next call sees its latest value.
+    ///
+    /// # Example
+    /// ```
+    /// # use sycamore_reactive::*;
+    /// # create_scope_immediate(|ctx| {
+    /// let submit = ctx.create_signal(());
+    /// let input = ctx.create_signal(String::new());
+    /// submit.sample_on(ctx, input, |_, input| println!("submitted {}", input));
+    ///
+    /// input.set("Hello".to_string()); // Does not print anything.
+    /// submit.set(()); // Prints "submitted Hello".
+    /// # });
+    /// ```
+    pub fn sample_on<'a, U>(
+        &'a self,
+        ctx: ScopeRef<'a>,
+        other: &'a ReadSignal<U>,
+        mut f: impl FnMut(&T, &U) + 'a,
+    ) {
+        ctx.create_effect(move || f(&self.get(), &other.sample()));
     }
 
     /// Creates a mapped [`ReadSignal`]. This is equivalent to using
@@ -146,6 +849,201 @@ impl<T> ReadSignal<T> {
         ctx.create_memo(move || f(&self.get()))
     }
 
+    /// Creates a [`ReadSignal`] that only reflects the values of `self` that satisfy `pred`. When
+    /// `self` is updated to a value that does not satisfy `pred`, the returned signal keeps its
+    /// last value and dependents are not notified.
+    ///
+    /// If the current value of `self` does not satisfy `pred`, the returned signal starts out
+    /// with `T::default()`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use sycamore_reactive::*;
+    /// # create_scope_immediate(|ctx| {
+    /// let state = ctx.create_signal(1);
+    /// let positive = state.filter(ctx, |&x| x > 0);
+    /// assert_eq!(*positive.get(), 1);
+    ///
+    /// state.set(-1);
+    /// assert_eq!(*positive.get(), 1);
+    /// state.set(2);
+    /// assert_eq!(*positive.get(), 2);
+    /// # });
+    /// ```
+    #[must_use]
+    pub fn filter<'a>(
+        &'a self,
+        ctx: ScopeRef<'a>,
+        pred: impl Fn(&T) -> bool + 'a,
+    ) -> &'a ReadSignal<T>
+    where
+        T: Clone + Default,
+    {
+        let filtered = ctx.create_signal(T::default());
+        ctx.create_effect(move || {
+            let value = self.get();
+            if pred(&value) {
+                filtered.set((*value).clone());
+            }
+        });
+        filtered
+    }
+
+    /// Creates a [`ReadSignal`] that forwards the values of `self` unchanged and calls `f` with
+    /// every value, like [`Iterator::inspect`]. This is useful for logging the values flowing
+    /// through a chain of derived signals.
+    ///
+    /// `f` is untracked, so reading other signals inside of it does not make the returned signal
+    /// depend on them.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use sycamore_reactive::*;
+    /// # create_scope_immediate(|ctx| {
+    /// let state = ctx.create_signal(1);
+    /// let double = state
+    ///     .map(ctx, |&x| x * 2)
+    ///     .inspect(ctx, |x| println!("double = {x}"));
+    /// assert_eq!(*double.get(), 2);
+    /// # });
+    /// ```
+    #[must_use]
+    pub fn inspect<'a>(&'a self, ctx: ScopeRef<'a>, f: impl Fn(&T) + 'a) -> &'a ReadSignal<T>
+    where
+        T: Clone,
+    {
+        ctx.create_memo(move || {
+            let value = self.get();
+            untrack(|| f(&value));
+            (*value).clone()
+        })
+    }
+
+    /// Creates a [`ReadSignal`] that only notifies its dependents when the value of `self` changes
+    /// to a different value. Updates of `self` to a value that is equal to the previous one are
+    /// not forwarded. This is equivalent to using [`create_selector`](Scope::create_selector).
+    ///
+    /// # Example
+    /// ```rust
+    /// # use sycamore_reactive::*;
+    /// # create_scope_immediate(|ctx| {
+    /// let state = ctx.create_signal(1);
+    /// let distinct = state.distinct_until_changed(ctx);
+    /// assert_eq!(*distinct.get(), 1);
+    ///
+    /// state.set(1); // Dependents of `distinct` are not notified.
+    /// state.set(2);
+    /// assert_eq!(*distinct.get(), 2);
+    /// # });
+    /// ```
+    #[must_use]
+    pub fn distinct_until_changed<'a>(&'a self, ctx: ScopeRef<'a>) -> &'a ReadSignal<T>
+    where
+        T: Clone + PartialEq,
+    {
+        ctx.create_selector(move || (*self.get()).clone())
+    }
+
+    /// Creates a [`ReadSignal`] that is `true` when the value of `self` is equal to `value`.
+    ///
+    /// Dependents are notified every time `self` is updated, even if the result is the same. To
+    /// only notify dependents when the result changes, use [`when_eq`](Self::when_eq) instead.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use sycamore_reactive::*;
+    /// # create_scope_immediate(|ctx| {
+    /// let state = ctx.create_signal(1);
+    /// let is_two = state.eq_value(ctx, 2);
+    /// assert!(!*is_two.get());
+    ///
+    /// state.set(2);
+    /// assert!(*is_two.get());
+    /// # });
+    /// ```
+    #[must_use]
+    pub fn eq_value<'a>(&'a self, ctx: ScopeRef<'a>, value: T) -> &'a ReadSignal<bool>
+    where
+        T: PartialEq + 'a,
+    {
+        ctx.create_memo(move || *self.get() == value)
+    }
+
+    /// Same as [`eq_value`](Self::eq_value) but only notifies dependents when the result changes,
+    /// i.e. when `self` changes from or to `value`. This is equivalent to using
+    /// [`create_selector`](Scope::create_selector).
+    #[must_use]
+    pub fn when_eq<'a>(&'a self, ctx: ScopeRef<'a>, value: T) -> &'a ReadSignal<bool>
+    where
+        T: PartialEq + 'a,
+    {
+        ctx.create_selector(move || *self.get() == value)
+    }
+
+    /// Creates a [`ReadSignal`] that contains the values of both `self` and `other` as a tuple.
+    /// The returned signal is updated whenever either of the signals is updated.
+    ///
+    /// To combine more than two signals, use the [`combine!`](crate::combine) macro.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use sycamore_reactive::*;
+    /// # create_scope_immediate(|ctx| {
+    /// let name = ctx.create_signal("Alice");
+    /// let age = ctx.create_signal(30);
+    /// let person = name.zip(ctx, age);
+    /// assert_eq!(*person.get(), ("Alice", 30));
+    ///
+    /// age.set(31);
+    /// assert_eq!(*person.get(), ("Alice", 31));
+    /// # });
+    /// ```
+    #[must_use]
+    pub fn zip<'a, U>(
+        &'a self,
+        ctx: ScopeRef<'a>,
+        other: &'a ReadSignal<U>,
+    ) -> &'a ReadSignal<(T, U)>
+    where
+        T: Clone,
+        U: Clone,
+    {
+        ctx.create_memo(move || ((*self.get()).clone(), (*other.get()).clone()))
+    }
+
+    /// Creates a [`ReadSignal`] that contains the value of the signal returned by `f` for the
+    /// value of `self`.
+    ///
+    /// The returned signal is updated whenever the inner signal is updated. When `self` is
+    /// updated, `f` is called again and the returned signal follows the new inner signal instead.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use sycamore_reactive::*;
+    /// # create_scope_immediate(|ctx| {
+    /// let names = [ctx.create_signal("Alice"), ctx.create_signal("Bob")];
+    /// let selected = ctx.create_signal(0);
+    /// let name = selected.flat_map(ctx, move |&i| &*names[i]);
+    /// assert_eq!(*name.get(), "Alice");
+    ///
+    /// selected.set(1);
+    /// assert_eq!(*name.get(), "Bob");
+    /// names[1].set("Carol");
+    /// assert_eq!(*name.get(), "Carol");
+    /// # });
+    /// ```
+    #[must_use]
+    pub fn flat_map<'a, U>(
+        &'a self,
+        ctx: ScopeRef<'a>,
+        f: impl Fn(&T) -> &'a ReadSignal<U> + 'a,
+    ) -> &'a ReadSignal<U>
+    where
+        U: Clone,
+    {
+        ctx.create_memo(move || (*f(&self.get()).get()).clone())
+    }
+
     /// When called inside a reactive scope, calling this will add itself to the scope's
     /// dependencies.
     ///
@@ -153,6 +1051,172 @@ impl<T> ReadSignal<T> {
     pub fn track(&self) {
         self.emitter.track();
     }
+
+    /// Sets the height of the signal to the height of the running effect. See
+    /// [`SignalEmitter::inherit_height`].
+    pub(crate) fn inherit_height(&self) {
+        self.emitter.inherit_height();
+    }
+
+    /// Makes the running effect the source of the signal. See [`SignalEmitter::inherit_source`].
+    pub(crate) fn inherit_source(&self) {
+        self.emitter.inherit_source();
+    }
+
+    /// Returns the most recent updates to this signal, oldest first. Only the last few updates are
+    /// kept.
+    ///
+    /// This is useful for finding out which code path is updating a signal more often than
+    /// expected.
+    ///
+    /// _This API requires the following crate features to be activated: `devtools`_
+    #[cfg(feature = "devtools")]
+    pub fn recent_changes(&self) -> Vec<ChangeRecord> {
+        self.changes.borrow().iter().copied().collect()
+    }
+
+    /// Returns the identifier of this signal. This can be compared with the result of
+    /// [`current_trigger`] to find out which signal caused an effect to re-run.
+    ///
+    /// _This API requires the following crate features to be activated: `devtools`_
+    #[cfg(feature = "devtools")]
+    pub fn id(&self) -> SignalId {
+        self.emitter.id()
+    }
+
+    /// Sets the name that identifies this signal in debugging tools, such as the callback set with
+    /// [`on_signal_dropped`].
+    ///
+    /// _This API requires the following crate features to be activated: `devtools`_
+    #[cfg(feature = "devtools")]
+    pub fn set_debug_name(&self, name: &'static str) {
+        self.emitter.0.debug_name.set(Some(name));
+    }
+
+    /// Returns the name set with [`set_debug_name`](Self::set_debug_name), if any.
+    ///
+    /// _This API requires the following crate features to be activated: `devtools`_
+    #[cfg(feature = "devtools")]
+    pub fn debug_name(&self) -> Option<&'static str> {
+        self.emitter.0.debug_name.get()
+    }
+
+    /// Returns the number of effects (including memos) that are currently subscribed to this
+    /// signal. These are the effects that will re-run when the signal is updated.
+    ///
+    /// _This API requires the following crate features to be activated: `devtools`_
+    #[cfg(feature = "devtools")]
+    pub fn dependent_count(&self) -> usize {
+        self.emitter.prune_dead_subscribers();
+        self.emitter.0.subscribers.borrow().len()
+    }
+
+    /// Returns identifiers for all the effects (including memos) that are currently subscribed to
+    /// this signal. The identifiers do not keep the effects alive.
+    ///
+    /// _This API requires the following crate features to be activated: `devtools`_
+    #[cfg(feature = "devtools")]
+    pub fn dependents(&self) -> Vec<DependentId> {
+        self.emitter.prune_dead_subscribers();
+        self.emitter
+            .0
+            .subscribers
+            .borrow()
+            .keys()
+            .map(|&ptr| DependentId(ptr as *const () as usize))
+            .collect()
+    }
+
+    /// Borrow the value of the signal. Panics with the location of the caller if the value is
+    /// currently being updated.
+    #[track_caller]
+    fn borrow_value(&self) -> Ref<'_, Rc<T>> {
+        match self.value.try_borrow() {
+            Ok(value) => value,
+            Err(_) => panic!(
+                "signal read at {} while it is being updated. This can happen if the old value of \
+                 the signal accesses the signal when it is dropped.",
+                Location::caller()
+            ),
+        }
+    }
+
+    /// Mutably borrow the value of the signal. Panics with the location of the caller if the value
+    /// is currently being read or updated.
+    #[track_caller]
+    fn borrow_value_mut(&self) -> RefMut<'_, Rc<T>> {
+        match self.value.try_borrow_mut() {
+            Ok(value) => value,
+            Err(_) => panic!(
+                "signal updated at {} while it is being read or updated. This can happen if the \
+                 old value of the signal accesses the signal when it is dropped.",
+                Location::caller()
+            ),
+        }
+    }
+
+    /// Add a [`ChangeRecord`] to the change log, evicting the oldest record if full.
+    #[cfg(feature = "devtools")]
+    fn record_change(&self, location: &'static Location<'static>) {
+        let tick = CHANGE_TICK.with(|tick| {
+            let next = tick.get() + 1;
+            tick.set(next);
+            next
+        });
+        let mut changes = self.changes.borrow_mut();
+        if changes.len() == CHANGE_LOG_CAPACITY {
+            changes.pop_front();
+        }
+        changes.push_back(ChangeRecord { location, tick });
+    }
+}
+
+impl<T: Copy> ReadSignal<T> {
+    /// Get a copy of the current value of the state. When called inside a reactive scope, calling
+    /// this will add itself to the scope's dependencies, just like [`get`](Self::get).
+    ///
+    /// Unlike [`get`](Self::get), the value is read without cloning the [`Rc`] that holds it, which
+    /// makes this slightly faster for small [`Copy`] values such as numbers and booleans.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use sycamore_reactive::*;
+    /// # create_scope_immediate(|ctx| {
+    /// let state = ctx.create_signal(0);
+    /// let double = ctx.create_memo(|| state.get_copy() * 2);
+    ///
+    /// state.set(1);
+    /// assert_eq!(double.get_copy(), 2);
+    /// # });
+    /// ```
+    #[must_use = "to only subscribe the signal without using the value, use .track() instead"]
+    #[track_caller]
+    pub fn get_copy(&self) -> T {
+        self.emitter.refresh();
+        self.emitter.track();
+        **self.borrow_value()
+    }
+}
+
+impl<T: Clone> ReadSignal<T> {
+    /// Read a clone of the current value of the state without tracking it as a dependency. See
+    /// [`peek`](Self::peek).
+    #[must_use = "discarding the returned value does nothing"]
+    #[track_caller]
+    pub fn peek_clone(&self) -> T {
+        self.peek().as_ref().clone()
+    }
+}
+
+impl<'a, T: Clone> ReadSignal<&'a ReadSignal<T>> {
+    /// Creates a [`ReadSignal`] that contains the value of the inner signal. This is equivalent
+    /// to using [`flat_map`](Self::flat_map) with the identity function.
+    ///
+    /// The returned signal stops tracking the previous inner signal when `self` is updated.
+    #[must_use]
+    pub fn flatten(&'a self, ctx: ScopeRef<'a>) -> &'a ReadSignal<T> {
+        self.flat_map(ctx, |inner| *inner)
+    }
 }
 
 /// Reactive state that can be updated and subscribed to.
@@ -164,34 +1228,105 @@ impl<T> Signal<T> {
         Self(ReadSignal {
             value: RefCell::new(Rc::new(value)),
             emitter: Default::default(),
+            #[cfg(feature = "devtools")]
+            changes: Default::default(),
         })
     }
 
-    /// Set the current value of the state.
+    /// Set the current value of the state.
+    ///
+    /// This will notify and update any effects and memos that depend on this value.
+    ///
+    /// # Example
+    /// ```
+    /// # use sycamore_reactive::*;
+    /// # create_scope_immediate(|ctx| {
+    /// let state = ctx.create_signal(0);
+    /// assert_eq!(*state.get(), 0);
+    ///
+    /// state.set(1);
+    /// assert_eq!(*state.get(), 1);
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn set(&self, value: T) {
+        self.set_at(value, Location::caller());
+    }
+
+    /// Like [`set`](Self::set), but records `location` as the source of the change instead of the
+    /// caller. This is used by memos to report where they were created.
+    #[track_caller]
+    #[cfg_attr(not(feature = "devtools"), allow(unused_variables))]
+    pub(crate) fn set_at(&self, value: T, location: &'static Location<'static>) {
+        #[cfg(feature = "devtools")]
+        self.0.record_change(location);
+        *self.0.borrow_value_mut() = Rc::new(value);
+        self.0.emitter.trigger_subscribers();
+    }
+
+    /// Set the current value of the state if it is different from the new value. Returns `true`
+    /// if the value was updated, in which case subscribers are triggered like with
+    /// [`set`](Self::set).
+    ///
+    /// # Example
+    /// ```
+    /// # use sycamore_reactive::*;
+    /// # create_scope_immediate(|ctx| {
+    /// let state = ctx.create_signal(0);
+    /// assert!(!state.set_if_changed(0));
+    /// assert!(state.set_if_changed(1));
+    /// assert_eq!(*state.get(), 1);
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn set_if_changed(&self, value: T) -> bool
+    where
+        T: PartialEq,
+    {
+        if *self.get_untracked() == value {
+            return false;
+        }
+        self.set(value);
+        true
+    }
+
+    /// Set the current value of the state to `new` only if it is equal to `expected`. Returns
+    /// `true` if the value was updated, in which case subscribers are triggered like with
+    /// [`set`](Self::set).
     ///
-    /// This will notify and update any effects and memos that depend on this value.
+    /// This is useful for optimistic updates: compute a new value from a value read earlier and
+    /// only apply it if the state was not changed in the meantime, e.g. by an effect.
     ///
     /// # Example
     /// ```
     /// # use sycamore_reactive::*;
     /// # create_scope_immediate(|ctx| {
     /// let state = ctx.create_signal(0);
-    /// assert_eq!(*state.get(), 0);
-    ///
-    /// state.set(1);
+    /// let read = *state.get();
+    /// assert!(state.compare_and_set(&read, read + 1));
+    /// // The state is no longer equal to `read`.
+    /// assert!(!state.compare_and_set(&read, read + 10));
     /// assert_eq!(*state.get(), 1);
     /// # });
     /// ```
-    pub fn set(&self, value: T) {
-        *self.0.value.borrow_mut() = Rc::new(value);
-        self.0.emitter.trigger_subscribers();
+    #[track_caller]
+    pub fn compare_and_set(&self, expected: &T, new: T) -> bool
+    where
+        T: PartialEq,
+    {
+        if *self.get_untracked() != *expected {
+            return false;
+        }
+        self.set(new);
+        true
     }
 
     /// Set the current value of the state _without_ triggering subscribers.
     ///
     /// Make sure you know what you are doing because this can make state inconsistent.
+    #[track_caller]
     pub fn set_silent(&self, value: T) {
-        *self.0.value.borrow_mut() = Rc::new(value);
+        *self.0.borrow_value_mut() = Rc::new(value);
     }
 
     /// Split a signal into getter and setter handles.
@@ -214,12 +1349,48 @@ impl<T> Signal<T> {
     }
 }
 
+impl<T: Clone> Signal<T> {
+    /// Mutate the current value of the state in place with `f` and return the result of `f`.
+    /// Subscribers are triggered once, after `f` returns.
+    ///
+    /// `f` mutates a clone of the current value, which is stored in the signal after `f` returns.
+    /// The signal is not borrowed while `f` runs, so `f` can read the signal, which still returns
+    /// the value from before the mutation.
+    ///
+    /// # Example
+    /// ```
+    /// # use sycamore_reactive::*;
+    /// # create_scope_immediate(|ctx| {
+    /// let items = ctx.create_signal(vec![1, 2]);
+    /// let len = items.with_mut(|items| {
+    ///     items.push(3);
+    ///     items.len()
+    /// });
+    /// assert_eq!(len, 3);
+    /// assert_eq!(*items.get(), [1, 2, 3]);
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn with_mut<U>(&self, f: impl FnOnce(&mut T) -> U) -> U {
+        #[cfg(feature = "devtools")]
+        self.0.record_change(Location::caller());
+        let mut value = Rc::clone(&self.0.borrow_value());
+        let ret = f(Rc::make_mut(&mut value));
+        *self.0.borrow_value_mut() = value;
+        self.0.emitter.trigger_subscribers();
+        ret
+    }
+}
+
 impl<T: Default> Signal<T> {
     /// Take the current value out and replace it with the default value.
     ///
     /// This will notify and update any effects and memos that depend on this value.
+    #[track_caller]
     pub fn take(&self) -> Rc<T> {
-        let ret = self.0.value.take();
+        #[cfg(feature = "devtools")]
+        self.0.record_change(Location::caller());
+        let ret = mem::take(&mut *self.0.borrow_value_mut());
         self.0.emitter.trigger_subscribers();
         ret
     }
@@ -228,8 +1399,43 @@ impl<T: Default> Signal<T> {
     /// subscribers.
     ///
     /// Make sure you know what you are doing because this can make state inconsistent.
+    #[track_caller]
     pub fn take_silent(&self) -> Rc<T> {
-        self.0.value.take()
+        mem::take(&mut *self.0.borrow_value_mut())
+    }
+}
+
+impl Signal<bool> {
+    /// Flip the current value of the state.
+    ///
+    /// The current value is read _without_ tracking it as a dependency. This makes it safe to
+    /// toggle a signal inside an effect without making the effect depend on the signal.
+    ///
+    /// # Example
+    /// ```
+    /// # use sycamore_reactive::*;
+    /// # create_scope_immediate(|ctx| {
+    /// let open = ctx.create_signal(false);
+    /// open.toggle();
+    /// assert!(*open.get());
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn toggle(&self) {
+        let value = !*self.get_untracked();
+        self.set(value);
+    }
+
+    /// Set the current value of the state to `true`. Equivalent to `self.set(true)`.
+    #[track_caller]
+    pub fn set_true(&self) {
+        self.set(true);
+    }
+
+    /// Set the current value of the state to `false`. Equivalent to `self.set(false)`.
+    #[track_caller]
+    pub fn set_false(&self) {
+        self.set(false);
     }
 }
 
@@ -241,6 +1447,35 @@ impl<'a, T> Deref for Signal<T> {
     }
 }
 
+/// Track all the `signals` in the effect scope. This is equivalent to calling
+/// [`ReadSignal::track`] on each signal, but the dependencies are added to the running effect all
+/// at once, which is faster for effects that depend on many signals.
+///
+/// See also [`on_all`].
+///
+/// # Example
+/// ```
+/// # use sycamore_reactive::*;
+/// # create_scope_immediate(|ctx| {
+/// let fields: Vec<&ReadSignal<String>> =
+///     (0..50).map(|_| &**ctx.create_signal(String::new())).collect();
+/// let dirty = ctx.create_signal(false);
+/// ctx.create_effect(move || {
+///     track_all(&fields);
+///     dirty.set(true);
+/// });
+/// # });
+/// ```
+pub fn track_all<T>(signals: &[&ReadSignal<T>]) {
+    EFFECTS.with(|effects| {
+        if let Some(last) = effects.borrow().last() {
+            // SAFETY: See guarantee on EffectState within EFFECTS.
+            let last = unsafe { &mut **last };
+            last.add_dependencies(signals.iter().map(|signal| signal.emitter.downgrade()));
+        }
+    });
+}
+
 /// A trait that is implemented for all [`ReadSignal`]s regardless of the type parameter.
 pub trait AnyReadSignal<'a> {
     /// Call the [`ReadSignal::track`] method.
@@ -315,6 +1550,13 @@ impl<T> Clone for RcSignal<T> {
     }
 }
 
+impl<T> RcSignal<T> {
+    /// Create a [`WeakRcSignal`] pointing to the same signal, which does not keep it alive.
+    pub fn downgrade(&self) -> WeakRcSignal<T> {
+        WeakRcSignal(Rc::downgrade(&self.0))
+    }
+}
+
 /// Create a new [`RcSignal`] with the specified initial value.
 ///
 /// For more details, check the documentation for [`RcSignal`].
@@ -322,6 +1564,47 @@ pub fn create_rc_signal<T>(value: T) -> RcSignal<T> {
     RcSignal(Rc::new(Signal::new(value)))
 }
 
+/// A weak reference to a [`RcSignal`], created with [`RcSignal::downgrade`].
+///
+/// Closures that outlive the code owning a [`RcSignal`], such as effects on a long-lived
+/// [`Scope`], keep the signal alive if they capture a clone of it. Capturing a [`WeakRcSignal`]
+/// instead allows the signal to be dropped as soon as its owners are done with it. The closure
+/// then calls [`upgrade`](Self::upgrade) to access the signal and does nothing if it is gone.
+///
+/// Tracking an upgraded signal inside an effect does not keep the signal alive either: effects
+/// only hold weak references to their dependencies.
+///
+/// # Example
+/// ```
+/// # use sycamore_reactive::*;
+/// # create_scope_immediate(|ctx| {
+/// let state = create_rc_signal(0);
+/// let weak = state.downgrade();
+/// ctx.create_effect(move || {
+///     if let Some(state) = weak.upgrade() {
+///         println!("State = {}", state.get());
+///     }
+/// });
+///
+/// state.set(1); // Prints "State = 1".
+/// drop(state); // The signal is dropped even though the effect is still alive.
+/// # });
+/// ```
+pub struct WeakRcSignal<T>(Weak<Signal<T>>);
+
+impl<T> WeakRcSignal<T> {
+    /// Returns the [`RcSignal`] if it has not been dropped yet.
+    pub fn upgrade(&self) -> Option<RcSignal<T>> {
+        self.0.upgrade().map(RcSignal)
+    }
+}
+
+impl<T> Clone for WeakRcSignal<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
 /* Display implementations */
 
 impl<T: Display> Display for RcSignal<T> {
@@ -347,6 +1630,11 @@ impl<T: Debug> Debug for RcSignal<T> {
         f.debug_tuple("RcSignal").field(&self.get()).finish()
     }
 }
+impl<T> Debug for WeakRcSignal<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("WeakRcSignal").finish_non_exhaustive()
+    }
+}
 impl<T: Debug> Debug for Signal<T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         f.debug_tuple("Signal").field(&self.get()).finish()
@@ -476,6 +1764,60 @@ mod tests {
         });
     }
 
+    #[test]
+    fn with_untracked_signal() {
+        create_scope_immediate(|ctx| {
+            let state = ctx.create_signal(vec![1, 2, 3]);
+            let counter = ctx.create_signal(0);
+            ctx.create_effect(|| {
+                counter.set(*counter.get_untracked() + 1);
+                assert_eq!(state.with_untracked(|v| v.iter().sum::<i32>()), 6);
+            });
+            assert_eq!(*counter.get(), 1);
+
+            state.set(vec![6]);
+            assert_eq!(*counter.get(), 1, "state should not be a dependency");
+        });
+    }
+
+    #[test]
+    fn peek_signal() {
+        create_scope_immediate(|ctx| {
+            let state = ctx.create_signal(String::from("a"));
+            let runs = ctx.create_signal(0);
+            ctx.create_effect(|| {
+                runs.set(*runs.peek() + 1);
+                let _ = state.peek();
+                let _ = state.peek_clone();
+            });
+            assert_eq!(*runs.peek(), 1);
+
+            state.set(String::from("b"));
+            assert_eq!(state.peek_clone(), "b");
+            assert_eq!(*runs.peek(), 1, "state should not be a dependency");
+        });
+    }
+
+    #[test]
+    fn sample_on_tracks_only_self() {
+        create_scope_immediate(|ctx| {
+            let a = ctx.create_signal(0);
+            let b = ctx.create_signal("x");
+            let calls = ctx.create_ref(RefCell::new(Vec::new()));
+            a.sample_on(ctx, b, |&a, &b| calls.borrow_mut().push((a, b)));
+            assert_eq!(*calls.borrow(), [(0, "x")]);
+
+            b.set("y");
+            b.set("z");
+            assert_eq!(*calls.borrow(), [(0, "x")], "b should not be a dependency");
+
+            a.set(1);
+            assert_eq!(*calls.borrow(), [(0, "x"), (1, "z")]);
+            a.set(2);
+            assert_eq!(*calls.borrow(), [(0, "x"), (1, "z"), (2, "z")]);
+        });
+    }
+
     #[test]
     fn map_signal() {
         create_scope_immediate(|ctx| {
@@ -488,6 +1830,555 @@ mod tests {
         });
     }
 
+    #[test]
+    fn misuse_reports_caller_location() {
+        type Hook = Rc<RefCell<Option<Box<dyn Fn()>>>>;
+        /// Calls the hook when dropped.
+        struct OnDrop(Hook);
+        impl Drop for OnDrop {
+            fn drop(&mut self) {
+                if let Some(f) = self.0.borrow_mut().take() {
+                    f();
+                }
+            }
+        }
+
+        let hook = Hook::default();
+        let signal = create_rc_signal(OnDrop(Rc::clone(&hook)));
+        let line = Rc::new(Cell::new(0));
+        let message = Rc::new(RefCell::new(String::new()));
+        *hook.borrow_mut() = Some(Box::new({
+            let signal = signal.clone();
+            let (line, message) = (Rc::clone(&line), Rc::clone(&message));
+            move || {
+                // Reading the signal while the old value is being replaced is not allowed.
+                let err = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                    let _ = (line.set(line!()), signal.get());
+                }))
+                .unwrap_err();
+                *message.borrow_mut() = err.downcast_ref::<String>().unwrap().clone();
+            }
+        }));
+
+        signal.set(OnDrop(Default::default()));
+        // The hook was taken out when it was called, which dropped its clone of the signal. The
+        // signal does not keep itself alive anymore.
+        assert!(hook.borrow().is_none());
+        let message = message.borrow();
+        let location = format!("{}:{}:", file!(), line.get());
+        assert!(
+            message.contains(&location),
+            "panic message should point to {location}, got: {message}"
+        );
+    }
+
+    #[test]
+    fn toggle_signal() {
+        create_scope_immediate(|ctx| {
+            let state = ctx.create_signal(false);
+            let notified = ctx.create_signal(0);
+            ctx.create_effect(|| {
+                state.track();
+                notified.set(*notified.get_untracked() + 1);
+            });
+
+            state.toggle();
+            assert!(*state.get());
+            assert_eq!(*notified.get(), 2);
+
+            state.set_false();
+            assert!(!*state.get());
+            state.set_true();
+            assert!(*state.get());
+            assert_eq!(*notified.get(), 4);
+        });
+    }
+
+    #[test]
+    fn toggle_signal_inside_effect() {
+        create_scope_immediate(|ctx| {
+            let trigger = ctx.create_signal(());
+            let state = ctx.create_signal(false);
+            let runs = ctx.create_signal(0);
+            ctx.create_effect(|| {
+                trigger.track();
+                runs.set(*runs.get_untracked() + 1);
+                state.toggle();
+            });
+            assert_eq!(*runs.get(), 1);
+            assert!(*state.get());
+
+            // The effect does not depend on `state`.
+            state.toggle();
+            assert_eq!(*runs.get(), 1);
+            assert!(!*state.get());
+
+            trigger.set(());
+            assert_eq!(*runs.get(), 2);
+            assert!(*state.get());
+        });
+    }
+
+    #[test]
+    fn signal_get_copy() {
+        create_scope_immediate(|ctx| {
+            let state = ctx.create_signal(1);
+            let double = ctx.create_memo(|| state.get_copy() * 2);
+            assert_eq!(state.get_copy(), 1);
+            assert_eq!(double.get_copy(), 2);
+
+            state.set(2);
+            assert_eq!(state.get_copy(), 2);
+            assert_eq!(double.get_copy(), 4, "get_copy tracks the signal");
+
+            let enabled = ctx.create_signal(false);
+            let runs = ctx.create_ref(Cell::new(0));
+            ctx.create_effect(|| {
+                if enabled.get_copy() {
+                    runs.set(runs.get() + 1);
+                }
+            });
+            enabled.set(true);
+            enabled.set(true);
+            assert_eq!(runs.get(), 2);
+        });
+    }
+
+    #[test]
+    fn inspect_signal() {
+        create_scope_immediate(|ctx| {
+            let state = ctx.create_signal(1);
+            let other = ctx.create_signal(0);
+            let seen = ctx.create_ref(RefCell::new(Vec::new()));
+            let double = state.map(ctx, |&x| x * 2).inspect(ctx, |&x| {
+                other.track();
+                seen.borrow_mut().push(x);
+            });
+            let tripled = double.map(ctx, |&x| x * 3);
+            assert_eq!(*seen.borrow(), [2]);
+
+            state.set(2);
+            state.set(3);
+            assert_eq!(*seen.borrow(), [2, 4, 6]);
+            assert_eq!(*double.get(), 6);
+            assert_eq!(*tripled.get(), 18);
+
+            other.set(1);
+            assert_eq!(*seen.borrow(), [2, 4, 6], "the tap is untracked");
+        });
+    }
+
+    #[test]
+    fn filter_signal() {
+        create_scope_immediate(|ctx| {
+            let state = ctx.create_signal(-1);
+            let positive = state.filter(ctx, |&x| x > 0);
+            assert_eq!(*positive.get(), 0, "initial value fails the predicate");
+
+            let notified = ctx.create_signal(0);
+            ctx.create_effect(|| {
+                positive.track();
+                notified.set(*notified.get_untracked() + 1);
+            });
+
+            let mut seen = Vec::new();
+            for value in [3, -2, 0, 5, -7, 8] {
+                state.set(value);
+                seen.push(*positive.get());
+            }
+            assert_eq!(seen, [3, 3, 3, 5, 5, 8]);
+            assert_eq!(
+                *notified.get(),
+                4,
+                "dependents are only notified of passing values"
+            );
+        });
+    }
+
+    #[test]
+    fn effect_scheduler_coalesces_updates() {
+        let pending = Rc::new(RefCell::new(Vec::<Box<dyn FnOnce()>>::new()));
+        set_effect_scheduler(Some(Rc::new({
+            let pending = Rc::clone(&pending);
+            move |f| pending.borrow_mut().push(f)
+        })));
+        create_scope_immediate(|ctx| {
+            let state = ctx.create_signal(0);
+            let double = ctx.create_memo(|| *state.get() * 2);
+            let counter = ctx.create_signal(0);
+            ctx.create_effect(|| {
+                double.track();
+                counter.set(*counter.get_untracked() + 1);
+            });
+            assert_eq!(*counter.get(), 1);
+            assert!(pending.borrow().is_empty());
+
+            state.set(1);
+            state.set(2);
+            state.set(3);
+            assert_eq!(*double.get(), 0, "effects are deferred");
+            assert_eq!(*counter.get(), 1);
+            assert_eq!(pending.borrow().len(), 1, "a single flush is scheduled");
+
+            let flush = pending.borrow_mut().pop().unwrap();
+            flush();
+            assert_eq!(*double.get(), 6);
+            assert_eq!(
+                *counter.get(),
+                2,
+                "the effect runs once for all the updates"
+            );
+            assert!(pending.borrow().is_empty());
+
+            state.set(4);
+            assert_eq!(pending.borrow().len(), 1);
+        });
+        set_effect_scheduler(None);
+    }
+
+    #[test]
+    fn signal_with_mut() {
+        create_scope_immediate(|ctx| {
+            let items = ctx.create_signal(vec![1, 2]);
+            let runs = ctx.create_ref(Cell::new(0));
+            ctx.create_effect(|| {
+                items.track();
+                runs.set(runs.get() + 1);
+            });
+            assert_eq!(runs.get(), 1);
+
+            let len = items.with_mut(|items| {
+                items.push(3);
+                items.push(4);
+                items.len()
+            });
+            assert_eq!(len, 4);
+            assert_eq!(*items.get(), [1, 2, 3, 4]);
+            assert_eq!(runs.get(), 2, "subscribers are triggered once");
+
+            // The old value is left untouched.
+            let old = items.get_untracked();
+            items.with_mut(|items| items.clear());
+            assert_eq!(*old, [1, 2, 3, 4]);
+            assert!(items.get().is_empty());
+        });
+    }
+
+    #[test]
+    fn signal_with_mut_reads_signal_inside_f() {
+        create_scope_immediate(|ctx| {
+            let items = ctx.create_signal(vec![1, 2]);
+            let runs = ctx.create_ref(Cell::new(0));
+            ctx.create_effect(|| {
+                items.track();
+                runs.set(runs.get() + 1);
+            });
+
+            let len = items.with_mut(|v| {
+                v.push(3);
+                // Reading the signal inside `f` returns the value from before the mutation.
+                assert_eq!(*items.get(), [1, 2]);
+                v.extend(items.get_untracked().iter().copied());
+                v.len()
+            });
+            assert_eq!(len, 5);
+            assert_eq!(*items.get(), [1, 2, 3, 1, 2]);
+            assert_eq!(runs.get(), 2);
+        });
+    }
+
+    #[test]
+    fn custom_scheduler_defers_effects_until_flushed() {
+        #[derive(Default)]
+        struct FrameScheduler {
+            pending: RefCell<Vec<Box<dyn FnOnce()>>>,
+        }
+        impl FrameScheduler {
+            fn flush(&self) {
+                let pending = self.pending.take();
+                for run in pending {
+                    run();
+                }
+            }
+        }
+        impl Scheduler for FrameScheduler {
+            fn schedule(&self, run: Box<dyn FnOnce()>) {
+                self.pending.borrow_mut().push(run);
+            }
+        }
+
+        let scheduler = Rc::new(FrameScheduler::default());
+        set_effect_scheduler(Some(scheduler.clone()));
+        create_scope_immediate(|ctx| {
+            let state = ctx.create_signal(0);
+            let seen = ctx.create_ref(RefCell::new(Vec::new()));
+            ctx.create_effect(|| seen.borrow_mut().push(*state.get()));
+            assert_eq!(*seen.borrow(), [0]);
+
+            state.set(1);
+            state.set(2);
+            assert_eq!(*seen.borrow(), [0], "effects do not run before the flush");
+
+            scheduler.flush();
+            assert_eq!(*seen.borrow(), [0, 2]);
+
+            scheduler.flush();
+            assert_eq!(*seen.borrow(), [0, 2], "nothing is pending");
+
+            state.set(3);
+            assert_eq!(*seen.borrow(), [0, 2]);
+            scheduler.flush();
+            assert_eq!(*seen.borrow(), [0, 2, 3]);
+        });
+        set_effect_scheduler(None);
+    }
+
+    #[test]
+    fn distinct_until_changed_signal() {
+        create_scope_immediate(|ctx| {
+            let state = ctx.create_signal(0);
+            let distinct = state.map(ctx, |&x| x * 2).distinct_until_changed(ctx);
+
+            let notified = ctx.create_signal(0);
+            ctx.create_effect(|| {
+                distinct.track();
+                notified.set(*notified.get_untracked() + 1);
+            });
+
+            let mut seen = Vec::new();
+            for value in [1, 1, 2, 2, 2, 1, 3, 3] {
+                state.set(value);
+                seen.push(*distinct.get());
+            }
+            assert_eq!(seen, [2, 2, 4, 4, 4, 2, 6, 6]);
+            assert_eq!(
+                *notified.get(),
+                5,
+                "dependents are only notified of distinct values"
+            );
+        });
+    }
+
+    #[test]
+    fn eq_value_signal() {
+        create_scope_immediate(|ctx| {
+            let state = ctx.create_signal(0);
+            let eq = state.eq_value(ctx, 2);
+            let when = state.when_eq(ctx, 2);
+
+            let eq_notified = ctx.create_signal(0);
+            let when_notified = ctx.create_signal(0);
+            ctx.create_effect(|| {
+                eq.track();
+                eq_notified.set(*eq_notified.get_untracked() + 1);
+            });
+            ctx.create_effect(|| {
+                when.track();
+                when_notified.set(*when_notified.get_untracked() + 1);
+            });
+
+            let mut seen = Vec::new();
+            for value in [1, 2, 2, 3, 4, 2] {
+                state.set(value);
+                assert_eq!(*eq.get(), *when.get());
+                seen.push(*when.get());
+            }
+            assert_eq!(seen, [false, true, true, false, false, true]);
+            assert_eq!(*eq_notified.get(), 7, "notified on every update");
+            assert_eq!(
+                *when_notified.get(),
+                4,
+                "only notified when crossing the compared value"
+            );
+        });
+    }
+
+    #[test]
+    fn zip_signal() {
+        create_scope_immediate(|ctx| {
+            let a = ctx.create_signal(1);
+            let b = ctx.create_signal("one");
+            let zipped = a.zip(ctx, b);
+            assert_eq!(*zipped.get(), (1, "one"));
+
+            a.set(2);
+            assert_eq!(*zipped.get(), (2, "one"));
+            b.set("two");
+            assert_eq!(*zipped.get(), (2, "two"));
+        });
+    }
+
+    #[test]
+    fn trigger_tick_wraps_around() {
+        TRIGGER_TICK.with(|tick| tick.set(u64::MAX - 1));
+        create_scope_immediate(|ctx| {
+            let state = ctx.create_signal(0);
+            let runs = ctx.create_ref(Cell::new(0));
+            ctx.create_effect(|| {
+                runs.set(runs.get() + 1);
+                let value = *state.get();
+                // Make the dependency stale so that the effect is re-run once.
+                if value % 2 == 1 {
+                    state.set(value + 1);
+                }
+            });
+            assert_eq!(runs.get(), 1);
+
+            // The counter wraps around while the effect is running.
+            state.set(1);
+            assert_eq!(current_trigger_tick(), 0);
+            assert_eq!(*state.get(), 2);
+            assert_eq!(runs.get(), 3);
+
+            state.set(3);
+            assert_eq!(*state.get(), 4);
+            assert_eq!(runs.get(), 5);
+        });
+    }
+
+    #[test]
+    fn batch_updates() {
+        create_scope_immediate(|ctx| {
+            let a = ctx.create_signal(0);
+            let b = ctx.create_signal(0);
+            let sum = ctx.create_memo(|| *a.get() + *b.get());
+            let runs = ctx.create_ref(Cell::new(0));
+            ctx.create_effect(|| {
+                a.track();
+                b.track();
+                runs.set(runs.get() + 1);
+            });
+
+            let ret = batch(|| {
+                a.set(1);
+                b.set(2);
+                assert_eq!(*sum.get(), 0, "memos are updated after the batch");
+                "done"
+            });
+            assert_eq!(ret, "done");
+            assert_eq!(*sum.get(), 3);
+            assert_eq!(runs.get(), 2);
+        });
+    }
+
+    #[test]
+    fn batch_coalesces_writes() {
+        create_scope_immediate(|ctx| {
+            let state = ctx.create_signal(0);
+            let observed = ctx.create_ref(RefCell::new(Vec::new()));
+            ctx.create_effect(|| observed.borrow_mut().push(*state.get()));
+
+            batch(|| {
+                state.set(1);
+                state.set(2);
+            });
+            assert_eq!(*observed.borrow(), [0, 2]);
+
+            // Writes made by an effect that is re-run are coalesced as well.
+            let trigger = ctx.create_signal(());
+            ctx.create_effect(|| {
+                trigger.track();
+                state.set(3);
+                state.set(4);
+            });
+            observed.borrow_mut().clear();
+            trigger.set(());
+            assert_eq!(*observed.borrow(), [4]);
+        });
+    }
+
+    #[test]
+    fn signal_set_if_changed() {
+        create_scope_immediate(|ctx| {
+            let state = ctx.create_signal(0);
+            let runs = ctx.create_ref(Cell::new(0));
+            ctx.create_effect(|| {
+                state.track();
+                runs.set(runs.get() + 1);
+            });
+
+            assert!(!state.set_if_changed(0));
+            assert!(!state.set_if_changed(0));
+            assert_eq!(runs.get(), 1);
+
+            assert!(state.set_if_changed(1));
+            assert_eq!(*state.get(), 1);
+            assert_eq!(runs.get(), 2);
+        });
+    }
+
+    #[test]
+    fn signal_compare_and_set() {
+        create_scope_immediate(|ctx| {
+            let state = ctx.create_signal(0);
+            let runs = ctx.create_ref(Cell::new(0));
+            ctx.create_effect(|| {
+                state.track();
+                runs.set(runs.get() + 1);
+            });
+
+            let read = *state.get();
+            // The state changes between the read and the write.
+            state.set(5);
+            assert!(!state.compare_and_set(&read, read + 1));
+            assert_eq!(*state.get(), 5);
+            assert_eq!(runs.get(), 2);
+
+            let read = *state.get();
+            assert!(state.compare_and_set(&read, read + 1));
+            assert_eq!(*state.get(), 6);
+            assert_eq!(runs.get(), 3);
+        });
+    }
+
+    #[test]
+    fn flatten_signal() {
+        create_scope_immediate(|ctx| {
+            let a = ctx.create_signal(1);
+            let b = ctx.create_signal(10);
+            let outer = ctx.create_signal(&**a);
+            let flat = outer.flatten(ctx);
+            let runs = ctx.create_ref(Cell::new(0));
+            ctx.create_effect(|| {
+                flat.track();
+                runs.set(runs.get() + 1);
+            });
+            assert_eq!(*flat.get(), 1);
+
+            a.set(2);
+            assert_eq!(*flat.get(), 2);
+            assert_eq!(runs.get(), 2);
+
+            outer.set(b);
+            assert_eq!(*flat.get(), 10);
+            assert_eq!(runs.get(), 3);
+            b.set(11);
+            assert_eq!(*flat.get(), 11);
+            assert_eq!(runs.get(), 4);
+
+            // The previous inner signal is no longer tracked.
+            a.set(3);
+            assert_eq!(*flat.get(), 11);
+            assert_eq!(runs.get(), 4);
+        });
+    }
+
+    #[test]
+    fn combine_signals() {
+        create_scope_immediate(|ctx| {
+            let a = ctx.create_signal(1);
+            let b = ctx.create_signal(2);
+            let c = ctx.create_signal(3);
+            let combined = combine!(ctx, a, b, c);
+            assert_eq!(*combined.get(), (1, 2, 3));
+
+            c.set(4);
+            assert_eq!(*combined.get(), (1, 2, 4));
+            a.set(0);
+            assert_eq!(*combined.get(), (0, 2, 4));
+        });
+    }
+
     #[test]
     fn take_signal() {
         create_scope_immediate(|ctx| {
@@ -540,6 +2431,129 @@ mod tests {
         assert_eq!(*outer.unwrap().get(), 1);
     }
 
+    #[test]
+    fn weak_rc_signal_in_effect() {
+        create_scope_immediate(|ctx| {
+            let trigger = ctx.create_signal(());
+            let state = create_rc_signal(0);
+            let weak = state.downgrade();
+            let seen = ctx.create_ref(RefCell::new(Vec::new()));
+            ctx.create_effect({
+                let weak = weak.clone();
+                move || {
+                    trigger.track();
+                    seen.borrow_mut()
+                        .push(weak.upgrade().map(|state| *state.get()));
+                }
+            });
+            assert_eq!(*seen.borrow(), [Some(0)]);
+
+            state.set(1);
+            assert_eq!(*seen.borrow(), [Some(0), Some(1)]);
+
+            // The effect does not keep the signal alive.
+            drop(state);
+            assert!(weak.upgrade().is_none());
+
+            trigger.set(());
+            assert_eq!(*seen.borrow(), [Some(0), Some(1), None]);
+        });
+    }
+
+    #[cfg(feature = "devtools")]
+    #[test]
+    fn signal_recent_changes() {
+        create_scope_immediate(|ctx| {
+            let state = ctx.create_signal(0);
+            assert!(state.recent_changes().is_empty());
+
+            let set_from_a = || state.set(1);
+            let set_from_b = || state.set(2);
+            set_from_a();
+            set_from_b();
+
+            let changes = state.recent_changes();
+            assert_eq!(changes.len(), 2);
+            assert_ne!(changes[0].location, changes[1].location);
+            assert!(changes[0].tick < changes[1].tick);
+            assert_eq!(changes[0].location.file(), file!());
+        });
+    }
+
+    #[cfg(feature = "devtools")]
+    #[test]
+    fn signal_recent_changes_is_bounded() {
+        create_scope_immediate(|ctx| {
+            let state = ctx.create_signal(0);
+            for i in 0..CHANGE_LOG_CAPACITY * 2 {
+                state.set(i);
+            }
+            assert_eq!(state.recent_changes().len(), CHANGE_LOG_CAPACITY);
+        });
+    }
+
+    #[cfg(feature = "devtools")]
+    #[test]
+    fn signal_dependents() {
+        create_scope_immediate(|ctx| {
+            let state = ctx.create_signal(0);
+            assert_eq!(state.dependent_count(), 0);
+
+            ctx.create_effect(|| state.track());
+            let disposer = ctx.create_child_scope(|ctx| {
+                ctx.create_effect(|| state.track());
+            });
+            assert_eq!(state.dependent_count(), 2);
+            let dependents = state.dependents();
+            assert_eq!(dependents.len(), 2);
+            assert_ne!(dependents[0], dependents[1]);
+
+            unsafe {
+                disposer.dispose();
+            }
+            assert_eq!(state.dependent_count(), 1);
+            assert_eq!(state.dependents(), &dependents[..1]);
+        });
+    }
+
+    #[cfg(feature = "devtools")]
+    #[test]
+    fn signal_dropped_callback() {
+        let dropped = Rc::new(RefCell::new(Vec::new()));
+        on_signal_dropped(Some(Rc::new({
+            let dropped = Rc::clone(&dropped);
+            move |id, name| dropped.borrow_mut().push((id, name))
+        })));
+
+        let ids = Rc::new(RefCell::new(Vec::new()));
+        create_scope_immediate(|ctx| {
+            let count = ctx.create_signal(0);
+            count.set_debug_name("count");
+            assert_eq!(count.debug_name(), Some("count"));
+            let anonymous = ctx.create_signal(0);
+            ids.borrow_mut().push((count.id(), Some("count")));
+            ids.borrow_mut().push((anonymous.id(), None));
+
+            let disposer = ctx.create_child_scope(|ctx| {
+                let nested = ctx.create_signal(0);
+                nested.set_debug_name("nested");
+                ids.borrow_mut().push((nested.id(), Some("nested")));
+            });
+            assert!(dropped.borrow().is_empty());
+            unsafe {
+                disposer.dispose();
+            }
+            assert_eq!(*dropped.borrow(), ids.borrow()[2..]);
+        });
+        on_signal_dropped(None);
+
+        let dropped = dropped.borrow();
+        assert_eq!(dropped.len(), 3);
+        for id in ids.borrow().iter() {
+            assert!(dropped.contains(id), "{:?} was not reported as dropped", id);
+        }
+    }
+
     #[test]
     fn signal_display() {
         create_scope_immediate(|ctx| {
@@ -0,0 +1,23 @@
+use std::cell::Cell;
+
+use sycamore_reactive::*;
+
+fn main() {
+    create_scope_immediate(|ctx| {
+        // An effect in the parent scope cannot read a signal from a child scope since the effect
+        // can run after the child scope is disposed.
+        let _ = ctx.create_child_scope(|child| {
+            let signal = child.create_signal(0);
+            ctx.create_effect(move || {
+                signal.track();
+            });
+        });
+
+        // A signal from a child scope cannot be smuggled out of it either.
+        let outer = ctx.create_ref(Cell::new(None));
+        let _ = ctx.create_child_scope(|child| {
+            let signal = child.create_signal(0);
+            outer.set(Some(signal));
+        });
+    });
+}
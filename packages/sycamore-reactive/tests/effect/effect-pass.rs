@@ -0,0 +1,38 @@
+use std::cell::Cell;
+
+use sycamore_reactive::*;
+
+fn main() {
+    create_scope_immediate(|ctx| {
+        let parent_signal = ctx.create_signal(0);
+        let runs = ctx.create_ref(Cell::new(0));
+
+        // An effect in a child scope can read signals from its parent scope since the parent scope
+        // outlives the child scope.
+        let (child_signal, disposer) = ctx.create_child_scope_with_safe(|child| {
+            let local = child.create_signal(1);
+            child.create_effect(move || {
+                parent_signal.track();
+                local.track();
+                runs.set(runs.get() + 1);
+            });
+            // A signal that is read by effects outside of the child scope must be owned instead.
+            create_rc_signal(2)
+        });
+        parent_signal.set(1);
+        assert_eq!(runs.get(), 2);
+
+        // An effect in the parent scope can read a signal that was created in the child scope
+        // if it owns it.
+        ctx.create_effect({
+            let child_signal = child_signal.clone();
+            move || {
+                child_signal.track();
+                runs.set(runs.get() + 1);
+            }
+        });
+        disposer.dispose();
+        child_signal.set(3);
+        assert_eq!(runs.get(), 4);
+    });
+}
@@ -0,0 +1,8 @@
+#[test]
+fn effect_ui() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/effect/*-pass.rs");
+    if std::env::var("RUN_UI_TESTS").is_ok() {
+        t.compile_fail("tests/effect/*-fail.rs");
+    }
+}
@@ -14,6 +14,18 @@ pub fn bench(c: &mut Criterion) {
         });
     });
 
+    c.bench_function("reactivity_signals_get_copy", |b| {
+        b.iter(|| {
+            create_scope_immediate(|ctx| {
+                let state = ctx.create_signal(0);
+
+                for _i in 0..1000 {
+                    state.set(state.get_copy() + 1);
+                }
+            });
+        });
+    });
+
     c.bench_function("reactivity_effects", |b| {
         b.iter(|| {
             create_scope_immediate(|ctx| {
@@ -0,0 +1,61 @@
+//! Toggling CSS classes reactively.
+
+use crate::generic_node::GenericNode;
+use crate::noderef::NodeRef;
+use crate::reactive::*;
+
+/// Extension trait for [`Scope`] adding the [`class_list`](ScopeClassListExt::class_list) method.
+pub trait ScopeClassListExt<'a> {
+    /// Reactively add or remove each class in `classes` on the node referenced by `node_ref`.
+    ///
+    /// A class is added when its signal is `true` and removed when it is `false`. Each class is
+    /// updated by its own effect, so changing one signal does not touch the other classes of the
+    /// node, including the classes that are not in `classes`.
+    ///
+    /// The effects are disposed along with the scope.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use sycamore::prelude::*;
+    /// # create_scope_immediate(|ctx| {
+    /// let count = ctx.create_signal(0);
+    /// let even = ctx.create_memo(|| *count.get() % 2 == 0);
+    /// let large = ctx.create_memo(|| *count.get() > 100);
+    /// let counter = ctx.create_node_ref();
+    /// let view: View<DomNode> = view! { ctx,
+    ///     span(ref=counter, class="counter") { (count.get()) }
+    /// };
+    /// ctx.class_list(counter, [("even", even), ("large", large)]);
+    /// # });
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `node_ref` is not set yet. It should therefore be called after the
+    /// element is created, e.g. after the `view!` containing it.
+    fn class_list<G: GenericNode>(
+        &'a self,
+        node_ref: &NodeRef<G>,
+        classes: impl IntoIterator<Item = (&'a str, &'a ReadSignal<bool>)>,
+    );
+}
+
+impl<'a> ScopeClassListExt<'a> for Scope<'a> {
+    fn class_list<G: GenericNode>(
+        &'a self,
+        node_ref: &NodeRef<G>,
+        classes: impl IntoIterator<Item = (&'a str, &'a ReadSignal<bool>)>,
+    ) {
+        let node = node_ref.get_raw();
+        for (class, enabled) in classes {
+            let node = node.clone();
+            self.create_effect(move || {
+                if *enabled.get() {
+                    node.add_class(class);
+                } else {
+                    node.remove_class(class);
+                }
+            });
+        }
+    }
+}
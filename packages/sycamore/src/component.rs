@@ -1,5 +1,7 @@
 //! Utilities for components and component properties.
 
+use std::collections::HashMap;
+
 use crate::generic_node::GenericNode;
 use crate::reactive::*;
 use crate::view::View;
@@ -90,3 +92,89 @@ impl<'a, G: GenericNode> Children<'a, G> {
         Self { f: Box::new(f) }
     }
 }
+
+/// Named component children, for components that render several pieces of content supplied by
+/// their parent in different places (e.g. the header and the body of a card).
+///
+/// # Example
+/// ```
+/// use sycamore::component::Slots;
+/// use sycamore::prelude::*;
+///
+/// #[derive(Prop)]
+/// struct CardProps<'a, G: GenericNode> {
+///     slots: Slots<'a, G>,
+/// }
+///
+/// #[component]
+/// fn Card<'a, G: Html>(ctx: ScopeRef<'a>, mut props: CardProps<'a, G>) -> View<G> {
+///     let header = props.slots.render(ctx, "header");
+///     let body = props.slots.render(ctx, "body");
+///     view! { ctx,
+///         header { (header) }
+///         div(class="card-body") { (body) }
+///     }
+/// }
+///
+/// #[component]
+/// fn App<G: Html>(ctx: ScopeRef) -> View<G> {
+///     view! { ctx,
+///         Card {
+///             slots: Slots::new()
+///                 .add("header", |ctx| view! { ctx, "Title" })
+///                 .add("body", |ctx| view! { ctx, "Content" }),
+///         }
+///     }
+/// }
+/// ```
+pub struct Slots<'a, G: GenericNode> {
+    slots: HashMap<&'static str, Children<'a, G>>,
+}
+
+impl<'a, G: GenericNode> Slots<'a, G> {
+    /// Creates an empty [`Slots`].
+    pub fn new() -> Self {
+        Self {
+            slots: HashMap::new(),
+        }
+    }
+
+    /// Adds the content of the slot called `name`, replacing the previous content if any.
+    pub fn add(
+        mut self,
+        name: &'static str,
+        f: impl FnOnce(BoundedScopeRef<'_, 'a>) -> View<G> + 'a,
+    ) -> Self {
+        self.slots.insert(name, Children::from(f));
+        self
+    }
+
+    /// Returns `true` if the slot called `name` has content that was not taken yet.
+    pub fn contains(&self, name: &str) -> bool {
+        self.slots.contains_key(name)
+    }
+
+    /// Removes the content of the slot called `name` and returns it, or returns `None` if there
+    /// is none.
+    pub fn take(&mut self, name: &str) -> Option<Children<'a, G>> {
+        self.slots.remove(name)
+    }
+
+    /// Instantiates the content of the slot called `name` with the passed [`ScopeRef`]. Returns
+    /// an empty [`View`] if the slot has no content or if it was already taken.
+    pub fn render<'b>(&mut self, ctx: ScopeRef<'b>, name: &str) -> View<G>
+    where
+        'a: 'b,
+    {
+        match self.take(name) {
+            Some(children) => children.call(ctx),
+            None => View::empty(),
+        }
+    }
+}
+
+impl<'a, G: GenericNode> Default for Slots<'a, G> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
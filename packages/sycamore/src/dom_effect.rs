@@ -0,0 +1,53 @@
+//! Effects that only run in the browser.
+
+use crate::reactive::*;
+
+/// Context that is provided in the root scope when rendering on the server.
+struct SsrMarker;
+
+/// Marks `ctx` and all its descendants as being rendered on the server.
+#[cfg(feature = "ssr")]
+pub(crate) fn provide_ssr_marker(ctx: ScopeRef<'_>) {
+    ctx.provide_context(SsrMarker);
+}
+
+/// Extension trait for [`Scope`] adding the
+/// [`create_dom_effect`](ScopeDomEffectExt::create_dom_effect) and
+/// [`is_ssr`](ScopeDomEffectExt::is_ssr) methods.
+pub trait ScopeDomEffectExt<'a> {
+    /// Returns `true` if the scope is being rendered on the server, i.e. it was created inside
+    /// [`render_to_string`](crate::render_to_string) or one of its variants.
+    fn is_ssr(&'a self) -> bool;
+
+    /// Creates an effect that is only created when the scope is not rendered on the server. This
+    /// is useful for effects that access the DOM in components that are shared between the server
+    /// and the client.
+    ///
+    /// During server side rendering (see [`is_ssr`](Self::is_ssr)), `f` is never called and
+    /// therefore does not track any signal. Use [`create_effect`](Scope::create_effect) or
+    /// [`create_memo`](Scope::create_memo) for state that is needed to render the view.
+    ///
+    /// # Example
+    /// ```
+    /// # use sycamore::prelude::*;
+    /// fn title<G: Html>(ctx: ScopeRef, title: &'static str) -> View<G> {
+    ///     ctx.create_dom_effect(move || {
+    ///         web_sys::window().unwrap().document().unwrap().set_title(title);
+    ///     });
+    ///     view! { ctx, h1 { (title) } }
+    /// }
+    /// ```
+    fn create_dom_effect(&'a self, f: impl FnMut() + 'a);
+}
+
+impl<'a> ScopeDomEffectExt<'a> for Scope<'a> {
+    fn is_ssr(&'a self) -> bool {
+        self.try_use_context::<SsrMarker>().is_some()
+    }
+
+    fn create_dom_effect(&'a self, f: impl FnMut() + 'a) {
+        if !self.is_ssr() {
+            self.create_effect(f);
+        }
+    }
+}
@@ -1,19 +1,25 @@
 //! Iteration utility components for [view!](crate::view!).
 //!
 //! Iteration can be either _"keyed"_ or _"non keyed"_.
-//! Use the [`Keyed`] and [`Indexed`] utility components respectively.
+//! Use the [`Keyed`] and [`Indexed`] utility components respectively. To render keyed items in
+//! sections with headers, use [`KeyedGrouped`]. To share a value with the view of every item, use
+//! [`KeyedWithContext`] and [`IndexedWithContext`].
 
-use std::hash::Hash;
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
 
 use crate::prelude::*;
 
 /// Props for [`Keyed`].
 #[derive(Prop)]
+#[allow(explicit_outlives_requirements)] // The bound on `Key` is needed by the generated builder.
 pub struct KeyedProps<'a, T, F, G: GenericNode, K, Key>
 where
     F: Fn(BoundedScopeRef<'_, 'a>, T) -> View<G> + 'a,
     K: Fn(&T) -> Key + 'a,
-    Key: Clone + Hash + Eq,
+    Key: Clone + Hash + Eq + fmt::Debug + 'a,
     T: Clone + PartialEq,
 {
     iterable: &'a ReadSignal<Vec<T>>,
@@ -21,6 +27,38 @@ where
     view: F,
     /// The key function that assigns each element in `iterable` an unique key.
     key: K,
+    /// The maximum number of elements of `iterable` to render. Views are only created for the
+    /// rendered elements.
+    #[builder(default, setter(strip_option))]
+    max: Option<usize>,
+    /// Rendered after the items when `iterable` has more than `max` elements. Receives the number
+    /// of elements that are not rendered.
+    #[builder(default, setter(transform = |f: impl Fn(usize) -> View<G> + 'a| Some(Box::new(f) as Box<dyn Fn(usize) -> View<G> + 'a>)))]
+    overflow: Option<Box<dyn Fn(usize) -> View<G> + 'a>>,
+    /// Accumulates [`ReconcileStats`] every time the list is diffed. Only updated when the
+    /// `profiling` feature is enabled and `renames` is not set.
+    #[builder(default, setter(strip_option))]
+    stats: Option<&'a Signal<ReconcileStats>>,
+    /// Changes the keys of elements without re-rendering them, e.g. to replace the temporary key
+    /// of an element that was added optimistically with the key assigned by the server. See
+    /// [`KeyRenames`].
+    #[builder(default, setter(strip_option))]
+    renames: Option<&'a KeyRenames<Key>>,
+    /// Set to the number of elements in `iterable`, including the elements that are not rendered
+    /// because of `max`. Only updated when the number changes, so that the subscribers of the
+    /// signal are not re-run when an element is updated.
+    #[builder(default, setter(strip_option))]
+    len: Option<&'a Signal<usize>>,
+    /// If `true`, the elements are rendered in the reverse order of `iterable`, e.g. to show the
+    /// newest element at the bottom of a list that is sorted newest first. Elements are still
+    /// reconciled in the order of `iterable`, so moving elements moves their views as usual.
+    #[builder(default)]
+    reverse: bool,
+    /// Called when the end of the list scrolls into view, e.g. to load more items. An empty
+    /// sentinel `<div>` is rendered after the items and watched with an `IntersectionObserver`.
+    /// Only supported on the DOM backend.
+    #[builder(default, setter(transform = |f: impl Fn() + 'a| Some(Box::new(f) as Box<dyn Fn() + 'a>)))]
+    on_reach_end: Option<Box<dyn Fn() + 'a>>,
 }
 
 /// Keyed iteration. Use this instead of directly rendering an array of [`View`]s.
@@ -36,19 +74,145 @@ pub fn Keyed<'a, G: GenericNode, T, F, K, Key>(
 where
     F: Fn(BoundedScopeRef<'_, 'a>, T) -> View<G> + 'a,
     K: Fn(&T) -> Key + 'a,
-    Key: Clone + Hash + Eq,
+    Key: Clone + Hash + Eq + fmt::Debug + 'a,
     T: Clone + Eq,
 {
     let KeyedProps {
         iterable,
         view,
         key,
+        max,
+        overflow,
+        stats,
+        renames,
+        len,
+        reverse,
+        on_reach_end,
     } = props;
 
-    let mapped = ctx.map_keyed(iterable, view, key);
+    if let Some(len) = len {
+        ctx.create_effect(move || {
+            len.set_if_changed(iterable.get().len());
+        });
+    }
+    let capped = match max {
+        Some(max) => {
+            ctx.create_selector(move || iterable.get().iter().take(max).cloned().collect())
+        }
+        None => iterable,
+    };
+    let mapped = match (stats, renames) {
+        (_, Some(renames)) => ctx.map_keyed_with_renames(capped, view, key, renames),
+        (Some(stats), None) => ctx.map_keyed_with_stats(capped, view, key, stats),
+        (None, None) => ctx.map_keyed(capped, view, key),
+    };
+    let items = render_items(ctx, mapped, reverse);
+    let items = match on_reach_end {
+        Some(on_reach_end) => {
+            let sentinel = G::element("div");
+            sentinel.set_attribute("aria-hidden", "true");
+            observe_sentinel(ctx, &sentinel, iterable, on_reach_end);
+            View::new_fragment(vec![items, View::new_node(sentinel)])
+        }
+        None => items,
+    };
+    with_overflow(ctx, items, iterable, max, overflow)
+}
+
+/// Props for [`KeyedGrouped`].
+#[derive(Prop)]
+pub struct KeyedGroupedProps<'a, T, F, G: GenericNode, K, Key, B, Group, H>
+where
+    F: Fn(BoundedScopeRef<'_, 'a>, T) -> View<G> + 'a,
+    K: Fn(&T) -> Key + 'a,
+    Key: Clone + Hash + Eq + fmt::Debug + 'a,
+    B: Fn(&T) -> Group + 'a,
+    Group: Clone + Hash + Eq + fmt::Debug + 'a,
+    H: Fn(BoundedScopeRef<'_, 'a>, Group) -> View<G> + 'a,
+    T: Clone + PartialEq,
+{
+    iterable: &'a ReadSignal<Vec<T>>,
+    /// The map function that renders a [`View`] for each element in `iterable`.
+    view: F,
+    /// The key function that assigns each element in `iterable` an unique key.
+    key: K,
+    /// The function that assigns each element in `iterable` to a group.
+    group_by: B,
+    /// The map function that renders the header [`View`] of each group.
+    header: H,
+}
+
+/// Keyed iteration with the items split into groups. Each group is rendered as a header followed
+/// by the items in the group.
+///
+/// Groups are rendered in the order in which they first appear in `iterable`. Items within a group
+/// keep their relative order. Both headers and items are keyed, which means that moving an item
+/// to another group only moves its view, and headers are only re-rendered when a group is added.
+///
+/// For keyed iteration without groups, see [`Keyed`].
+#[component]
+pub fn KeyedGrouped<'a, G: GenericNode, T, F, K, Key, B, Group, H>(
+    ctx: ScopeRef<'a>,
+    props: KeyedGroupedProps<'a, T, F, G, K, Key, B, Group, H>,
+) -> View<G>
+where
+    F: Fn(BoundedScopeRef<'_, 'a>, T) -> View<G> + 'a,
+    K: Fn(&T) -> Key + 'a,
+    Key: Clone + Hash + Eq + fmt::Debug + 'a,
+    B: Fn(&T) -> Group + 'a,
+    Group: Clone + Hash + Eq + fmt::Debug + 'a,
+    H: Fn(BoundedScopeRef<'_, 'a>, Group) -> View<G> + 'a,
+    T: Clone + PartialEq,
+{
+    let KeyedGroupedProps {
+        iterable,
+        view,
+        key,
+        group_by,
+        header,
+    } = props;
+
+    let entries = ctx.create_memo(move || {
+        let mut groups: Vec<(Group, Vec<T>)> = Vec::new();
+        let mut indices = HashMap::new();
+        for item in iterable.get().iter() {
+            let group = group_by(item);
+            let i = *indices.entry(group.clone()).or_insert_with(|| {
+                groups.push((group, Vec::new()));
+                groups.len() - 1
+            });
+            groups[i].1.push(item.clone());
+        }
+        groups
+            .into_iter()
+            .flat_map(|(group, items)| {
+                std::iter::once(GroupedEntry::Header(group))
+                    .chain(items.into_iter().map(GroupedEntry::Item))
+            })
+            .collect()
+    });
+    let mapped = ctx.map_keyed(
+        entries,
+        move |ctx, entry| match entry {
+            GroupedEntry::Header(group) => header(ctx, group),
+            GroupedEntry::Item(item) => view(ctx, item),
+        },
+        move |entry| match entry {
+            GroupedEntry::Header(group) => GroupedEntry::Header(group.clone()),
+            GroupedEntry::Item(item) => GroupedEntry::Item(key(item)),
+        },
+    );
     View::new_dyn(ctx, || View::new_fragment(mapped.get().as_ref().clone()))
 }
 
+/// An entry of the flattened list rendered by [`KeyedGrouped`]. Also used for the keys of the
+/// entries.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+enum GroupedEntry<Group, T> {
+    Header(Group),
+    Item(T),
+}
+
 /// Props for [`Indexed`].
 #[derive(Prop)]
 pub struct IndexedProps<'a, G: GenericNode, T, F>
@@ -58,13 +222,102 @@ where
     iterable: &'a ReadSignal<Vec<T>>,
     /// The map function that renders a [`View`] for each element in `iterable`.
     view: F,
+    /// The maximum number of elements of `iterable` to render. Views are only created for the
+    /// rendered elements.
+    #[builder(default, setter(strip_option))]
+    max: Option<usize>,
+    /// Rendered after the items when `iterable` has more than `max` elements. Receives the number
+    /// of elements that are not rendered.
+    #[builder(default, setter(transform = |f: impl Fn(usize) -> View<G> + 'a| Some(Box::new(f) as Box<dyn Fn(usize) -> View<G> + 'a>)))]
+    overflow: Option<Box<dyn Fn(usize) -> View<G> + 'a>>,
+    /// If `true`, inserting or removing elements at a single position shifts the views of the
+    /// other elements instead of updating every view after that position. See
+    /// [`map_indexed_detect_shifts`](Scope::map_indexed_detect_shifts).
+    #[builder(default)]
+    detect_shifts: bool,
+    /// Assigns a key to each element in `iterable`. If set, the elements are reconciled by key
+    /// like with [`Keyed`], so that reordering elements moves their views instead of updating
+    /// them. `detect_shifts` is then ignored.
+    #[builder(default, setter(transform = |f: impl Fn(&T) -> IndexedKey + 'a| Some(Box::new(f) as KeyHint<'a, T>)))]
+    key_hint: Option<KeyHint<'a, T>>,
+    /// If `true`, the elements are rendered in the reverse order of `iterable`. Elements are still
+    /// mapped by their index in `iterable`.
+    #[builder(default)]
+    reverse: bool,
+}
+
+/// The `key_hint` of [`Indexed`].
+type KeyHint<'a, T> = Box<dyn Fn(&T) -> IndexedKey + 'a>;
+
+/// A key returned by the `key_hint` of [`Indexed`]. Any type that can be used as a key for
+/// [`Keyed`] can be converted into an [`IndexedKey`] with [`IndexedKey::new`].
+#[derive(Debug)]
+pub struct IndexedKey(Box<dyn DynKey>);
+
+impl IndexedKey {
+    /// Create a new [`IndexedKey`] from `key`. Keys of different types are never equal.
+    pub fn new<K: Hash + Eq + fmt::Debug + 'static>(key: K) -> Self {
+        Self(Box::new(key))
+    }
+}
+
+impl PartialEq for IndexedKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.dyn_eq(other.0.as_any())
+    }
+}
+impl Eq for IndexedKey {}
+
+impl Hash for IndexedKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.dyn_hash(state);
+    }
+}
+
+/// Object safe version of [`Hash`] and [`Eq`] for [`IndexedKey`].
+trait DynKey: fmt::Debug {
+    fn as_any(&self) -> &dyn Any;
+    fn dyn_eq(&self, other: &dyn Any) -> bool;
+    fn dyn_hash(&self, state: &mut dyn Hasher);
+}
+
+impl<K: Hash + Eq + fmt::Debug + 'static> DynKey for K {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn dyn_eq(&self, other: &dyn Any) -> bool {
+        other.downcast_ref::<K>() == Some(self)
+    }
+
+    fn dyn_hash(&self, mut state: &mut dyn Hasher) {
+        self.hash(&mut state);
+    }
 }
 
 /// Non keyed iteration (or keyed by index). Use this instead of directly rendering an array of
 /// [`View`]s. Using this will minimize re-renders instead of re-rendering every single
 /// node on every state change.
 ///
-/// For keyed iteration, see [`Keyed`].
+/// For keyed iteration, see [`Keyed`]. Alternatively, a `key_hint` can be provided to reconcile the
+/// elements by key without changing the component:
+///
+/// ```
+/// # use sycamore::prelude::*;
+/// # #[derive(Clone, PartialEq)]
+/// # struct Todo { id: u32, title: String }
+/// # fn view<'a, G: Html>(ctx: ScopeRef<'a>, todos: &'a ReadSignal<Vec<Todo>>) -> View<G> {
+/// view! { ctx,
+///     ul {
+///         Indexed {
+///             iterable: todos,
+///             view: |ctx, todo| view! { ctx, li { (todo.title) } },
+///             key_hint: |todo| IndexedKey::new(todo.id),
+///         }
+///     }
+/// }
+/// # }
+/// ```
 #[component]
 pub fn Indexed<'a, G: GenericNode, T, F>(
     ctx: ScopeRef<'a>,
@@ -74,8 +327,239 @@ where
     T: Clone + PartialEq,
     F: Fn(BoundedScopeRef<'_, 'a>, T) -> View<G> + 'a,
 {
-    let IndexedProps { iterable, view } = props;
+    let IndexedProps {
+        iterable,
+        view,
+        max,
+        overflow,
+        detect_shifts,
+        key_hint,
+        reverse,
+    } = props;
+
+    let capped = match max {
+        Some(max) => {
+            ctx.create_selector(move || iterable.get().iter().take(max).cloned().collect())
+        }
+        None => iterable,
+    };
+    let mapped = match key_hint {
+        Some(key_hint) => ctx.map_keyed(capped, view, key_hint),
+        None if detect_shifts => ctx.map_indexed_detect_shifts(capped, view),
+        None => ctx.map_indexed(capped, view),
+    };
+    let items = render_items(ctx, mapped, reverse);
+    with_overflow(ctx, items, iterable, max, overflow)
+}
+
+/// Props for [`KeyedWithContext`].
+#[derive(Prop)]
+pub struct KeyedWithContextProps<'a, T, C, F, G: GenericNode, K, Key>
+where
+    F: Fn(BoundedScopeRef<'_, 'a>, T, &'a C) -> View<G> + 'a,
+    K: Fn(&T) -> Key + 'a,
+    Key: Clone + Hash + Eq + fmt::Debug + 'a,
+    T: Clone + PartialEq,
+    C: 'a,
+{
+    iterable: &'a ReadSignal<Vec<T>>,
+    /// The value that is passed by reference to `view` for every element in `iterable`.
+    context: C,
+    /// The map function that renders a [`View`] for each element in `iterable`.
+    view: F,
+    /// The key function that assigns each element in `iterable` an unique key.
+    key: K,
+}
 
-    let mapped = ctx.map_indexed(iterable, view);
+/// Keyed iteration where the view of every element also receives a shared `context`, such as a
+/// signal holding the selected element. This is the same as [`Keyed`] with the context captured by
+/// `view`, but allows the map function to be defined separately from the list. The context lives
+/// as long as the list, so the reference to it can be used in the effects of the item views.
+///
+/// ```
+/// # use sycamore::prelude::*;
+/// fn row<'a, G: Html>(
+///     ctx: BoundedScopeRef<'_, 'a>,
+///     id: u32,
+///     selected: &'a &'a Signal<u32>,
+/// ) -> View<G> {
+///     let is_selected = ctx.create_selector(move || *selected.get() == id);
+///     view! { ctx,
+///         li(class=if *is_selected.get() { "selected" } else { "" }) { (id) }
+///     }
+/// }
+///
+/// # fn view<'a, G: Html>(ctx: ScopeRef<'a>, ids: &'a ReadSignal<Vec<u32>>) -> View<G> {
+/// let selected = ctx.create_signal(0);
+/// view! { ctx,
+///     ul {
+///         KeyedWithContext {
+///             iterable: ids,
+///             context: selected,
+///             view: row,
+///             key: |id| *id,
+///         }
+///     }
+/// }
+/// # }
+/// ```
+#[component]
+pub fn KeyedWithContext<'a, G: GenericNode, T, C, F, K, Key>(
+    ctx: ScopeRef<'a>,
+    props: KeyedWithContextProps<'a, T, C, F, G, K, Key>,
+) -> View<G>
+where
+    F: Fn(BoundedScopeRef<'_, 'a>, T, &'a C) -> View<G> + 'a,
+    K: Fn(&T) -> Key + 'a,
+    Key: Clone + Hash + Eq + fmt::Debug + 'a,
+    T: Clone + PartialEq,
+    C: 'a,
+{
+    let KeyedWithContextProps {
+        iterable,
+        context,
+        view,
+        key,
+    } = props;
+
+    let context = ctx.create_ref(context);
+    let mapped = ctx.map_keyed(iterable, move |ctx, item| view(ctx, item, context), key);
     View::new_dyn(ctx, || View::new_fragment(mapped.get().as_ref().clone()))
 }
+
+/// Props for [`IndexedWithContext`].
+#[derive(Prop)]
+pub struct IndexedWithContextProps<'a, G: GenericNode, T, C, F>
+where
+    F: Fn(BoundedScopeRef<'_, 'a>, T, &'a C) -> View<G> + 'a,
+    C: 'a,
+{
+    iterable: &'a ReadSignal<Vec<T>>,
+    /// The value that is passed by reference to `view` for every element in `iterable`.
+    context: C,
+    /// The map function that renders a [`View`] for each element in `iterable`.
+    view: F,
+}
+
+/// Non keyed iteration where the view of every element also receives a shared `context`. See
+/// [`KeyedWithContext`] and [`Indexed`].
+#[component]
+pub fn IndexedWithContext<'a, G: GenericNode, T, C, F>(
+    ctx: ScopeRef<'a>,
+    props: IndexedWithContextProps<'a, G, T, C, F>,
+) -> View<G>
+where
+    T: Clone + PartialEq,
+    F: Fn(BoundedScopeRef<'_, 'a>, T, &'a C) -> View<G> + 'a,
+    C: 'a,
+{
+    let IndexedWithContextProps {
+        iterable,
+        context,
+        view,
+    } = props;
+
+    let context = ctx.create_ref(context);
+    let mapped = ctx.map_indexed(iterable, move |ctx, item| view(ctx, item, context));
+    View::new_dyn(ctx, || View::new_fragment(mapped.get().as_ref().clone()))
+}
+
+/// Renders the mapped views of a list, in reverse order if `reverse` is `true`.
+fn render_items<'a, G: GenericNode>(
+    ctx: ScopeRef<'a>,
+    mapped: &'a ReadSignal<Vec<View<G>>>,
+    reverse: bool,
+) -> View<G> {
+    View::new_dyn(ctx, move || {
+        let mapped = mapped.get();
+        if reverse {
+            View::new_fragment_from_iter(mapped.iter().rev().cloned())
+        } else {
+            View::new_fragment(mapped.as_ref().clone())
+        }
+    })
+}
+
+/// Calls `on_reach_end` whenever `sentinel` scrolls into view. The sentinel is observed again
+/// every time `iterable` changes so that `on_reach_end` is called again if the sentinel is still
+/// visible after more items were loaded. Does nothing if `sentinel` is not a DOM node or if
+/// `IntersectionObserver` is not supported.
+#[cfg_attr(not(feature = "dom"), allow(unused_variables))]
+fn observe_sentinel<'a, G: GenericNode, T>(
+    ctx: ScopeRef<'a>,
+    sentinel: &G,
+    iterable: &'a ReadSignal<Vec<T>>,
+    on_reach_end: Box<dyn Fn() + 'a>,
+) {
+    #[cfg(feature = "dom")]
+    {
+        use std::any::Any;
+
+        use wasm_bindgen::prelude::*;
+        use wasm_bindgen::JsCast;
+        use web_sys::{Element, IntersectionObserver, IntersectionObserverEntry};
+
+        let node = <dyn Any>::downcast_ref::<DomNode>(sentinel).map(DomNode::inner_element);
+        #[cfg(feature = "experimental-hydrate")]
+        let node = node.or_else(|| {
+            <dyn Any>::downcast_ref::<HydrateNode>(sentinel).map(HydrateNode::inner_element)
+        });
+        let element: Element = match node {
+            Some(node) => node.unchecked_into(),
+            None => return,
+        };
+
+        // SAFETY: extend lifetime because the closure is dropped when the ctx is disposed,
+        // preventing the callback from ever being accessed after its lifetime.
+        let on_reach_end: Box<dyn Fn() + 'static> = unsafe { std::mem::transmute(on_reach_end) };
+        let callback = Closure::wrap(Box::new(move |entries: js_sys::Array| {
+            if entries.iter().any(|entry| {
+                entry
+                    .unchecked_into::<IntersectionObserverEntry>()
+                    .is_intersecting()
+            }) {
+                on_reach_end();
+            }
+        }) as Box<dyn FnMut(js_sys::Array)>);
+        let observer = match IntersectionObserver::new(callback.as_ref().unchecked_ref()) {
+            Ok(observer) => observer,
+            Err(_) => return,
+        };
+
+        ctx.create_effect({
+            let observer = observer.clone();
+            move || {
+                iterable.track();
+                // Observing the sentinel again reports whether it is currently visible.
+                observer.unobserve(&element);
+                observer.observe(&element);
+            }
+        });
+        ctx.on_cleanup(move || {
+            observer.disconnect();
+            drop(callback);
+        });
+    }
+}
+
+/// Appends the `overflow` indicator to `items` if both `max` and `overflow` are set. The indicator
+/// is only rendered while `iterable` has more than `max` elements.
+fn with_overflow<'a, G: GenericNode, T>(
+    ctx: ScopeRef<'a>,
+    items: View<G>,
+    iterable: &'a ReadSignal<Vec<T>>,
+    max: Option<usize>,
+    overflow: Option<Box<dyn Fn(usize) -> View<G> + 'a>>,
+) -> View<G> {
+    match (max, overflow) {
+        (Some(max), Some(overflow)) => {
+            let hidden = ctx.create_selector(move || iterable.get().len().saturating_sub(max));
+            let indicator = View::new_dyn_scoped(ctx, move |_| match *hidden.get() {
+                0 => View::empty(),
+                hidden => overflow(hidden),
+            });
+            View::new_fragment(vec![items, indicator])
+        }
+        _ => items,
+    }
+}
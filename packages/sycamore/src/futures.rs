@@ -8,11 +8,32 @@
 //! `'static`, allowing values in the surrounding scope to be directly referenced from inside the
 //! future without any cloning necessary.
 //!
+//! # Stale-while-revalidate
+//!
+//! [`create_resource_swr`](ScopeFuturesExt::create_resource_swr) fetches data for a key and caches
+//! the result. When a key is visited again, the cached data is shown immediately while it is
+//! refetched in the background.
+//!
+//! # Retrying
+//!
+//! [`create_resource_with_retry`](ScopeFuturesExt::create_resource_with_retry) fetches data with a
+//! fallible fetcher and retries failed fetches with exponential backoff, according to a
+//! [`RetryPolicy`].
+//!
+//! # Async computed values
+//!
+//! [`create_async_computed`](ScopeFuturesExt::create_async_computed) derives a value from other
+//! signals with an async computation, aborting the pending computation whenever the inputs change.
+//!
 //! # Suspense
 //!
 //! To find out more about suspense, read the [docs for the suspense module](crate::suspense).
 
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::future::Future;
+use std::hash::Hash;
+use std::time::Duration;
 
 // Re-export `wasm_bindgen_futures` crate.
 pub use wasm_bindgen_futures::*;
@@ -21,17 +42,313 @@ pub use sycamore_futures::*;
 
 use crate::prelude::*;
 
+/// A cache for [`create_resource_swr`](ScopeFuturesExt::create_resource_swr), mapping keys to
+/// previously fetched data.
+///
+/// By default, every resource uses a cache that is shared by all the resources with the same key
+/// and data types in the same scope. To share a cache between more resources (e.g. across the
+/// whole app) or to configure the TTL, provide a cache as a context in a parent scope:
+///
+/// ```
+/// # use std::time::Duration;
+/// # use sycamore::futures::SwrCache;
+/// # use sycamore::prelude::*;
+/// # create_scope_immediate(|ctx| {
+/// ctx.provide_context(SwrCache::<u32, String>::new(Duration::from_secs(60)));
+/// # });
+/// ```
+pub struct SwrCache<K, T> {
+    entries: RefCell<HashMap<K, SwrCacheEntry<T>>>,
+    ttl: Duration,
+}
+
+struct SwrCacheEntry<T> {
+    value: T,
+    /// The time at which the value was fetched. See [`now`].
+    fetched_at: Duration,
+}
+
+impl<K: Hash + Eq, T: Clone> SwrCache<K, T> {
+    /// Create a new empty cache. Cached data is considered fresh for `ttl` after it was fetched.
+    /// Fresh data is not revalidated. Once the `ttl` has elapsed, the data is still shown but it
+    /// is revalidated.
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            entries: Default::default(),
+            ttl,
+        }
+    }
+
+    /// Returns the cached data for `key` and whether it is still fresh.
+    fn get(&self, key: &K) -> Option<(T, bool)> {
+        self.entries.borrow().get(key).map(|entry| {
+            let fresh = now().saturating_sub(entry.fetched_at) < self.ttl;
+            (entry.value.clone(), fresh)
+        })
+    }
+
+    fn insert(&self, key: K, value: T) {
+        self.entries.borrow_mut().insert(
+            key,
+            SwrCacheEntry {
+                value,
+                fetched_at: now(),
+            },
+        );
+    }
+
+    /// Removes the cached data for `key`. The data is fetched again the next time it is needed.
+    pub fn invalidate(&self, key: &K) {
+        self.entries.borrow_mut().remove(key);
+    }
+}
+
+/// The default [`SwrCache`] has a TTL of zero, meaning that cached data is always revalidated.
+impl<K: Hash + Eq, T: Clone> Default for SwrCache<K, T> {
+    fn default() -> Self {
+        Self::new(Duration::ZERO)
+    }
+}
+
+/// Returns the current time as a duration since the Unix epoch.
+fn now() -> Duration {
+    #[cfg(target_arch = "wasm32")]
+    {
+        Duration::from_secs_f64(js_sys::Date::now() / 1000.0)
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+    }
+}
+
+/// How [`create_resource_with_retry`](ScopeFuturesExt::create_resource_with_retry) retries failed
+/// fetches.
+///
+/// The delay before a retry doubles after every failed attempt: the first retry happens after
+/// `base_delay`, the second one after `2 * base_delay`, and so on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// The maximum number of retries after the first attempt.
+    pub max_retries: u32,
+    /// The delay before the first retry.
+    pub base_delay: Duration,
+}
+
+impl RetryPolicy {
+    /// Returns the delay before the retry with the given index, starting at `0`.
+    fn delay(&self, retry: u32) -> Duration {
+        self.base_delay
+            .saturating_mul(1u32.checked_shl(retry).unwrap_or(u32::MAX))
+    }
+}
+
+/// The default [`RetryPolicy`] retries 3 times, starting after 1 second.
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_secs(1),
+        }
+    }
+}
+
+/// An async resource created with
+/// [`create_resource_with_retry`](ScopeFuturesExt::create_resource_with_retry).
+pub struct RetryResource<'a, T, E> {
+    data: &'a Signal<Option<T>>,
+    error: &'a Signal<Option<E>>,
+    attempts: &'a Signal<u32>,
+    trigger: &'a Signal<()>,
+}
+
+impl<'a, T, E> RetryResource<'a, T, E> {
+    /// The fetched data, or `None` while it is being fetched or if all the attempts failed.
+    pub fn data(&self) -> &'a ReadSignal<Option<T>> {
+        self.data
+    }
+
+    /// The error of the last attempt, once all the attempts failed.
+    pub fn error(&self) -> &'a ReadSignal<Option<E>> {
+        self.error
+    }
+
+    /// The number of attempts that were started to fetch the current data.
+    pub fn attempts(&self) -> &'a ReadSignal<u32> {
+        self.attempts
+    }
+
+    /// Fetch the data again, aborting the current fetch or retry if there is one.
+    pub fn refetch(&self) {
+        self.trigger.set(());
+    }
+
+    /// Creates a signal that derives a value from the [`data`](Self::data) with `f`. The signal is
+    /// `None` while the data is `None`, and is updated every time the data is fetched.
+    ///
+    /// # Example
+    /// ```
+    /// # use sycamore::futures::*;
+    /// # use sycamore::prelude::*;
+    /// # async fn fetch_users(page: u32) -> Result<Vec<String>, String> { todo!() }
+    /// # create_scope_immediate(|ctx| {
+    /// # ctx.provide_executor(ManualExecutor::new());
+    /// let page = ctx.create_signal(1);
+    /// let users = ctx.create_resource_with_retry(page, fetch_users, RetryPolicy::default());
+    /// let count = users.map(ctx, |users| users.len());
+    /// # });
+    /// ```
+    pub fn map<U: 'a>(
+        &self,
+        ctx: ScopeRef<'a>,
+        f: impl Fn(&T) -> U + 'a,
+    ) -> &'a ReadSignal<Option<U>> {
+        let data = self.data;
+        ctx.create_memo(move || Option::as_ref(&data.get()).map(&f))
+    }
+
+    /// Same as [`map`](Self::map) but only notifies its subscribers when the derived value
+    /// changes, e.g. to select a field of the data that does not change when it is refetched.
+    pub fn select<U: PartialEq + 'a>(
+        &self,
+        ctx: ScopeRef<'a>,
+        f: impl Fn(&T) -> U + 'a,
+    ) -> &'a ReadSignal<Option<U>> {
+        let data = self.data;
+        ctx.create_selector(move || Option::as_ref(&data.get()).map(&f))
+    }
+}
+
 /// Extension trait for [`Scope`] adding the [`create_resource`](ScopeFuturesExt::create_resource)
 /// method.
 pub trait ScopeFuturesExt<'a> {
     /// Create a new async resource.
     ///
-    /// TODO: docs + example
+    /// The returned signal is `None` until `f` resolves.
+    ///
+    /// The resource is loaded in a [`suspense_scope`](crate::suspense::suspense_scope), so the
+    /// nearest [`Suspense`](crate::suspense::Suspense) boundary waits for it, including when
+    /// streaming with [`render_to_stream`](crate::generic_node::render_to_stream). Without a
+    /// [`Suspense`](crate::suspense::Suspense) boundary, it is spawned on the scope like with
+    /// [`spawn_local`](ScopeSpawnLocal::spawn_local). In both cases, loading is aborted when the
+    /// scope is disposed.
+    ///
+    /// # Example
+    /// ```
+    /// # #![allow(deprecated)]
+    /// # use sycamore::futures::*;
+    /// # use sycamore::prelude::*;
+    /// # create_scope_immediate(|ctx| {
+    /// # let executor = ManualExecutor::new();
+    /// # ctx.provide_executor(executor.clone());
+    /// let user = ctx.create_resource(async { String::from("Alice") });
+    /// assert_eq!(*user.get(), None);
+    /// # executor.run_until_stalled();
+    /// // Once the future is resolved:
+    /// assert_eq!(user.get().as_deref(), Some("Alice"));
+    /// # });
+    /// ```
     #[deprecated = "use Scope::spawn_local instead"]
     fn create_resource<U, F>(&'a self, f: F) -> RcSignal<Option<U>>
     where
         U: 'static,
         F: Future<Output = U> + 'static;
+
+    /// Create a new async resource with stale-while-revalidate caching.
+    ///
+    /// Every time `source` changes, the data for the new key is fetched with `fetcher`. If there is
+    /// cached data for the key, it is shown immediately and refetched in the background unless it
+    /// is still fresh (see [`SwrCache`]). The returned signal is `None` while data for a key that
+    /// is not cached is being fetched.
+    ///
+    /// If `source` changes before the data is fetched, the fetch is aborted.
+    ///
+    /// # Example
+    /// ```
+    /// # use sycamore::futures::*;
+    /// # use sycamore::prelude::*;
+    /// # async fn fetch_user(id: u32) -> String { todo!() }
+    /// # create_scope_immediate(|ctx| {
+    /// # ctx.provide_executor(ManualExecutor::new());
+    /// let id = ctx.create_signal(1);
+    /// let user = ctx.create_resource_swr(id, fetch_user);
+    /// # });
+    /// ```
+    fn create_resource_swr<K, T, F, Fut>(
+        &'a self,
+        source: &'a ReadSignal<K>,
+        fetcher: F,
+    ) -> &'a ReadSignal<Option<T>>
+    where
+        K: Hash + Eq + Clone + 'static,
+        T: Clone + 'static,
+        F: Fn(K) -> Fut + 'a,
+        Fut: Future<Output = T> + 'a;
+
+    /// Create a new async resource with a fallible `fetcher` whose failed fetches are retried
+    /// according to `policy`.
+    ///
+    /// Every time `source` changes or [`refetch`](RetryResource::refetch) is called, the data is
+    /// fetched again and the current fetch, including any pending retry, is aborted. The delays
+    /// between retries are measured with the timer provided with
+    /// [`provide_timer`](ScopeSpawnLocal::provide_timer).
+    ///
+    /// # Example
+    /// ```
+    /// # use std::time::Duration;
+    /// # use sycamore::futures::*;
+    /// # use sycamore::prelude::*;
+    /// # async fn fetch_user(id: u32) -> Result<String, String> { todo!() }
+    /// # create_scope_immediate(|ctx| {
+    /// # ctx.provide_executor(ManualExecutor::new());
+    /// let id = ctx.create_signal(1);
+    /// let policy = RetryPolicy {
+    ///     max_retries: 5,
+    ///     base_delay: Duration::from_millis(500),
+    /// };
+    /// let user = ctx.create_resource_with_retry(id, fetch_user, policy);
+    /// # });
+    /// ```
+    fn create_resource_with_retry<K, T, E, F, Fut>(
+        &'a self,
+        source: &'a ReadSignal<K>,
+        fetcher: F,
+        policy: RetryPolicy,
+    ) -> &'a RetryResource<'a, T, E>
+    where
+        K: Clone + 'a,
+        T: 'a,
+        E: 'a,
+        F: Fn(K) -> Fut + 'a,
+        Fut: Future<Output = Result<T, E>> + 'a;
+
+    /// Create a signal whose value is computed asynchronously from other signals.
+    ///
+    /// `f` is called in an effect and the signals that it reads are tracked. It should therefore
+    /// read its inputs synchronously and return a future that computes the new value from them:
+    /// the signals that are read inside the future are not tracked. Every time an input changes,
+    /// `f` is called again and the pending computation is aborted, so that the value of the
+    /// returned signal always comes from the latest inputs. The signal keeps its previous value,
+    /// starting with `initial`, until a computation completes.
+    ///
+    /// # Example
+    /// ```
+    /// # use sycamore::futures::*;
+    /// # use sycamore::prelude::*;
+    /// # async fn search(query: String) -> Vec<String> { todo!() }
+    /// # create_scope_immediate(|ctx| {
+    /// # ctx.provide_executor(ManualExecutor::new());
+    /// let query = ctx.create_signal(String::new());
+    /// let results = ctx.create_async_computed(|| search((*query.get()).clone()), Vec::new());
+    /// # });
+    /// ```
+    fn create_async_computed<T, F, Fut>(&'a self, f: F, initial: T) -> &'a ReadSignal<T>
+    where
+        T: 'a,
+        F: Fn() -> Fut + 'a,
+        Fut: Future<Output = T> + 'a;
 }
 
 impl<'a> ScopeFuturesExt<'a> for Scope<'a> {
@@ -42,7 +359,7 @@ impl<'a> ScopeFuturesExt<'a> for Scope<'a> {
     {
         let signal = create_rc_signal(None);
 
-        spawn_local({
+        crate::suspense::suspense_scope(self, {
             let signal = signal.clone();
             async move {
                 signal.set(Some(f.await));
@@ -51,4 +368,391 @@ impl<'a> ScopeFuturesExt<'a> for Scope<'a> {
 
         signal
     }
+
+    fn create_resource_swr<K, T, F, Fut>(
+        &'a self,
+        source: &'a ReadSignal<K>,
+        fetcher: F,
+    ) -> &'a ReadSignal<Option<T>>
+    where
+        K: Hash + Eq + Clone + 'static,
+        T: Clone + 'static,
+        F: Fn(K) -> Fut + 'a,
+        Fut: Future<Output = T> + 'a,
+    {
+        let cache = self.use_context_or_else(SwrCache::<K, T>::default);
+        let fetcher = self.create_ref(fetcher);
+        let data = self.create_signal(None);
+
+        // Use a scoped effect so that the previous fetch is aborted when the key changes.
+        self.create_effect_scoped(move |ctx| {
+            let key = (*source.get()).clone();
+            let fresh = match cache.get(&key) {
+                Some((value, fresh)) => {
+                    data.set(Some(value));
+                    fresh
+                }
+                None => {
+                    data.set(None);
+                    false
+                }
+            };
+            if !fresh {
+                ctx.spawn_local(async move {
+                    let value = fetcher(key.clone()).await;
+                    cache.insert(key, value.clone());
+                    data.set(Some(value));
+                });
+            }
+        });
+
+        data
+    }
+
+    fn create_resource_with_retry<K, T, E, F, Fut>(
+        &'a self,
+        source: &'a ReadSignal<K>,
+        fetcher: F,
+        policy: RetryPolicy,
+    ) -> &'a RetryResource<'a, T, E>
+    where
+        K: Clone + 'a,
+        T: 'a,
+        E: 'a,
+        F: Fn(K) -> Fut + 'a,
+        Fut: Future<Output = Result<T, E>> + 'a,
+    {
+        let fetcher = self.create_ref(fetcher);
+        let resource = self.create_ref(RetryResource {
+            data: self.create_signal(None),
+            error: self.create_signal(None),
+            attempts: self.create_signal(0),
+            trigger: self.create_signal(()),
+        });
+        let RetryResource {
+            data,
+            error,
+            attempts,
+            trigger,
+        } = *resource;
+
+        // Use a scoped effect so that the previous fetch is aborted when the key changes.
+        self.create_effect_scoped(move |ctx| {
+            trigger.track();
+            let key = (*source.get()).clone();
+            data.set(None);
+            error.set(None);
+            attempts.set(0);
+            ctx.spawn_local(async move {
+                for retry in 0.. {
+                    attempts.set(retry + 1);
+                    match fetcher(key.clone()).await {
+                        Ok(value) => {
+                            data.set(Some(value));
+                            return;
+                        }
+                        Err(_) if retry < policy.max_retries => {
+                            ctx.sleep(policy.delay(retry)).await;
+                        }
+                        Err(err) => {
+                            error.set(Some(err));
+                            return;
+                        }
+                    }
+                }
+            });
+        });
+
+        resource
+    }
+
+    fn create_async_computed<T, F, Fut>(&'a self, f: F, initial: T) -> &'a ReadSignal<T>
+    where
+        T: 'a,
+        F: Fn() -> Fut + 'a,
+        Fut: Future<Output = T> + 'a,
+    {
+        let value = self.create_signal(initial);
+
+        // Use a scoped effect so that the pending computation is aborted when an input changes.
+        self.create_effect_scoped(move |ctx| {
+            let computation = f();
+            ctx.spawn_local(async move {
+                value.set(computation.await);
+            });
+        });
+
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use futures::channel::oneshot;
+
+    use super::*;
+
+    /// A fetcher whose requests are completed manually.
+    #[derive(Default)]
+    struct Requests(RefCell<Vec<(&'static str, oneshot::Sender<String>)>>);
+
+    impl Requests {
+        async fn fetch(&self, key: &'static str) -> String {
+            let (sender, receiver) = oneshot::channel();
+            self.0.borrow_mut().push((key, sender));
+            receiver.await.unwrap()
+        }
+
+        fn keys(&self) -> Vec<&'static str> {
+            self.0.borrow().iter().map(|(key, _)| *key).collect()
+        }
+
+        fn complete(&self, executor: &ManualExecutor, value: &str) {
+            for (_, sender) in self.0.take() {
+                let _ = sender.send(value.to_string());
+            }
+            executor.run_until_stalled();
+        }
+    }
+
+    #[test]
+    fn resource_swr_shows_cached_value_while_revalidating() {
+        let executor = ManualExecutor::new();
+        create_scope_immediate(|ctx| {
+            ctx.provide_executor(executor.clone());
+            let key = ctx.create_signal("a");
+            let requests = ctx.create_ref(Requests::default());
+            let data = ctx.create_resource_swr(key, |key| requests.fetch(key));
+            executor.run_until_stalled();
+            assert_eq!(*data.get(), None);
+            requests.complete(&executor, "a1");
+            assert_eq!(*data.get(), Some("a1".to_string()));
+
+            key.set("b");
+            executor.run_until_stalled();
+            assert_eq!(*data.get(), None, "nothing cached for b yet");
+            requests.complete(&executor, "b1");
+            assert_eq!(*data.get(), Some("b1".to_string()));
+
+            key.set("a");
+            assert_eq!(
+                *data.get(),
+                Some("a1".to_string()),
+                "cached value should be shown immediately"
+            );
+            executor.run_until_stalled();
+            assert_eq!(requests.keys(), ["a"], "stale value should be revalidated");
+            requests.complete(&executor, "a2");
+            assert_eq!(*data.get(), Some("a2".to_string()));
+
+            key.set("b");
+            assert_eq!(*data.get(), Some("b1".to_string()));
+        });
+    }
+
+    #[test]
+    fn resource_swr_does_not_revalidate_fresh_value() {
+        let executor = ManualExecutor::new();
+        create_scope_immediate(|ctx| {
+            ctx.provide_executor(executor.clone());
+            ctx.provide_context(SwrCache::<&'static str, String>::new(Duration::from_secs(
+                3600,
+            )));
+            let key = ctx.create_signal("a");
+            let requests = ctx.create_ref(Requests::default());
+            let data = ctx.create_resource_swr(key, |key| requests.fetch(key));
+            executor.run_until_stalled();
+            requests.complete(&executor, "a1");
+
+            key.set("b");
+            executor.run_until_stalled();
+            requests.complete(&executor, "b1");
+
+            key.set("a");
+            executor.run_until_stalled();
+            assert_eq!(*data.get(), Some("a1".to_string()));
+            assert!(
+                requests.keys().is_empty(),
+                "fresh value should not be refetched"
+            );
+        });
+    }
+
+    #[test]
+    fn resource_with_retry_map_and_select() {
+        let executor = ManualExecutor::new();
+        create_scope_immediate(|ctx| {
+            ctx.provide_executor(executor.clone());
+            let key = ctx.create_signal("a");
+            let requests = ctx.create_ref(Requests::default());
+            let resource = ctx.create_resource_with_retry(
+                key,
+                move |key| async move { Ok::<_, ()>(requests.fetch(key).await) },
+                RetryPolicy::default(),
+            );
+            let len = resource.map(ctx, |data| data.len());
+            let first = resource.select(ctx, |data| data.chars().next());
+            let first_updates = ctx.create_ref(Cell::new(0));
+            ctx.create_effect(|| {
+                first.track();
+                first_updates.set(first_updates.get() + 1);
+            });
+            executor.run_until_stalled();
+            assert_eq!(*len.get(), None);
+            assert_eq!(*first.get(), None);
+
+            requests.complete(&executor, "abc");
+            assert_eq!(*len.get(), Some(3));
+            assert_eq!(*first.get(), Some(Some('a')));
+            assert_eq!(first_updates.get(), 2);
+
+            resource.refetch();
+            assert_eq!(*len.get(), None, "loading again");
+            executor.run_until_stalled();
+            requests.complete(&executor, "abcd");
+            assert_eq!(*len.get(), Some(4));
+            assert_eq!(*first.get(), Some(Some('a')));
+            assert_eq!(
+                first_updates.get(),
+                4,
+                "only notified when the selection changes"
+            );
+        });
+    }
+
+    #[test]
+    fn async_computed_ignores_stale_results() {
+        let executor = ManualExecutor::new();
+        create_scope_immediate(|ctx| {
+            ctx.provide_executor(executor.clone());
+            let key = ctx.create_signal("a");
+            let requests = ctx.create_ref(Requests::default());
+            let value = ctx.create_async_computed(|| requests.fetch(*key.get()), String::new());
+            executor.run_until_stalled();
+            assert_eq!(requests.keys(), ["a"]);
+
+            key.set("b");
+            executor.run_until_stalled();
+            assert_eq!(requests.keys(), ["a", "b"]);
+            assert_eq!(*value.get(), "", "initial value is kept while computing");
+
+            let mut pending = requests.0.take().into_iter();
+            let (_, a) = pending.next().unwrap();
+            let (_, b) = pending.next().unwrap();
+            // The computation for "a" was aborted when the key changed.
+            assert!(a.send("a".to_string()).is_err());
+            executor.run_until_stalled();
+            assert_eq!(*value.get(), "");
+
+            b.send("b".to_string()).unwrap();
+            executor.run_until_stalled();
+            assert_eq!(*value.get(), "b");
+
+            key.set("c");
+            executor.run_until_stalled();
+            assert_eq!(*value.get(), "b", "previous value is kept while computing");
+            requests.complete(&executor, "c");
+            assert_eq!(*value.get(), "c");
+        });
+    }
+
+    #[test]
+    fn resource_with_retry_backs_off() {
+        let executor = ManualExecutor::new();
+        let timer = ManualTimer::new();
+        create_scope_immediate(|ctx| {
+            ctx.provide_executor(executor.clone());
+            ctx.provide_timer(timer.clone());
+            let key = ctx.create_signal("a");
+            let calls = ctx.create_ref(Cell::new(0));
+            let policy = RetryPolicy {
+                max_retries: 3,
+                base_delay: Duration::from_millis(100),
+            };
+            // Fails twice, then succeeds.
+            let resource = ctx.create_resource_with_retry(
+                key,
+                |key| {
+                    calls.set(calls.get() + 1);
+                    let call = calls.get();
+                    async move {
+                        if call <= 2 {
+                            Err(format!("error {call}"))
+                        } else {
+                            Ok(key.to_string())
+                        }
+                    }
+                },
+                policy,
+            );
+            executor.run_until_stalled();
+            assert_eq!(*resource.attempts().get(), 1);
+            assert_eq!(timer.requested(), [Duration::from_millis(100)]);
+
+            timer.advance(Duration::from_millis(99));
+            executor.run_until_stalled();
+            assert_eq!(
+                *resource.attempts().get(),
+                1,
+                "should wait for the full delay"
+            );
+
+            timer.advance(Duration::from_millis(1));
+            executor.run_until_stalled();
+            assert_eq!(*resource.attempts().get(), 2);
+            assert_eq!(
+                timer.requested(),
+                [Duration::from_millis(100), Duration::from_millis(200)]
+            );
+
+            timer.advance(Duration::from_millis(200));
+            executor.run_until_stalled();
+            assert_eq!(*resource.attempts().get(), 3);
+            assert_eq!(*resource.data().get(), Some("a".to_string()));
+            assert_eq!(*resource.error().get(), None);
+        });
+    }
+
+    #[test]
+    fn resource_with_retry_gives_up_and_cancels() {
+        let executor = ManualExecutor::new();
+        let timer = ManualTimer::new();
+        create_scope_immediate(|ctx| {
+            ctx.provide_executor(executor.clone());
+            ctx.provide_timer(timer.clone());
+            let key = ctx.create_signal("a");
+            let calls = ctx.create_ref(RefCell::new(Vec::new()));
+            let policy = RetryPolicy {
+                max_retries: 1,
+                base_delay: Duration::from_millis(100),
+            };
+            let resource = ctx.create_resource_with_retry(
+                key,
+                |key| {
+                    calls.borrow_mut().push(key);
+                    async move { Err::<(), _>(format!("{key} failed")) }
+                },
+                policy,
+            );
+            executor.run_until_stalled();
+
+            // Changing the key aborts the pending retry.
+            key.set("b");
+            executor.run_until_stalled();
+            timer.advance(Duration::from_millis(100));
+            executor.run_until_stalled();
+            assert_eq!(*calls.borrow(), ["a", "b", "b"]);
+            assert_eq!(*resource.attempts().get(), 2);
+            assert_eq!(*resource.error().get(), Some("b failed".to_string()));
+            assert_eq!(*resource.data().get(), None);
+
+            // Refetching starts over.
+            resource.refetch();
+            executor.run_until_stalled();
+            assert_eq!(*resource.attempts().get(), 1);
+            assert_eq!(*resource.error().get(), None);
+        });
+    }
 }
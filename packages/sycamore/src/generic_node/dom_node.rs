@@ -2,6 +2,7 @@
 
 #![allow(clippy::unused_unit)] // TODO: wasm-bindgen bug
 
+use std::borrow::Cow;
 use std::cell::Cell;
 use std::fmt;
 use std::hash::{Hash, Hasher};
@@ -25,6 +26,12 @@ extern "C" {
 
     #[wasm_bindgen(method, setter, js_name = "$$$nodeId")]
     pub fn set_node_id(this: &NodeWithId, id: usize);
+
+    #[wasm_bindgen(method, getter, js_name = "$$$replacement")]
+    pub fn replacement(this: &NodeWithId) -> Option<Node>;
+
+    #[wasm_bindgen(method, setter, js_name = "$$$replacement")]
+    pub fn set_replacement(this: &NodeWithId, node: &Node);
 }
 
 #[wasm_bindgen]
@@ -36,6 +43,12 @@ extern "C" {
     pub fn create_text_node_int(this: &DocumentCreateTextNodeInt, num: i32) -> web_sys::Text;
 }
 
+thread_local! {
+    /// Whether a node was ever replaced with [`View::replace_with`]. This avoids looking up the
+    /// replacement of every node when the method is not used.
+    static HAS_REPLACEMENTS: Cell<bool> = Default::default();
+}
+
 /// An unique id for every node.
 #[derive(Default, Clone, Copy, PartialEq, Eq, Hash)]
 pub(super) struct NodeId(pub usize);
@@ -66,16 +79,40 @@ pub struct DomNode {
 impl DomNode {
     /// Get the underlying [`web_sys::Node`].
     pub fn inner_element(&self) -> Node {
-        self.node.clone()
+        self.node().into_owned()
     }
 
     /// Cast the underlying [`web_sys::Node`] using [`JsCast`].
     pub fn unchecked_into<T: JsCast>(self) -> T {
-        self.node.unchecked_into()
+        self.node().into_owned().unchecked_into()
+    }
+
+    /// Returns the node that this node was replaced with by [`View::replace_with`], if any.
+    fn replacement(node: &Node) -> Option<Node> {
+        if !HAS_REPLACEMENTS.with(Cell::get) {
+            return None;
+        }
+        let mut replacement = node.unchecked_ref::<NodeWithId>().replacement()?;
+        while let Some(next) = replacement.unchecked_ref::<NodeWithId>().replacement() {
+            replacement = next;
+        }
+        Some(replacement)
+    }
+
+    /// Returns the node that is currently in the DOM in place of this node. This is the
+    /// underlying node unless it was replaced with [`View::replace_with`].
+    fn node(&self) -> Cow<'_, Node> {
+        match Self::replacement(&self.node) {
+            Some(replacement) => Cow::Owned(replacement),
+            None => Cow::Borrowed(&self.node),
+        }
     }
 
     /// Get the [`NodeId`] for the node.
     pub(super) fn get_node_id(&self) -> NodeId {
+        if let Some(replacement) = Self::replacement(&self.node) {
+            return Self::from_web_sys(replacement).get_node_id();
+        }
         if self.id.get().0 == 0 {
             // self.id not yet initialized.
             if let Some(id) = self.node.unchecked_ref::<NodeWithId>().node_id() {
@@ -98,7 +135,7 @@ impl DomNode {
 
 impl PartialEq for DomNode {
     fn eq(&self, other: &Self) -> bool {
-        self.node == other.node
+        self.node() == other.node()
     }
 }
 
@@ -118,21 +155,22 @@ impl AsRef<JsValue> for DomNode {
 
 impl From<DomNode> for JsValue {
     fn from(node: DomNode) -> Self {
-        (*node.node).clone().into()
+        node.node().into_owned().into()
     }
 }
 
 impl fmt::Debug for DomNode {
     /// Prints outerHtml of [`Node`].
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let outer_html = if let Some(element) = self.node.dyn_ref::<Element>() {
+        let node = self.node();
+        let outer_html = if let Some(element) = node.dyn_ref::<Element>() {
             element.outer_html()
-        } else if let Some(text) = self.node.dyn_ref::<Text>() {
+        } else if let Some(text) = node.dyn_ref::<Text>() {
             text.text_content().unwrap_or_default()
-        } else if let Some(comment) = self.node.dyn_ref::<Comment>() {
+        } else if let Some(comment) = node.dyn_ref::<Comment>() {
             format!("<!--{}-->", comment.text_content().unwrap_or_default())
         } else {
-            self.node.to_string().as_string().unwrap_throw()
+            node.to_string().as_string().unwrap_throw()
         };
         f.debug_tuple("DomNode").field(&outer_html).finish()
     }
@@ -189,25 +227,25 @@ impl GenericNode for DomNode {
     }
 
     fn set_attribute(&self, name: &str, value: &str) {
-        self.node
+        self.node()
             .unchecked_ref::<Element>()
             .set_attribute(intern(name), value)
             .unwrap_throw();
     }
 
     fn remove_attribute(&self, name: &str) {
-        self.node
+        self.node()
             .unchecked_ref::<Element>()
             .remove_attribute(intern(name))
             .unwrap_throw();
     }
 
     fn set_class_name(&self, value: &str) {
-        self.node.unchecked_ref::<Element>().set_class_name(value);
+        self.node().unchecked_ref::<Element>().set_class_name(value);
     }
 
     fn add_class(&self, class: &str) {
-        self.node
+        self.node()
             .unchecked_ref::<Element>()
             .class_list()
             .add_1(class)
@@ -215,7 +253,7 @@ impl GenericNode for DomNode {
     }
 
     fn remove_class(&self, class: &str) {
-        self.node
+        self.node()
             .unchecked_ref::<Element>()
             .class_list()
             .remove_1(class)
@@ -223,70 +261,83 @@ impl GenericNode for DomNode {
     }
 
     fn set_property(&self, name: &str, value: &JsValue) {
-        assert!(js_sys::Reflect::set(&self.node, &name.into(), value).unwrap_throw());
+        assert!(js_sys::Reflect::set(&self.node(), &name.into(), value).unwrap_throw());
     }
 
     fn remove_property(&self, name: &str) {
-        assert!(js_sys::Reflect::delete_property(&self.node, &name.into()).unwrap_throw());
+        assert!(js_sys::Reflect::delete_property(&self.node(), &name.into()).unwrap_throw());
     }
 
     fn append_child(&self, child: &Self) {
-        self.node.append_child(&child.node).unwrap_throw();
+        self.node().append_child(&child.node()).unwrap_throw();
     }
 
     fn first_child(&self) -> Option<Self> {
-        self.node.first_child().map(|node| Self {
+        self.node().first_child().map(|node| Self {
             id: Default::default(),
             node,
         })
     }
 
     fn insert_child_before(&self, new_node: &Self, reference_node: Option<&Self>) {
-        self.node
-            .insert_before(&new_node.node, reference_node.map(|n| n.node.as_ref()))
+        let reference_node = reference_node.map(|n| n.node());
+        self.node()
+            .insert_before(&new_node.node(), reference_node.as_deref())
             .unwrap_throw();
     }
 
     fn remove_child(&self, child: &Self) {
-        self.node.remove_child(&child.node).unwrap_throw();
+        self.node().remove_child(&child.node()).unwrap_throw();
     }
 
     fn replace_child(&self, old: &Self, new: &Self) {
-        self.node.replace_child(&new.node, &old.node).unwrap_throw();
+        self.node()
+            .replace_child(&new.node(), &old.node())
+            .unwrap_throw();
     }
 
     fn insert_sibling_before(&self, child: &Self) {
-        self.node
+        self.node()
             .unchecked_ref::<Element>()
-            .before_with_node_1(&child.node)
+            .before_with_node_1(&child.node())
             .unwrap_throw();
     }
 
     fn parent_node(&self) -> Option<Self> {
-        self.node.parent_node().map(|node| Self {
+        self.node().parent_node().map(|node| Self {
             id: Default::default(),
             node,
         })
     }
 
     fn next_sibling(&self) -> Option<Self> {
-        self.node.next_sibling().map(|node| Self {
+        self.node().next_sibling().map(|node| Self {
             id: Default::default(),
             node,
         })
     }
 
     fn remove_self(&self) {
-        self.node.unchecked_ref::<Element>().remove();
+        self.node().unchecked_ref::<Element>().remove();
     }
 
     fn event<'a>(&self, ctx: ScopeRef<'a>, name: &str, handler: Box<dyn Fn(Self::EventType) + 'a>) {
+        // The listeners of a node that was replaced with `View::replace_with` are disabled.
+        let handler: Box<dyn Fn(Self::EventType) + 'a> = Box::new(move |event: web_sys::Event| {
+            let replaced = matches!(
+                event.current_target(),
+                Some(target) if Self::replacement(target.unchecked_ref()).is_some()
+            );
+            if !replaced {
+                handler(event);
+            }
+        });
         // SAFETY: extend lifetime because the closure is dropped when the ctx is disposed,
         // preventing the handler from ever being accessed after its lifetime.
         let handler: Box<dyn Fn(Self::EventType) + 'static> =
             unsafe { std::mem::transmute(handler) };
         let closure = Closure::wrap(handler);
-        self.node
+        self.node()
             .add_event_listener_with_callback(intern(name), closure.as_ref().unchecked_ref())
             .unwrap_throw();
 
@@ -296,25 +347,113 @@ impl GenericNode for DomNode {
     }
 
     fn update_inner_text(&self, text: &str) {
-        self.node.set_text_content(Some(text));
+        self.node().set_text_content(Some(text));
     }
 
     fn dangerously_set_inner_html(&self, html: &str) {
-        self.node.unchecked_ref::<Element>().set_inner_html(html);
+        self.node().unchecked_ref::<Element>().set_inner_html(html);
     }
 
     fn clone_node(&self) -> Self {
         Self {
-            node: self.node.clone_node_with_deep(true).unwrap_throw(),
+            node: self.node().clone_node_with_deep(true).unwrap_throw(),
             id: Default::default(),
         }
     }
+
+    fn patch(&self, other: &Self) -> bool {
+        let (this, other) = (self.node(), other.node());
+        if this == other {
+            return true;
+        }
+        if this.node_type() != other.node_type() {
+            return false;
+        }
+        match this.node_type() {
+            Node::TEXT_NODE | Node::COMMENT_NODE => {
+                let value = other.node_value();
+                if this.node_value() != value {
+                    this.set_node_value(value.as_deref());
+                }
+                true
+            }
+            Node::ELEMENT_NODE => {
+                let old = this.unchecked_ref::<Element>();
+                let new = other.unchecked_ref::<Element>();
+                if old.tag_name() != new.tag_name() {
+                    return false;
+                }
+
+                let old_children = this.child_nodes();
+                let new_children = other.child_nodes();
+                if old_children.length() != new_children.length() {
+                    return false;
+                }
+                for i in 0..old_children.length() {
+                    let old_child = Self::from_web_sys(old_children.get(i).unwrap_throw());
+                    let new_child = Self::from_web_sys(new_children.get(i).unwrap_throw());
+                    if !old_child.patch(&new_child) {
+                        return false;
+                    }
+                }
+
+                for name in old.get_attribute_names().iter() {
+                    let name = name.as_string().unwrap_throw();
+                    if !new.has_attribute(&name) {
+                        old.remove_attribute(&name).unwrap_throw();
+                    }
+                }
+                for name in new.get_attribute_names().iter() {
+                    let name = name.as_string().unwrap_throw();
+                    let value = new.get_attribute(&name);
+                    if old.get_attribute(&name) != value {
+                        old.set_attribute(&name, &value.unwrap_or_default())
+                            .unwrap_throw();
+                    }
+                }
+                true
+            }
+            _ => false,
+        }
+    }
 }
 
 impl Html for DomNode {
     const IS_BROWSER: bool = true;
 }
 
+impl View<DomNode> {
+    /// Replace the node of this view in the DOM with `node`, for instance a node that was created
+    /// by a third-party widget.
+    ///
+    /// The children of the old node are moved into `node`. Every reference to the old node,
+    /// including the ones held by this view and its clones, targets `node` afterwards: dynamic
+    /// updates of the view, and re-renders of a parent dynamic view, apply to `node`. The event
+    /// listeners that were added to the old node are disabled.
+    ///
+    /// # Panics
+    /// Panics if the view does not consist of exactly one node.
+    ///
+    /// _This API requires the following crate features to be activated: `dom`_
+    pub fn replace_with(&self, node: DomNode) {
+        let old = match self.clone().flatten().as_slice() {
+            [old] => old.node().into_owned(),
+            _ => panic!("`View::replace_with` can only be used on a view with exactly one node"),
+        };
+        let node = node.node().into_owned();
+
+        while let Some(child) = old.first_child() {
+            node.append_child(&child).unwrap_throw();
+        }
+        if let Some(parent) = old.parent_node() {
+            parent.replace_child(&node, &old).unwrap_throw();
+        }
+
+        old.unchecked_ref::<NodeWithId>().set_replacement(&node);
+        HAS_REPLACEMENTS.with(|x| x.set(true));
+    }
+}
+
 /// Render a [`View`] into the DOM.
 /// Alias for [`render_to`] with `parent` being the `<body>` tag.
 ///
@@ -361,3 +500,21 @@ pub fn render_get_scope<'a>(
         );
     })
 }
+
+/// Returns an [`EffectScheduler`] that runs effects in a microtask, using `queueMicrotask`. Use it
+/// with [`set_effect_scheduler`] to coalesce all the signal updates made in the same synchronous
+/// turn (e.g. in an event handler) into a single run of the effects.
+///
+/// ```no_run
+/// # use sycamore::prelude::*;
+/// set_effect_scheduler(Some(sycamore::microtask_scheduler()));
+/// ```
+///
+/// _This API requires the following crate features to be activated: `dom`_
+pub fn microtask_scheduler() -> EffectScheduler {
+    std::rc::Rc::new(|f| {
+        web_sys::window()
+            .unwrap_throw()
+            .queue_microtask(Closure::once_into_js(f).unchecked_ref());
+    })
+}
@@ -10,7 +10,10 @@ use web_sys::Node;
 use crate::generic_node::{DomNode, GenericNode, Html};
 use crate::reactive::*;
 use crate::utils::hydrate::web::get_next_element;
-use crate::utils::hydrate::{hydration_completed, with_hydration_context};
+use crate::utils::hydrate::{
+    client_rendering, get_current_id, hydration_completed, set_client_rendering, set_next_id,
+    with_hydration_context,
+};
 use crate::utils::render::insert;
 use crate::view::View;
 
@@ -22,6 +25,10 @@ use super::dom_node::NodeId;
 #[derive(Clone)]
 pub struct HydrateNode {
     node: DomNode,
+    /// `true` if the node was created on the client instead of being hydrated, e.g. because the
+    /// server markup did not match. The children of such a node are appended to it even during
+    /// hydration.
+    client_rendered: bool,
 }
 
 impl HydrateNode {
@@ -42,8 +49,22 @@ impl HydrateNode {
 
     /// Create a new [`DomNode`] from a raw [`web_sys::Node`].
     pub fn from_web_sys(node: Node) -> Self {
+        Self::hydrated(DomNode::from_web_sys(node))
+    }
+
+    /// Wraps a [`DomNode`] that was not created on the client for this view.
+    fn hydrated(node: DomNode) -> Self {
+        Self {
+            node,
+            client_rendered: false,
+        }
+    }
+
+    /// Wraps a [`DomNode`] that was created on the client for this view.
+    fn client_rendered(node: DomNode) -> Self {
         Self {
-            node: DomNode::from_web_sys(node),
+            node,
+            client_rendered: true,
         }
     }
 }
@@ -88,15 +109,41 @@ impl GenericNode for HydrateNode {
 
     /// When hydrating, instead of creating a new node, this will attempt to hydrate an existing
     /// node.
+    ///
+    /// If the existing node is not a `tag` element, the server markup does not match the view. The
+    /// existing node and its children are then replaced by a new element, whose children are
+    /// rendered on the client instead of being hydrated. The hydration keys of the replaced
+    /// children are skipped so that the rest of the view is still hydrated.
     fn element(tag: &str) -> Self {
-        let el = get_next_element();
-        if let Some(el) = el {
-            Self {
-                node: DomNode::from_web_sys(el.into()),
+        if client_rendering() {
+            return Self::client_rendered(DomNode::element(tag));
+        }
+        match get_next_element() {
+            Some(el) if el.tag_name().eq_ignore_ascii_case(tag) => {
+                Self::hydrated(DomNode::from_web_sys(el.into()))
+            }
+            Some(el) => {
+                web_sys::console::warn_1(
+                    &format!(
+                        "hydration mismatch: expected a <{}> element but found <{}>, rendering it \
+                         on the client instead",
+                        tag,
+                        el.tag_name().to_lowercase()
+                    )
+                    .into(),
+                );
+                if let Some(id) = last_descendant_id(&el) {
+                    set_next_id(id + 1);
+                }
+                let node = DomNode::element(tag);
+                el.replace_with_with_node_1(&node.inner_element())
+                    .unwrap_throw();
+                set_client_rendering(true);
+                Self::client_rendered(node)
             }
-        } else {
-            Self {
-                node: DomNode::element(tag),
+            None => {
+                set_client_rendering(true);
+                Self::client_rendered(DomNode::element(tag))
             }
         }
     }
@@ -105,23 +152,17 @@ impl GenericNode for HydrateNode {
     /// node.
     fn text_node(text: &str) -> Self {
         // TODO
-        Self {
-            node: DomNode::text_node(text),
-        }
+        Self::client_rendered(DomNode::text_node(text))
     }
 
     fn marker() -> Self {
         // TODO
-        Self {
-            node: DomNode::marker(),
-        }
+        Self::client_rendered(DomNode::marker())
     }
 
     fn marker_with_text(text: &str) -> Self {
         // TODO
-        Self {
-            node: DomNode::marker_with_text(text),
-        }
+        Self::client_rendered(DomNode::marker_with_text(text))
     }
 
     #[inline]
@@ -161,15 +202,20 @@ impl GenericNode for HydrateNode {
 
     #[inline]
     fn append_child(&self, child: &Self) {
-        if hydration_completed() {
-            // Do not append nodes during hydration as that will result in duplicate text nodes.
+        // Do not append nodes to hydrated nodes during hydration as that will result in duplicate
+        // text nodes.
+        if self.client_rendered || hydration_completed() {
             self.node.append_child(&child.node);
+        } else if child.client_rendered {
+            // All the children of a client rendered element are appended to it before the element
+            // itself is appended, so the client rendered subtree is complete.
+            set_client_rendering(false);
         }
     }
 
     #[inline]
     fn first_child(&self) -> Option<Self> {
-        self.node.first_child().map(|node| Self { node })
+        self.node.first_child().map(Self::hydrated)
     }
 
     #[inline]
@@ -195,12 +241,12 @@ impl GenericNode for HydrateNode {
 
     #[inline]
     fn parent_node(&self) -> Option<Self> {
-        self.node.parent_node().map(|node| Self { node })
+        self.node.parent_node().map(Self::hydrated)
     }
 
     #[inline]
     fn next_sibling(&self) -> Option<Self> {
-        self.node.next_sibling().map(|node| Self { node })
+        self.node.next_sibling().map(Self::hydrated)
     }
 
     #[inline]
@@ -225,10 +271,31 @@ impl GenericNode for HydrateNode {
 
     #[inline]
     fn clone_node(&self) -> Self {
-        Self {
-            node: self.node.clone_node(),
-        }
+        Self::client_rendered(self.node.clone_node())
     }
+
+    #[inline]
+    fn patch(&self, other: &Self) -> bool {
+        self.node.patch(&other.node)
+    }
+}
+
+/// Returns the last hydration key of the current component that is used by a descendant of the
+/// server rendered element `el`.
+fn last_descendant_id(el: &web_sys::Element) -> Option<usize> {
+    let (component_id, _) = get_current_id()?;
+    let prefix = format!("{}.", component_id);
+    let descendants = el
+        .query_selector_all(&format!("[data-hk^=\"{}\"]", prefix))
+        .unwrap_throw();
+    (0..descendants.length())
+        .filter_map(|i| descendants.get(i))
+        .filter_map(|node| {
+            node.unchecked_into::<web_sys::Element>()
+                .get_attribute("data-hk")
+        })
+        .filter_map(|hk| hk.strip_prefix(&prefix)?.parse().ok())
+        .max()
 }
 
 impl Html for HydrateNode {
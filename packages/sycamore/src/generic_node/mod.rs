@@ -154,6 +154,20 @@ pub trait GenericNode: fmt::Debug + Clone + PartialEq + Eq + Hash + 'static {
     /// Create a deep clone of the node.
     #[must_use = "clone_node returns a new node"]
     fn clone_node(&self) -> Self;
+
+    /// Update this node in place so that it matches `other`, a freshly created node with the same
+    /// structure. Only text content and attributes are patched. Event handlers, properties and
+    /// reactive bindings attached to `other` are **not** transferred.
+    ///
+    /// Returns `true` if the node was patched, in which case `other` can be discarded. Returns
+    /// `false` if the nodes are not structurally equal (e.g. different tag names or a different
+    /// number of children). In that case, this node might have been partially modified and should
+    /// be replaced with `other`.
+    ///
+    /// The default implementation never patches and always returns `false`.
+    fn patch(&self, _other: &Self) -> bool {
+        false
+    }
 }
 
 /// Trait that is implemented by all [`GenericNode`] backends that render to HTML.
@@ -10,6 +10,7 @@ use indexmap::map::IndexMap;
 use once_cell::sync::Lazy;
 use wasm_bindgen::prelude::*;
 
+use crate::dom_effect::provide_ssr_marker;
 use crate::generic_node::{GenericNode, Html};
 use crate::reactive::*;
 use crate::utils::hydrate::{get_next_id, with_hydration_context};
@@ -224,6 +225,17 @@ impl GenericNode for SsrNode {
     }
 
     fn insert_child_before(&self, new_node: &Self, reference_node: Option<&Self>) {
+        // Like in the DOM, inserting a node before itself inserts it before its next sibling
+        // instead, i.e. it stays in place.
+        let next_sibling;
+        let reference_node = match reference_node {
+            Some(reference) if reference == new_node => {
+                next_sibling = new_node.next_sibling();
+                next_sibling.as_ref()
+            }
+            _ => reference_node,
+        };
+
         new_node.set_parent(Rc::downgrade(&self.0));
 
         match reference_node {
@@ -346,6 +358,47 @@ impl GenericNode for SsrNode {
         };
         Self(Rc::new(inner))
     }
+
+    fn patch(&self, other: &Self) -> bool {
+        if self == other {
+            return true;
+        }
+        match (self.0.ty.as_ref(), other.0.ty.as_ref()) {
+            (SsrNodeType::Element(old), SsrNodeType::Element(new)) => {
+                let new = new.borrow();
+                // Clone the children to not hold a borrow while recursing.
+                let old_children = {
+                    let old = old.borrow();
+                    if old.name != new.name || old.children.len() != new.children.len() {
+                        return false;
+                    }
+                    old.children.clone()
+                };
+                if !old_children
+                    .iter()
+                    .zip(&new.children)
+                    .all(|(old_child, new_child)| old_child.patch(new_child))
+                {
+                    return false;
+                }
+                old.borrow_mut().attributes = new.attributes.clone();
+                true
+            }
+            (SsrNodeType::Comment(old), SsrNodeType::Comment(new)) => {
+                old.borrow_mut().0 = new.borrow().0.clone();
+                true
+            }
+            (SsrNodeType::Text(old), SsrNodeType::Text(new)) => {
+                old.borrow_mut().0 = new.borrow().0.clone();
+                true
+            }
+            (SsrNodeType::RawText(old), SsrNodeType::RawText(new)) => {
+                old.borrow_mut().0 = new.borrow().0.clone();
+                true
+            }
+            _ => false,
+        }
+    }
 }
 
 impl Html for SsrNode {
@@ -448,6 +501,7 @@ impl WriteToString for RawText {
 pub fn render_to_string(view: impl FnOnce(ScopeRef<'_>) -> View<SsrNode>) -> String {
     let mut ret = String::new();
     create_scope_immediate(|ctx| {
+        provide_ssr_marker(ctx);
         let v = with_hydration_context(|| view(ctx));
 
         for node in v.flatten() {
@@ -477,6 +531,7 @@ pub async fn render_to_string_await_suspense(
     let disposer = create_scope({
         let v = Rc::clone(&v);
         move |ctx| {
+            provide_ssr_marker(ctx);
             ctx.spawn_local(async move {
                 *v.borrow_mut() = Some(
                     crate::suspense::await_suspense(ctx, async {
@@ -504,8 +559,148 @@ pub async fn render_to_string_await_suspense(
     ret
 }
 
+/// The script that moves the streamed content of a suspense boundary in place of its fallback.
+/// It is called with the id of the boundary.
+#[cfg(feature = "suspense")]
+const SWAP_SUSPENSE_SCRIPT: &str = "(function(i){\
+var t=document.getElementById('sycamore-suspense-'+i),w=document.createTreeWalker(document,128),s,n;\
+while(n=w.nextNode())if(n.data=='suspense-start:'+i){s=n;break}\
+if(s){while((n=s.nextSibling)&&n.data!='suspense-end:'+i)n.remove();s.parentNode.insertBefore(t.content,n)}\
+t.remove()})";
+
+/// State shared between [`render_to_stream`] and the [`Suspense`](crate::suspense::Suspense)
+/// boundaries that it renders.
+#[cfg(feature = "suspense")]
+pub(crate) struct SsrStreamState {
+    /// `None` once the stream is closed.
+    sender: RefCell<Option<futures::channel::mpsc::UnboundedSender<String>>>,
+    next_id: std::cell::Cell<u32>,
+    /// The number of boundaries that are not resolved yet.
+    pending: std::cell::Cell<u32>,
+    shell_sent: std::cell::Cell<bool>,
+}
+
+#[cfg(feature = "suspense")]
+impl SsrStreamState {
+    /// Registers a suspense boundary whose content is streamed once it is resolved. Returns the id
+    /// of the boundary.
+    pub(crate) fn register(&self) -> u32 {
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+        self.pending.set(self.pending.get() + 1);
+        id
+    }
+
+    /// Streams the resolved content of the suspense boundary `id`.
+    pub(crate) fn resolve(&self, id: u32, view: View<SsrNode>) {
+        let mut html = String::new();
+        for node in view.flatten() {
+            node.write_to_string(&mut html);
+        }
+        self.send(format!(
+            "<template id=\"sycamore-suspense-{id}\">{html}</template>\
+             <script>{SWAP_SUSPENSE_SCRIPT}({id})</script>"
+        ));
+        self.pending.set(self.pending.get() - 1);
+        self.close_if_done();
+    }
+
+    fn send(&self, chunk: String) {
+        if let Some(sender) = &*self.sender.borrow() {
+            // The stream might have been dropped already, in which case nobody cares.
+            let _ = sender.unbounded_send(chunk);
+        }
+    }
+
+    /// Closes the stream once everything has been sent.
+    fn close_if_done(&self) {
+        if self.shell_sent.get() && self.pending.get() == 0 {
+            self.sender.borrow_mut().take();
+        }
+    }
+}
+
+/// A stream of HTML chunks returned by [`render_to_stream`].
+///
+/// The reactive scope used for rendering is disposed when the stream is dropped.
+///
+/// _This API requires the following crate features to be activated: `suspense`, `ssr`_
+#[cfg(feature = "suspense")]
+pub struct SsrStream {
+    receiver: futures::channel::mpsc::UnboundedReceiver<String>,
+    disposer: Option<ScopeDisposer<'static>>,
+}
+
+#[cfg(feature = "suspense")]
+impl futures::Stream for SsrStream {
+    type Item = String;
+
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<String>> {
+        std::pin::Pin::new(&mut self.receiver).poll_next(cx)
+    }
+}
+
+#[cfg(feature = "suspense")]
+impl Drop for SsrStream {
+    fn drop(&mut self) {
+        if let Some(disposer) = self.disposer.take() {
+            // SAFETY: the stream cannot be accessed from inside the scope.
+            unsafe {
+                disposer.dispose();
+            }
+        }
+    }
+}
+
+/// Render a [`View`] into a stream of HTML chunks. Useful for streaming the response on the
+/// server side.
+///
+/// The first chunk is the whole view, in which the [`Suspense`](crate::suspense::Suspense)
+/// boundaries that are still loading are rendered with their fallback, surrounded by
+/// `<!--suspense-start:ID-->` and `<!--suspense-end:ID-->` comments. Every time one of these
+/// boundaries is resolved, a chunk containing its content in a `<template>` is sent, followed by
+/// an inline script that moves the content in place of the fallback. The content is rendered with
+/// the same hydration keys as with [`render_to_string`] so that the page can be hydrated once it
+/// has been fully streamed. Nested boundaries are streamed together with the outer boundary.
+///
+/// The stream ends once all the boundaries have been resolved.
+///
+/// _This API requires the following crate features to be activated: `suspense`, `ssr`_
+#[cfg(feature = "suspense")]
+#[must_use]
+pub fn render_to_stream(view: impl FnOnce(ScopeRef<'_>) -> View<SsrNode> + 'static) -> SsrStream {
+    let (sender, receiver) = futures::channel::mpsc::unbounded();
+    let disposer = create_scope(move |ctx| {
+        provide_ssr_marker(ctx);
+        let state = ctx.provide_context(SsrStreamState {
+            sender: RefCell::new(Some(sender)),
+            next_id: Default::default(),
+            pending: Default::default(),
+            shell_sent: Default::default(),
+        });
+        let v = with_hydration_context(|| view(ctx));
+        let mut shell = String::new();
+        for node in v.flatten() {
+            node.write_to_string(&mut shell);
+        }
+        state.send(shell);
+        state.shell_sent.set(true);
+        state.close_if_done();
+    });
+
+    SsrStream {
+        receiver,
+        disposer: Some(disposer),
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use std::cell::Cell;
+
     use super::*;
     use crate::prelude::*;
 
@@ -539,6 +734,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn dom_effect_does_not_run_during_ssr() {
+        let effect_runs = Rc::new(Cell::new(0));
+        let dom_effect_runs = Rc::new(Cell::new(0));
+        let html = render_to_string({
+            let effect_runs = Rc::clone(&effect_runs);
+            let dom_effect_runs = Rc::clone(&dom_effect_runs);
+            move |ctx| {
+                assert!(ctx.is_ssr());
+                let count = ctx.create_signal(1);
+                let double = ctx.create_signal(0);
+                ctx.create_effect(move || {
+                    effect_runs.set(effect_runs.get() + 1);
+                    double.set(*count.get() * 2);
+                });
+                ctx.create_dom_effect(move || {
+                    count.track();
+                    dom_effect_runs.set(dom_effect_runs.get() + 1);
+                });
+                count.set(2);
+                view! { ctx, (double.get()) }
+            }
+        });
+        assert_eq!(html, "4");
+        assert_eq!(effect_runs.get(), 2);
+        assert_eq!(dom_effect_runs.get(), 0);
+
+        create_scope_immediate(|ctx| {
+            assert!(!ctx.is_ssr());
+            let dom_effect_runs = Rc::clone(&dom_effect_runs);
+            ctx.create_dom_effect(move || dom_effect_runs.set(dom_effect_runs.get() + 1));
+        });
+        assert_eq!(dom_effect_runs.get(), 1);
+    }
+
     #[test]
     fn append_child() {
         let node = SsrNode::element("div");
@@ -599,4 +829,24 @@ mod tests {
         // node.first_child should be p2
         assert_eq!(node.first_child().as_ref(), Some(&p2));
     }
+
+    #[test]
+    fn insert_child_before_itself() {
+        let node = SsrNode::element("div");
+        let p = SsrNode::element("p");
+        let p2 = SsrNode::element("p");
+
+        node.append_child(&p);
+        node.append_child(&p2);
+
+        // Like in the DOM, p stays in place.
+        node.insert_child_before(&p, Some(&p));
+        assert_eq!(node.first_child().as_ref(), Some(&p));
+        assert_eq!(p.next_sibling().as_ref(), Some(&p2));
+        assert_eq!(p2.next_sibling().as_ref(), None);
+
+        node.insert_child_before(&p2, Some(&p2));
+        assert_eq!(p.next_sibling().as_ref(), Some(&p2));
+        assert_eq!(p2.next_sibling().as_ref(), None);
+    }
 }
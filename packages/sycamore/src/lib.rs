@@ -9,11 +9,17 @@
 //!
 //! - `builder` - Enables the builder API.
 //!
+//! - `devtools` - Enables debugging utilities for the reactive system, such as recording the recent
+//!   changes of a signal.
+//!
 //! - `dom` (_default_) - Enables rendering templates to DOM nodes. Only useful on
 //!   `wasm32-unknown-unknown` target.
 //!
 //! - `experimental-hydrate` - Enables client-side hydration support.
 //!
+//! - `profiling` - Enables recording reconciliation statistics for keyed lists (see the `stats`
+//!   prop of [`Keyed`](flow::Keyed)).
+//!
 //! - `suspense` - Enables wrappers around `wasm-bindgen-futures` to make it easier to extend a
 //!   reactive scope into an `async` function.
 //!
@@ -21,7 +27,7 @@
 //!   Pre-rendering).
 //!
 //! - `serde` - Enables serializing and deserializing `Signal`s and other wrapper types using
-//!   `serde`.
+//!   `serde`, as well as signals persisted to `localStorage` (see [`persist`]).
 //!
 //! - `wasm-bindgen-interning` (_default_) - Enables interning for `wasm-bindgen` strings. This
 //!   improves performance at a slight cost in binary size. If you want to minimize the size of the
@@ -41,7 +47,9 @@ extern crate self as sycamore;
 
 #[cfg(feature = "builder")]
 pub mod builder;
+pub mod class_list;
 pub mod component;
+pub mod dom_effect;
 pub mod easing;
 pub mod flow;
 #[cfg(feature = "suspense")]
@@ -50,7 +58,13 @@ pub mod generic_node;
 pub mod html;
 pub mod motion;
 pub mod noderef;
+#[cfg(feature = "serde")]
+pub mod persist;
 pub mod portal;
+#[cfg(feature = "dom")]
+pub mod reorder;
+#[cfg(feature = "dom")]
+pub mod style;
 #[cfg(feature = "suspense")]
 pub mod suspense;
 pub mod utils;
@@ -68,7 +82,7 @@ pub use crate::generic_node::render_to_string;
 #[cfg(all(feature = "dom", feature = "experimental-hydrate"))]
 pub use generic_node::{hydrate, hydrate_get_scope, hydrate_to};
 #[cfg(feature = "dom")]
-pub use generic_node::{render, render_get_scope, render_to};
+pub use generic_node::{microtask_scheduler, render, render_get_scope, render_to};
 
 pub use sycamore_macro::*;
 
@@ -88,11 +102,19 @@ pub mod prelude {
     #[cfg(feature = "ssr")]
     pub use crate::generic_node::SsrNode;
 
+    pub use crate::class_list::ScopeClassListExt;
     pub use crate::component::Children;
+    pub use crate::dom_effect::ScopeDomEffectExt;
     pub use crate::flow::*;
     pub use crate::generic_node::{GenericNode, Html};
     pub use crate::noderef::{NodeRef, ScopeCreateNodeRef};
+    #[cfg(feature = "serde")]
+    pub use crate::persist::ScopePersistExt;
     pub use crate::reactive::*;
+    #[cfg(feature = "dom")]
+    pub use crate::reorder::ScopeDragReorderExt;
+    #[cfg(feature = "dom")]
+    pub use crate::style::ScopeStyleExt;
     pub use crate::view::View;
 
     pub use sycamore_macro::*;
@@ -0,0 +1,358 @@
+//! Signals that are persisted to a key-value storage, such as `localStorage`.
+//!
+//! [`create_persisted_signal`](ScopePersistExt::create_persisted_signal) creates a [`Signal`] that
+//! is initialized from the storage and that writes its value back, serialized as JSON, whenever it
+//! changes. Writes are debounced: when the signal changes several times in a row, only the last
+//! value is written.
+//!
+//! By default, the value is persisted to the browser's `localStorage` (see [`LocalStorage`]). On
+//! other targets (e.g. when rendering on the server), nothing is read or written and the signal
+//! behaves like a normal signal. A different storage can be used by providing a
+//! `Rc<dyn PersistStorage>` as a context in a parent scope:
+//!
+//! ```
+//! # use std::rc::Rc;
+//! # use sycamore::persist::*;
+//! # use sycamore::prelude::*;
+//! # create_scope_immediate(|ctx| {
+//! ctx.provide_context::<Rc<dyn PersistStorage>>(Rc::new(LocalStorage));
+//! # });
+//! ```
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::reactive::*;
+
+/// The delay in milliseconds after the last change of a signal before [`LocalStorage`] writes it.
+#[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+const LOCAL_STORAGE_WRITE_DELAY_MS: i32 = 100;
+
+/// A string key-value storage that signals can be persisted to.
+pub trait PersistStorage {
+    /// Returns the value stored under `key`, or `None` if there is none.
+    fn get_item(&self, key: &str) -> Option<String>;
+    /// Stores `value` under `key`, overwriting any previous value.
+    fn set_item(&self, key: &str, value: &str);
+
+    /// Calls `write` later to store the new value of a signal. If the signal changes again before
+    /// `write` is called, `write` does nothing and the write scheduled for the new value stores it
+    /// instead.
+    ///
+    /// The default implementation calls `write` immediately.
+    fn schedule_write(&self, write: Box<dyn FnOnce()>) {
+        write();
+    }
+
+    /// Reports an error that occurred while persisting a signal, e.g. a value that could not be
+    /// serialized. Errors never panic as failing to persist a value should not break the app.
+    ///
+    /// The default implementation logs `msg` to the browser console, and does nothing when not
+    /// running in a browser.
+    fn report_error(&self, _msg: &str) {
+        #[cfg(target_arch = "wasm32")]
+        web_sys::console::error_1(&_msg.into());
+    }
+}
+
+/// A [`PersistStorage`] backed by the browser's `localStorage`.
+///
+/// When not running in a browser, or if `localStorage` is not available (e.g. in a web worker or
+/// because of the user's privacy settings), reads always return `None` and writes do nothing.
+///
+/// Writes are made 100ms after the last change of a signal.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LocalStorage;
+
+#[cfg(target_arch = "wasm32")]
+impl LocalStorage {
+    fn storage() -> Option<web_sys::Storage> {
+        web_sys::window()?.local_storage().ok()?
+    }
+}
+
+impl PersistStorage for LocalStorage {
+    fn get_item(&self, _key: &str) -> Option<String> {
+        #[cfg(target_arch = "wasm32")]
+        {
+            Self::storage()?.get_item(_key).ok()?
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            None
+        }
+    }
+
+    fn set_item(&self, _key: &str, _value: &str) {
+        #[cfg(target_arch = "wasm32")]
+        if let Some(storage) = Self::storage() {
+            if storage.set_item(_key, _value).is_err() {
+                self.report_error(&format!("failed to write `{_key}` to localStorage"));
+            }
+        }
+    }
+
+    fn schedule_write(&self, write: Box<dyn FnOnce()>) {
+        #[cfg(target_arch = "wasm32")]
+        {
+            use wasm_bindgen::closure::Closure;
+            use wasm_bindgen::JsCast;
+
+            // Without a window, there is no localStorage to write to either.
+            if let Some(window) = web_sys::window() {
+                let callback = Closure::once_into_js(write);
+                if window
+                    .set_timeout_with_callback_and_timeout_and_arguments_0(
+                        callback.unchecked_ref(),
+                        LOCAL_STORAGE_WRITE_DELAY_MS,
+                    )
+                    .is_err()
+                {
+                    self.report_error("failed to schedule a write to localStorage");
+                }
+            }
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        write();
+    }
+}
+
+/// Extension trait for [`Scope`] adding the
+/// [`create_persisted_signal`](ScopePersistExt::create_persisted_signal) method.
+pub trait ScopePersistExt<'a> {
+    /// Create a new [`Signal`] whose value is persisted under `key`.
+    ///
+    /// The signal is initialized with the value in the storage. If there is none, or if it cannot
+    /// be deserialized, `default` is used instead. Every time the signal changes, the new value is
+    /// serialized as JSON and written to the storage, when the storage decides to (see
+    /// [`PersistStorage::schedule_write`]). Writes are skipped if the serialized value did not
+    /// change.
+    ///
+    /// Serialization errors never panic. They are reported with [`PersistStorage::report_error`].
+    ///
+    /// The storage defaults to [`LocalStorage`]. See the [module docs](crate::persist) for how to
+    /// use a different storage.
+    fn create_persisted_signal<T>(&'a self, key: &str, default: T) -> &'a Signal<T>
+    where
+        T: Serialize + DeserializeOwned + 'a;
+}
+
+impl<'a> ScopePersistExt<'a> for Scope<'a> {
+    fn create_persisted_signal<T>(&'a self, key: &str, default: T) -> &'a Signal<T>
+    where
+        T: Serialize + DeserializeOwned + 'a,
+    {
+        let storage: Rc<dyn PersistStorage> = match self.try_use_context::<Rc<dyn PersistStorage>>()
+        {
+            Some(storage) => Rc::clone(storage),
+            None => Rc::new(LocalStorage),
+        };
+        let key = key.to_string();
+
+        let stored = storage.get_item(&key);
+        let initial = match stored.as_deref().map(serde_json::from_str) {
+            Some(Ok(value)) => value,
+            Some(Err(err)) => {
+                storage.report_error(&format!("failed to deserialize persisted `{key}`: {err}"));
+                default
+            }
+            None => default,
+        };
+        let signal = self.create_signal(initial);
+
+        // The last value that is known to be in the storage, used to skip redundant writes.
+        let last_written = Rc::new(RefCell::new(stored));
+        // Incremented on every change so that a scheduled write can tell whether it is stale.
+        let generation = Rc::new(Cell::new(0u64));
+        self.create_effect(move || match serde_json::to_string(&*signal.get()) {
+            Ok(json) => {
+                generation.set(generation.get() + 1);
+                let write = {
+                    let storage = Rc::clone(&storage);
+                    let key = key.clone();
+                    let last_written = Rc::clone(&last_written);
+                    let generation = Rc::clone(&generation);
+                    let scheduled = generation.get();
+                    move || {
+                        // The signal has changed again and a write is scheduled for the new value.
+                        if generation.get() != scheduled {
+                            return;
+                        }
+                        if last_written.borrow().as_deref() != Some(json.as_str()) {
+                            storage.set_item(&key, &json);
+                            *last_written.borrow_mut() = Some(json);
+                        }
+                    }
+                };
+                storage.schedule_write(Box::new(write));
+            }
+            Err(err) => {
+                storage.report_error(&format!("failed to serialize persisted `{key}`: {err}"));
+            }
+        });
+
+        signal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct MockStorage {
+        items: RefCell<HashMap<String, String>>,
+        writes: Cell<u32>,
+        /// If `true`, scheduled writes are only run by [`MockStorage::flush`].
+        deferred: bool,
+        scheduled: RefCell<Vec<Box<dyn FnOnce()>>>,
+        errors: RefCell<Vec<String>>,
+    }
+
+    impl MockStorage {
+        fn flush(&self) {
+            let scheduled = self.scheduled.take();
+            for write in scheduled {
+                write();
+            }
+        }
+    }
+
+    impl PersistStorage for MockStorage {
+        fn get_item(&self, key: &str) -> Option<String> {
+            self.items.borrow().get(key).cloned()
+        }
+
+        fn set_item(&self, key: &str, value: &str) {
+            self.writes.set(self.writes.get() + 1);
+            self.items
+                .borrow_mut()
+                .insert(key.to_string(), value.to_string());
+        }
+
+        fn schedule_write(&self, write: Box<dyn FnOnce()>) {
+            if self.deferred {
+                self.scheduled.borrow_mut().push(write);
+            } else {
+                write();
+            }
+        }
+
+        fn report_error(&self, msg: &str) {
+            self.errors.borrow_mut().push(msg.to_string());
+        }
+    }
+
+    fn provide_mock(ctx: ScopeRef<'_>, storage: &Rc<MockStorage>) {
+        ctx.provide_context::<Rc<dyn PersistStorage>>(Rc::clone(storage) as Rc<dyn PersistStorage>);
+    }
+
+    #[test]
+    fn persisted_signal_reads_from_storage() {
+        let storage = Rc::new(MockStorage::default());
+        storage
+            .items
+            .borrow_mut()
+            .insert("count".to_string(), "5".to_string());
+        create_scope_immediate(|ctx| {
+            provide_mock(ctx, &storage);
+            let count = ctx.create_persisted_signal("count", 0);
+            assert_eq!(*count.get(), 5);
+        });
+        assert_eq!(storage.writes.get(), 0);
+    }
+
+    #[test]
+    fn persisted_signal_writes_on_change() {
+        let storage = Rc::new(MockStorage::default());
+        create_scope_immediate(|ctx| {
+            provide_mock(ctx, &storage);
+            let count = ctx.create_persisted_signal("count", 0);
+            assert_eq!(*count.get(), 0);
+            assert_eq!(storage.get_item("count").as_deref(), Some("0"));
+
+            count.set(1);
+            assert_eq!(storage.get_item("count").as_deref(), Some("1"));
+            assert_eq!(storage.writes.get(), 2);
+
+            // Setting the same value does not write again.
+            count.set(1);
+            assert_eq!(storage.writes.get(), 2);
+        });
+    }
+
+    #[test]
+    fn persisted_signal_debounces_writes() {
+        let storage = Rc::new(MockStorage {
+            deferred: true,
+            ..Default::default()
+        });
+        create_scope_immediate(|ctx| {
+            provide_mock(ctx, &storage);
+            let count = ctx.create_persisted_signal("count", 0);
+            count.set(1);
+            count.set(2);
+            assert_eq!(storage.writes.get(), 0);
+
+            // Only the last value is written.
+            storage.flush();
+            assert_eq!(storage.writes.get(), 1);
+            assert_eq!(storage.get_item("count").as_deref(), Some("2"));
+
+            count.set(3);
+            storage.flush();
+            assert_eq!(storage.writes.get(), 2);
+            assert_eq!(storage.get_item("count").as_deref(), Some("3"));
+        });
+    }
+
+    #[test]
+    fn persisted_signal_reports_errors() {
+        let storage = Rc::new(MockStorage::default());
+        storage
+            .items
+            .borrow_mut()
+            .insert("map".to_string(), "not json".to_string());
+        create_scope_immediate(|ctx| {
+            provide_mock(ctx, &storage);
+            // JSON objects only support string keys.
+            let map = ctx.create_persisted_signal("map", HashMap::<(i32, i32), i32>::new());
+            assert_eq!(storage.errors.borrow().len(), 1);
+            map.set(HashMap::from([((1, 2), 3)]));
+            assert_eq!(storage.errors.borrow().len(), 2);
+            assert!(storage.errors.borrow()[1].contains("failed to serialize persisted `map`"));
+        });
+        // The last value that could be serialized is kept in the storage.
+        assert_eq!(storage.get_item("map").as_deref(), Some("{}"));
+    }
+
+    #[test]
+    fn persisted_signal_falls_back_to_default_on_invalid_data() {
+        let storage = Rc::new(MockStorage::default());
+        storage
+            .items
+            .borrow_mut()
+            .insert("name".to_string(), "not json".to_string());
+        create_scope_immediate(|ctx| {
+            provide_mock(ctx, &storage);
+            let name = ctx.create_persisted_signal("name", "default".to_string());
+            assert_eq!(*name.get(), "default");
+            assert_eq!(storage.get_item("name").as_deref(), Some("\"default\""));
+        });
+    }
+
+    #[test]
+    fn persisted_signal_without_local_storage() {
+        create_scope_immediate(|ctx| {
+            let count = ctx.create_persisted_signal("count", 1);
+            count.set(2);
+            assert_eq!(*count.get(), 2);
+        });
+    }
+}
@@ -0,0 +1,129 @@
+//! Reordering lists with drag-and-drop.
+
+use std::cell::Cell;
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::{intern, JsCast};
+use web_sys::{Element, Event, Node};
+
+use crate::generic_node::DomNode;
+use crate::noderef::NodeRef;
+use crate::reactive::*;
+
+/// Extension trait for [`Scope`] adding the
+/// [`enable_drag_reorder`](ScopeDragReorderExt::enable_drag_reorder) method.
+pub trait ScopeDragReorderExt<'a> {
+    /// Allow the items of a list to be reordered by dragging them with a pointer.
+    ///
+    /// `node_ref` should reference the list element and every child element of the list should
+    /// render the item at the same index in `signal`, which is usually the case when the children
+    /// are rendered with [`Keyed`](crate::flow::Keyed) or [`Indexed`](crate::flow::Indexed).
+    /// When an item is dragged from one child element and dropped onto another one, it is moved
+    /// to the index of the latter in `signal`. With [`Keyed`](crate::flow::Keyed), this only moves
+    /// the existing DOM nodes instead of re-rendering them.
+    ///
+    /// The event listeners are removed when the scope is disposed.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use sycamore::prelude::*;
+    /// # create_scope_immediate(|ctx| {
+    /// let items = ctx.create_signal(vec![1, 2, 3]);
+    /// let list = ctx.create_node_ref();
+    /// let view: View<DomNode> = view! { ctx,
+    ///     ul(ref=list) {
+    ///         Keyed {
+    ///             iterable: items,
+    ///             view: |ctx, item| view! { ctx, li { (item) } },
+    ///             key: |item| *item,
+    ///         }
+    ///     }
+    /// };
+    /// ctx.enable_drag_reorder(list, items);
+    /// # });
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `node_ref` is not set yet. It should therefore be called after the
+    /// list element is created, e.g. after the `view!` containing it.
+    fn enable_drag_reorder<T: Clone + 'a>(
+        &'a self,
+        node_ref: &NodeRef<DomNode>,
+        signal: &'a Signal<Vec<T>>,
+    );
+}
+
+impl<'a> ScopeDragReorderExt<'a> for Scope<'a> {
+    fn enable_drag_reorder<T: Clone + 'a>(
+        &'a self,
+        node_ref: &NodeRef<DomNode>,
+        signal: &'a Signal<Vec<T>>,
+    ) {
+        let list = node_ref.get::<DomNode>().inner_element();
+        // The index of the item that is being dragged, if any.
+        let dragged = self.create_ref(Cell::new(None));
+
+        let list_node = list.clone();
+        add_listener(self, &list, "pointerdown", move |event| {
+            dragged.set(item_index(&list_node, &event));
+        });
+
+        let list_node = list.clone();
+        add_listener(self, &list, "pointerup", move |event| {
+            let (from, to) = match (dragged.take(), item_index(&list_node, &event)) {
+                (Some(from), Some(to)) => (from, to),
+                _ => return,
+            };
+            if from == to || from >= signal.get_untracked().len() {
+                return;
+            }
+            let mut items = (*signal.get_untracked()).clone();
+            let item = items.remove(from);
+            items.insert(to.min(items.len()), item);
+            signal.set(items);
+        });
+
+        add_listener(self, &list, "pointercancel", move |_| dragged.set(None));
+    }
+}
+
+/// Adds an event listener to `node` that is removed when `ctx` is disposed.
+///
+/// Unlike [`GenericNode::event`](crate::generic_node::GenericNode::event), which only drops the
+/// closure, this also detaches the listener from `node` so that events dispatched after the scope
+/// is disposed are ignored.
+fn add_listener<'a>(
+    ctx: ScopeRef<'a>,
+    node: &Node,
+    name: &'static str,
+    handler: impl Fn(Event) + 'a,
+) {
+    let handler: Box<dyn Fn(Event) + 'a> = Box::new(handler);
+    // SAFETY: extend lifetime because the listener is removed and the closure is dropped when the
+    // ctx is disposed, preventing the handler from ever being accessed after its lifetime.
+    let handler: Box<dyn Fn(Event) + 'static> = unsafe { std::mem::transmute(handler) };
+    let closure = Closure::wrap(handler);
+    node.add_event_listener_with_callback(intern(name), closure.as_ref().unchecked_ref())
+        .unwrap_throw();
+
+    let node = node.clone();
+    ctx.on_cleanup(move || {
+        node.remove_event_listener_with_callback(intern(name), closure.as_ref().unchecked_ref())
+            .unwrap_throw();
+    });
+}
+
+/// Returns the index of the child element of `list` that contains the target of `event`.
+fn item_index(list: &Node, event: &Event) -> Option<usize> {
+    let mut node: Node = event.target()?.dyn_into().ok()?;
+    loop {
+        let parent = node.parent_node()?;
+        if &parent == list {
+            break;
+        }
+        node = parent;
+    }
+    let children = list.unchecked_ref::<Element>().children();
+    (0..children.length()).position(|i| children.item(i).as_deref() == Some(node.unchecked_ref()))
+}
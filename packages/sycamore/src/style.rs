@@ -0,0 +1,95 @@
+//! Binding inline styles reactively.
+
+use wasm_bindgen::JsCast;
+use web_sys::HtmlElement;
+
+use crate::generic_node::DomNode;
+use crate::noderef::NodeRef;
+use crate::reactive::*;
+
+/// Extension trait for [`Scope`] adding the [`bind_style`](ScopeStyleExt::bind_style) and
+/// [`bind_styles`](ScopeStyleExt::bind_styles) methods.
+pub trait ScopeStyleExt<'a> {
+    /// Reactively set the inline style property `name` of the element referenced by `node_ref` to
+    /// the value of `value`.
+    ///
+    /// The property is removed from the inline style of the element when `value` is an empty
+    /// string. Only the property `name` is touched, so the other properties of the inline style,
+    /// e.g. the ones set with the `style` attribute, are kept.
+    ///
+    /// The effect is disposed along with the scope.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use sycamore::prelude::*;
+    /// # create_scope_immediate(|ctx| {
+    /// let progress = ctx.create_signal(0);
+    /// let width = ctx.create_memo(|| format!("{}%", progress.get()));
+    /// let bar = ctx.create_node_ref();
+    /// let view: View<DomNode> = view! { ctx,
+    ///     div(ref=bar, class="progress-bar")
+    /// };
+    /// ctx.bind_style(bar, "width", width);
+    /// # });
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `node_ref` is not set yet or if it does not reference an HTML
+    /// element. It should therefore be called after the element is created, e.g. after the
+    /// `view!` containing it.
+    fn bind_style(
+        &'a self,
+        node_ref: &NodeRef<DomNode>,
+        name: &'a str,
+        value: &'a ReadSignal<String>,
+    );
+
+    /// Reactively set each inline style property in `styles` on the element referenced by
+    /// `node_ref`. This is the same as calling [`bind_style`](Self::bind_style) for each property,
+    /// i.e. each property is updated by its own effect.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `node_ref` is not set yet or if it does not reference an HTML
+    /// element.
+    fn bind_styles(
+        &'a self,
+        node_ref: &NodeRef<DomNode>,
+        styles: impl IntoIterator<Item = (&'a str, &'a ReadSignal<String>)>,
+    );
+}
+
+impl<'a> ScopeStyleExt<'a> for Scope<'a> {
+    fn bind_style(
+        &'a self,
+        node_ref: &NodeRef<DomNode>,
+        name: &'a str,
+        value: &'a ReadSignal<String>,
+    ) {
+        self.bind_styles(node_ref, [(name, value)]);
+    }
+
+    fn bind_styles(
+        &'a self,
+        node_ref: &NodeRef<DomNode>,
+        styles: impl IntoIterator<Item = (&'a str, &'a ReadSignal<String>)>,
+    ) {
+        let element: HtmlElement = node_ref
+            .get::<DomNode>()
+            .inner_element()
+            .dyn_into()
+            .expect("node_ref should reference an HTML element");
+        for (name, value) in styles {
+            let style = element.style();
+            self.create_effect(move || {
+                let value = value.get();
+                if value.is_empty() {
+                    style.remove_property(name).unwrap();
+                } else {
+                    style.set_property(name, &value).unwrap();
+                }
+            });
+        }
+    }
+}
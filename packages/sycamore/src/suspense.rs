@@ -34,6 +34,10 @@ pub struct SuspenseProps<'a, G: GenericNode> {
 /// be rendered once all individual async components are rendered. This is useful for showing a
 /// loading indicator while the data is being loaded.
 ///
+/// A `Suspense` only waits for the async tasks created below it, up to the next nested `Suspense`.
+/// For instance, wrapping the view of each item of a `Keyed` list in its own `Suspense` lets every
+/// item show its content as soon as it is loaded, independently of the other items.
+///
 /// # Example
 /// ```
 /// use sycamore::prelude::*;
@@ -59,6 +63,9 @@ pub fn Suspense<'a, G: GenericNode>(ctx: ScopeRef<'a>, props: SuspenseProps<'a,
     let state = ctx.use_context_or_else(SuspenseState::default);
     // Get the outer suspense state.
     let outer_count = state.async_counts.borrow().last().cloned();
+    // Nested boundaries are streamed together with the outer boundary.
+    #[cfg(feature = "ssr")]
+    let streamed = outer_count.is_none();
     // Push a new suspense state.
     let count = create_rc_signal(0);
     state.async_counts.borrow_mut().push(count.clone());
@@ -81,11 +88,54 @@ pub fn Suspense<'a, G: GenericNode>(ctx: ScopeRef<'a>, props: SuspenseProps<'a,
         });
     }
 
+    #[cfg(feature = "ssr")]
+    if streamed {
+        if let Some(view) = stream_boundary(ctx, &v, &props.fallback, ready) {
+            return view;
+        }
+    }
+
     view! { ctx,
         (if *ready.get() { v.clone() } else { props.fallback.clone() })
     }
 }
 
+/// If rendering with [`render_to_stream`](crate::generic_node::render_to_stream) and the boundary
+/// is not ready yet, renders the fallback between markers and streams `v` once the boundary is
+/// ready.
+#[cfg(feature = "ssr")]
+fn stream_boundary<'a, G: GenericNode>(
+    ctx: ScopeRef<'a>,
+    v: &View<G>,
+    fallback: &View<G>,
+    ready: &'a ReadSignal<bool>,
+) -> Option<View<G>> {
+    use std::any::Any;
+
+    use crate::generic_node::SsrStreamState;
+
+    let stream = ctx.try_use_context::<SsrStreamState>()?;
+    if *ready.get_untracked() {
+        return None;
+    }
+    let resolved = <dyn Any>::downcast_ref::<View<SsrNode>>(v)?.clone();
+
+    let id = stream.register();
+    let streamed = ctx.create_ref(Cell::new(false));
+    ctx.create_effect(move || {
+        if !streamed.get() && *ready.get() {
+            stream.resolve(id, resolved.clone());
+            streamed.set(true);
+        }
+    });
+
+    Some(View::new_fragment(vec![
+        View::new_node(G::marker_with_text(&format!("suspense-start:{id}"))),
+        fallback.clone(),
+        View::new_node(G::marker_with_text(&format!("suspense-end:{id}"))),
+    ]))
+}
+
 /// Creates a new "suspense scope". This scope is used to signal to a [`Suspense`] component higher
 /// up in the component hierarchy that there is some async task that should be awaited before
 /// rendering the UI.
@@ -188,7 +238,9 @@ impl<'a> ScopeUseTransition<'a> for Scope<'a> {
 
 #[cfg(all(test, feature = "ssr"))]
 mod tests {
-    use sycamore_futures::provide_executor_scope;
+    use std::collections::HashMap;
+
+    use sycamore_futures::{provide_executor_scope, ManualExecutor};
 
     use super::*;
     use crate::generic_node::render_to_string_await_suspense;
@@ -215,6 +267,54 @@ mod tests {
         assert_eq!(view, "Hello Suspense!");
     }
 
+    #[test]
+    fn stream_suspense_with_resource() {
+        use futures::{FutureExt, StreamExt};
+
+        use crate::futures::ScopeFuturesExt;
+        use crate::generic_node::render_to_stream;
+
+        let executor = ManualExecutor::new();
+        let (sender, receiver) = oneshot::channel::<&'static str>();
+        let mut stream = render_to_stream({
+            let executor = executor.clone();
+            move |ctx| {
+                ctx.provide_executor(executor);
+                view! { ctx,
+                    p { "Before" }
+                    Suspense {
+                        fallback: view! { ctx, "Loading..." },
+                        children: Children::new(ctx, move |ctx| {
+                            #[allow(deprecated)]
+                            let data = ctx.create_resource(async move { receiver.await.unwrap() });
+                            view! { ctx, (data.get().unwrap_or_default()) }
+                        }),
+                    }
+                }
+            }
+        });
+        let mut next_chunk = || stream.next().now_or_never().expect("chunk should be ready");
+
+        assert_eq!(
+            next_chunk().unwrap(),
+            "<p data-hk=\"0.0\">Before</p><!--suspense-start:0-->Loading...<!--suspense-end:0-->"
+        );
+
+        sender.send("Loaded").unwrap();
+        executor.run_until_stalled();
+        let chunk = next_chunk().unwrap();
+        assert!(
+            chunk.starts_with(
+                "<template id=\"sycamore-suspense-0\">Loaded</template><script>(function(i){"
+            ),
+            "{chunk}"
+        );
+        assert!(chunk.ends_with("})(0)</script>"), "{chunk}");
+
+        // The stream ends once all the boundaries are resolved.
+        assert_eq!(next_chunk(), None);
+    }
+
     #[tokio::test]
     async fn transition() {
         provide_executor_scope(async {
@@ -237,4 +337,68 @@ mod tests {
         })
         .await;
     }
+
+    #[test]
+    fn keyed_items_suspend_independently() {
+        let executor = ManualExecutor::new();
+        create_scope_immediate(|ctx| {
+            ctx.provide_executor(executor.clone());
+            let (fast_tx, fast_rx) = oneshot::channel::<()>();
+            let (slow_tx, slow_rx) = oneshot::channel::<()>();
+            let loading = ctx.create_ref(RefCell::new(HashMap::from([
+                ("fast", fast_rx),
+                ("slow", slow_rx),
+            ])));
+            let items = ctx.create_signal(vec!["fast", "slow"]);
+            let node = view! { ctx,
+                ul {
+                    Keyed {
+                        iterable: items,
+                        view: move |ctx, item| {
+                            let loaded = loading.borrow_mut().remove(item).unwrap();
+                            view! { ctx,
+                                li {
+                                    Suspense {
+                                        fallback: view! { ctx, "loading " (item) },
+                                        children: Children::new(ctx, move |ctx| {
+                                            suspense_scope(&ctx, async move {
+                                                let _ = loaded.await;
+                                            });
+                                            view! { ctx, (item) }
+                                        }),
+                                    }
+                                }
+                            }
+                        },
+                        key: |item| *item,
+                    }
+                }
+            };
+            let render = || crate::render_to_string(|_| node.clone());
+            executor.run_until_stalled();
+            assert_eq!(
+                render(),
+                "<ul><li>loading fast</li><li>loading slow</li></ul>"
+            );
+
+            fast_tx.send(()).unwrap();
+            executor.run_until_stalled();
+            assert_eq!(render(), "<ul><li>fast</li><li>loading slow</li></ul>");
+
+            // Adding an item does not affect the items that are already loaded or loading.
+            let (new_tx, new_rx) = oneshot::channel::<()>();
+            loading.borrow_mut().insert("new", new_rx);
+            items.set(vec!["fast", "slow", "new"]);
+            executor.run_until_stalled();
+            assert_eq!(
+                render(),
+                "<ul><li>fast</li><li>loading slow</li><li>loading new</li></ul>"
+            );
+
+            new_tx.send(()).unwrap();
+            slow_tx.send(()).unwrap();
+            executor.run_until_stalled();
+            assert_eq!(render(), "<ul><li>fast</li><li>slow</li><li>new</li></ul>");
+        });
+    }
 }
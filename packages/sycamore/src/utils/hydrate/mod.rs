@@ -47,6 +47,51 @@ pub fn get_current_id() -> Option<(usize, usize)> {
     })
 }
 
+/// Sets the next hydration key of the current component, e.g. to skip the keys of server rendered
+/// elements that were replaced instead of hydrated.
+///
+/// If hydration context does not exist, does nothing.
+pub fn set_next_id(id: usize) {
+    HYDRATION_CONTEXT.with(|context| {
+        if let Some(reg) = context.borrow_mut().as_mut() {
+            reg.current_id = id;
+        }
+    });
+}
+
+/// Returns `true` if the nodes that are currently created are part of a subtree that is rendered
+/// on the client instead of being hydrated.
+///
+/// If hydration context does not exist, returns `false`.
+pub fn client_rendering() -> bool {
+    HYDRATION_CONTEXT.with(|context| matches!(*context.borrow(), Some(reg) if reg.client_rendering))
+}
+
+/// Sets whether the nodes that are created next are part of a subtree that is rendered on the
+/// client instead of being hydrated.
+///
+/// If hydration context does not exist, does nothing.
+pub fn set_client_rendering(client_rendering: bool) {
+    HYDRATION_CONTEXT.with(|context| {
+        if let Some(reg) = context.borrow_mut().as_mut() {
+            reg.client_rendering = client_rendering;
+        }
+    });
+}
+
+/// Calls the callback that creates a nested view and restores [`client_rendering`] afterwards, so
+/// that a client rendered element at the root of the nested view does not prevent the nodes after
+/// it from being hydrated.
+pub fn hydrate_nested<F, R>(f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    let prev = client_rendering();
+    let r = f();
+    set_client_rendering(prev);
+    r
+}
+
 /// Returns `true` if hydration has completed.
 pub fn hydration_completed() -> bool {
     HYDRATION_CONTEXT.with(|context| context.borrow().is_none())
@@ -64,12 +109,14 @@ where
         if context.borrow().is_some() {
             let prev_id;
             let prev_component_id;
+            let prev_client_rendering;
             {
                 let mut context = context.borrow_mut();
                 let context = context.as_mut().unwrap();
                 // Store previous state to restore after component.
                 prev_component_id = context.current_component_id;
                 prev_id = context.current_id;
+                prev_client_rendering = context.client_rendering;
 
                 context.current_component_id = context.next_component_id;
                 context.next_component_id += 1;
@@ -78,6 +125,7 @@ where
             let r = f();
             context.borrow_mut().as_mut().unwrap().current_component_id = prev_component_id;
             context.borrow_mut().as_mut().unwrap().current_id = prev_id;
+            context.borrow_mut().as_mut().unwrap().client_rendering = prev_client_rendering;
             r
         } else {
             f()
@@ -99,6 +147,9 @@ pub struct HydrationRegistry {
     /// the current component id. This is to ensure that component ids are unique for each
     /// instance of a component.
     pub next_component_id: usize,
+    /// `true` while creating the children of an element that is rendered on the client instead of
+    /// being hydrated. These children are created without looking up server rendered nodes.
+    pub client_rendering: bool,
 }
 
 impl HydrationRegistry {
@@ -108,6 +159,7 @@ impl HydrationRegistry {
             current_id: 0,
             current_component_id: 0,
             next_component_id: 1,
+            client_rendering: false,
         }
     }
 
@@ -2,7 +2,7 @@
 
 use std::any::Any;
 use std::borrow::Cow;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::fmt;
 use std::rc::Rc;
 
@@ -27,6 +27,16 @@ pub struct View<G: GenericNode> {
     pub(crate) inner: ViewType<G>,
 }
 
+/// Creates the nested [`View`] of a dynamic view. When hydrating, a mismatched element at the root
+/// of the nested view does not prevent the nodes after it from being hydrated.
+fn nested_view<G: GenericNode>(f: impl FnOnce() -> View<G>) -> View<G> {
+    #[cfg(feature = "experimental-hydrate")]
+    if G::USE_HYDRATION_CONTEXT {
+        return crate::utils::hydrate::hydrate_nested(f);
+    }
+    f()
+}
+
 impl<G: GenericNode> View<G> {
     /// Create a new [`View`] from a [`GenericNode`].
     pub fn new_node(node: G) -> Self {
@@ -39,7 +49,7 @@ impl<G: GenericNode> View<G> {
     pub fn new_dyn<'a>(ctx: ScopeRef<'a>, mut f: impl FnMut() -> View<G> + 'a) -> Self {
         let signal = ctx.create_ref(RefCell::new(None::<RcSignal<View<G>>>));
         ctx.create_effect(move || {
-            let view = f();
+            let view = nested_view(&mut f);
             if signal.borrow().is_some() {
                 signal.borrow().as_ref().unwrap().set(view);
             } else {
@@ -60,7 +70,7 @@ impl<G: GenericNode> View<G> {
         ctx.create_effect_scoped(move |ctx| {
             // SAFETY: `f` takes the same parameter as the child ctx provided by
             // `create_effect_scoped`.
-            let view = f(unsafe { std::mem::transmute(ctx) });
+            let view = nested_view(|| f(unsafe { std::mem::transmute(ctx) }));
             if signal.borrow().is_some() {
                 signal.borrow().as_ref().unwrap().set(view);
             } else {
@@ -72,13 +82,164 @@ impl<G: GenericNode> View<G> {
         }
     }
 
+    /// Create a new [`View`] that is only rebuilt when the value returned by `key` changes.
+    ///
+    /// `builder` is called in a new child scope with the current key. As long as `key` returns
+    /// values that are equal to the previous one, the view and its child scope are kept, including
+    /// the signals that were created in it, and the view is only updated by its own effects. When
+    /// the key changes, the child scope is disposed and `builder` is called again in a fresh one.
+    ///
+    /// Only `key` is tracked. Use it to return whatever decides the structure of the view, e.g. the
+    /// variant of an enum, and read the rest of the state reactively inside the view.
+    ///
+    /// ```
+    /// # use sycamore::prelude::*;
+    /// # fn view<'a, G: Html>(ctx: ScopeRef<'a>, user: &'a ReadSignal<Option<String>>) -> View<G> {
+    /// View::new_dyn_keyed(ctx, || user.get().is_some(), move |ctx, &logged_in| {
+    ///     if logged_in {
+    ///         // Keeps its value while the user is logged in, even if the name changes.
+    ///         let clicks = ctx.create_signal(0);
+    ///         view! { ctx,
+    ///             button(on:click=|_| clicks.set(*clicks.get() + 1)) {
+    ///                 (user.get().as_deref().unwrap_or_default().to_string())
+    ///             }
+    ///         }
+    ///     } else {
+    ///         view! { ctx, "Logged out" }
+    ///     }
+    /// })
+    /// # }
+    /// ```
+    pub fn new_dyn_keyed<'a, K: PartialEq + 'a>(
+        ctx: ScopeRef<'a>,
+        key: impl Fn() -> K + 'a,
+        builder: impl Fn(BoundedScopeRef<'_, 'a>, &K) -> View<G> + 'a,
+    ) -> Self {
+        let key = ctx.create_selector(key);
+        Self::new_dyn_scoped(ctx, move |ctx| {
+            let key = key.get();
+            untrack(|| builder(ctx, &key))
+        })
+    }
+
+    /// Create a new [`View`] from a [`FnMut`] that patches the existing nodes in place instead of
+    /// replacing them whenever possible.
+    ///
+    /// Every time `f` is re-run, the new view is compared with the view that is currently rendered,
+    /// position by position. If both views are structurally equal (same node types, tag names and
+    /// number of children), the text content and attributes of the existing nodes are updated to
+    /// match the new view and the new nodes are discarded. This preserves node identity and
+    /// therefore focus, selection and any state that lives on the nodes themselves. Otherwise, the
+    /// old view is replaced just like with [`View::new_dyn`].
+    ///
+    /// Since the new nodes are discarded when patching, event handlers and reactive bindings that
+    /// are created inside `f` are not carried over. This is therefore best suited for views that
+    /// only contain static content. See [`GenericNode::patch`] for more details.
+    pub fn new_dyn_patch<'a>(ctx: ScopeRef<'a>, mut f: impl FnMut() -> View<G> + 'a) -> Self {
+        let signal = ctx.create_ref(RefCell::new(None::<RcSignal<View<G>>>));
+        ctx.create_effect(move || {
+            let view = nested_view(&mut f);
+            if signal.borrow().is_some() {
+                let signal = signal.borrow();
+                let signal = signal.as_ref().unwrap();
+                if !signal.get_untracked().patch(&view) {
+                    signal.set(view);
+                }
+            } else {
+                *signal.borrow_mut() = Some(create_rc_signal(view));
+            }
+        });
+        Self {
+            inner: ViewType::Dyn(signal.borrow().as_ref().unwrap().clone()),
+        }
+    }
+
+    /// Create a new [`View`] that is only built once `visible` is `true`.
+    ///
+    /// `builder` is not called, and therefore no nodes or effects are created, until `visible`
+    /// first becomes `true`. The view then stays rendered even if `visible` becomes `false` again.
+    /// This is useful for deferring expensive views that are not initially shown, such as collapsed
+    /// sections. To dispose the view whenever `visible` becomes `false`, use
+    /// [`lazy_dispose_on_hide`](Self::lazy_dispose_on_hide) instead.
+    ///
+    /// `builder` is called in a new child scope and is untracked, so the view is only rebuilt when
+    /// it is revealed.
+    pub fn lazy<'a>(
+        ctx: ScopeRef<'a>,
+        visible: &'a ReadSignal<bool>,
+        builder: impl Fn(BoundedScopeRef<'_, 'a>) -> View<G> + 'a,
+    ) -> Self {
+        Self::_lazy(ctx, visible, false, builder)
+    }
+
+    /// Same as [`lazy`](Self::lazy) but the view, including its child scope, is disposed every
+    /// time `visible` becomes `false` and built again when `visible` becomes `true`.
+    pub fn lazy_dispose_on_hide<'a>(
+        ctx: ScopeRef<'a>,
+        visible: &'a ReadSignal<bool>,
+        builder: impl Fn(BoundedScopeRef<'_, 'a>) -> View<G> + 'a,
+    ) -> Self {
+        Self::_lazy(ctx, visible, true, builder)
+    }
+
+    fn _lazy<'a>(
+        ctx: ScopeRef<'a>,
+        visible: &'a ReadSignal<bool>,
+        dispose_on_hide: bool,
+        builder: impl Fn(BoundedScopeRef<'_, 'a>) -> View<G> + 'a,
+    ) -> Self {
+        let revealed = Cell::new(false);
+        let show = ctx.create_selector(move || {
+            if !dispose_on_hide && revealed.get() {
+                // Stop tracking `visible` once the view is built.
+                return true;
+            }
+            let visible = *visible.get();
+            revealed.set(visible);
+            visible
+        });
+        Self::new_dyn_scoped(ctx, move |ctx| {
+            if *show.get() {
+                untrack(|| builder(ctx))
+            } else {
+                Self::empty()
+            }
+        })
+    }
+
     /// Create a new [`View`] from a `Vec` of [`GenericNode`]s.
+    ///
+    /// Fragments in `fragment` that only contain nodes are replaced with their nodes, so that
+    /// composing many small views does not create deeply nested fragments. Fragments containing
+    /// dynamic views are kept as is. This does not change the nodes that are rendered or their
+    /// order.
     pub fn new_fragment(fragment: Vec<View<G>>) -> Self {
+        let fragment = if fragment.iter().any(View::is_static_fragment) {
+            let mut flattened = Vec::with_capacity(fragment.len());
+            for view in fragment {
+                match &view.inner {
+                    ViewType::Fragment(nodes) if view.is_static_fragment() => {
+                        flattened.extend(nodes.iter().cloned());
+                    }
+                    _ => flattened.push(view),
+                }
+            }
+            flattened
+        } else {
+            fragment
+        };
         Self {
             inner: ViewType::Fragment(Rc::from(fragment.into_boxed_slice())),
         }
     }
 
+    /// Create a new [`View`] from an iterator of [`View`]s. This is equivalent to
+    /// [`new_fragment`](Self::new_fragment) but does not require collecting the views into a
+    /// `Vec` first.
+    pub fn new_fragment_from_iter(fragment: impl IntoIterator<Item = View<G>>) -> Self {
+        Self::new_fragment(fragment.into_iter().collect())
+    }
+
     /// Create a new [`View`] with a blank comment node
     pub fn empty() -> Self {
         Self::new_node(G::marker())
@@ -111,6 +272,25 @@ impl<G: GenericNode> View<G> {
         }
     }
 
+    /// Patch the nodes of this view in place to match `other`. See [`GenericNode::patch`].
+    ///
+    /// Returns `false` if the views are not structurally equal. Dynamic views are never patched.
+    fn patch(&self, other: &Self) -> bool {
+        match (&self.inner, &other.inner) {
+            (ViewType::Node(old), ViewType::Node(new)) => old.patch(new),
+            (ViewType::Fragment(old), ViewType::Fragment(new)) => {
+                old.len() == new.len()
+                    && old.iter().zip(new.iter()).all(|(old, new)| old.patch(new))
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the view is a fragment that only contains nodes.
+    fn is_static_fragment(&self) -> bool {
+        matches!(&self.inner, ViewType::Fragment(fragment) if fragment.iter().all(View::is_node))
+    }
+
     /// Returns `true` if the view is a single node.
     pub fn is_node(&self) -> bool {
         matches!(
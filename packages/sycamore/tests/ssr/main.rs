@@ -1,5 +1,6 @@
-use std::cell::Cell;
+use std::cell::{Cell, RefCell};
 
+use sycamore::component::Slots;
 use sycamore::prelude::*;
 
 #[test]
@@ -75,6 +76,47 @@ fn fragments() {
     });
 }
 
+#[test]
+fn fragment_from_iter() {
+    create_scope_immediate(|ctx| {
+        let from_vec = View::new_fragment((1..=3).map(|i| view! { ctx, p { (i) } }).collect());
+        let from_iter = View::new_fragment_from_iter((1..=3).map(|i| view! { ctx, p { (i) } }));
+        assert_eq!(from_iter.as_fragment().unwrap().len(), 3);
+        assert_eq!(
+            sycamore::render_to_string(|_| from_iter),
+            sycamore::render_to_string(|_| from_vec)
+        );
+    });
+}
+
+#[test]
+fn nested_static_fragments_are_flattened() {
+    create_scope_immediate(|ctx| {
+        let count = ctx.create_signal(0);
+        let inner = View::new_fragment(vec![view! { ctx, p { "a" } }, view! { ctx, p { "b" } }]);
+        let nested = View::new_fragment(vec![
+            View::new_fragment(vec![inner, view! { ctx, p { "c" } }]),
+            view! { ctx, p { "d" } },
+        ]);
+        let fragment = nested.as_fragment().unwrap();
+        assert_eq!(fragment.len(), 4);
+        assert!(fragment.iter().all(View::is_node));
+        assert_eq!(
+            sycamore::render_to_string(|_| nested),
+            "<p>a</p><p>b</p><p>c</p><p>d</p>"
+        );
+
+        // Fragments with dynamic views are not flattened.
+        let dynamic: View<SsrNode> = View::new_fragment(vec![
+            View::new_fragment(vec![view! { ctx, (count.get()) }, view! { ctx, p { "a" } }]),
+            view! { ctx, p { "b" } },
+        ]);
+        let fragment = dynamic.as_fragment().unwrap();
+        assert_eq!(fragment.len(), 2);
+        assert!(fragment[0].is_fragment());
+    });
+}
+
 #[test]
 fn indexed() {
     create_scope_immediate(|ctx| {
@@ -132,3 +174,515 @@ fn using_ctx_in_dyn_node_creates_nested_scope() {
         node
     });
 }
+
+#[test]
+fn dyn_patch_preserves_nodes() {
+    create_scope_immediate(|ctx| {
+        let label = ctx.create_signal("a");
+        let node = View::new_dyn_patch(ctx, move || {
+            let label = *label.get();
+            view! { ctx, p(class=label) { (label) } }
+        });
+        let initial = node.as_dyn().unwrap().get().as_node().cloned().unwrap();
+        assert_eq!(
+            sycamore::render_to_string(|_| node.clone()),
+            "<p class=\"a\">a</p>"
+        );
+
+        label.set("b");
+        assert_eq!(
+            sycamore::render_to_string(|_| node.clone()),
+            "<p class=\"b\">b</p>"
+        );
+        let current = node.as_dyn().unwrap().get().as_node().cloned().unwrap();
+        assert_eq!(current, initial, "node should be patched in place");
+    });
+}
+
+#[test]
+fn dyn_patch_replaces_structurally_different_nodes() {
+    create_scope_immediate(|ctx| {
+        let show_span = ctx.create_signal(false);
+        let node = View::new_dyn_patch(ctx, move || {
+            if *show_span.get() {
+                view! { ctx, span { "b" } }
+            } else {
+                view! { ctx, p { "a" } }
+            }
+        });
+        let initial = node.as_dyn().unwrap().get().as_node().cloned().unwrap();
+
+        show_span.set(true);
+        assert_eq!(
+            sycamore::render_to_string(|_| node.clone()),
+            "<span>b</span>"
+        );
+        let current = node.as_dyn().unwrap().get().as_node().cloned().unwrap();
+        assert_ne!(current, initial);
+    });
+}
+
+#[test]
+fn dyn_keyed_keeps_scope_while_key_is_stable() {
+    create_scope_immediate(|ctx| {
+        let count = ctx.create_signal(0);
+        let builds = ctx.create_ref(Cell::new(0));
+        let local = ctx.create_ref(RefCell::new(None::<RcSignal<i32>>));
+        let node = View::new_dyn_keyed(
+            ctx,
+            || *count.get() < 10,
+            move |ctx, &small| {
+                builds.set(builds.get() + 1);
+                let signal = create_rc_signal(0);
+                *local.borrow_mut() = Some(signal.clone());
+                let label = if small { "small" } else { "large" };
+                view! { ctx, p { (label) " " (count.get()) " " (signal.get()) } }
+            },
+        );
+        assert_eq!(
+            sycamore::render_to_string(|_| node.clone()),
+            "<p>small <!--#-->0<!--/--> <!--#-->0<!--/--></p>"
+        );
+
+        local.borrow().as_ref().unwrap().set(5);
+        count.set(1);
+        assert_eq!(
+            sycamore::render_to_string(|_| node.clone()),
+            "<p>small <!--#-->1<!--/--> <!--#-->5<!--/--></p>",
+            "the local signal should keep its value"
+        );
+        assert_eq!(builds.get(), 1);
+
+        count.set(10);
+        assert_eq!(
+            sycamore::render_to_string(|_| node.clone()),
+            "<p>large <!--#-->10<!--/--> <!--#-->0<!--/--></p>"
+        );
+        assert_eq!(
+            builds.get(),
+            2,
+            "the view should be rebuilt when the key changes"
+        );
+    });
+}
+
+#[test]
+fn lazy_view_builds_once_revealed() {
+    create_scope_immediate(|ctx| {
+        let visible = ctx.create_signal(false);
+        let builds = ctx.create_ref(Cell::new(0));
+        let node = View::lazy(ctx, visible, |ctx| {
+            builds.set(builds.get() + 1);
+            view! { ctx, p { "content" } }
+        });
+        assert_eq!(sycamore::render_to_string(|_| node.clone()), "<!---->");
+        assert_eq!(builds.get(), 0);
+
+        visible.set(true);
+        assert_eq!(
+            sycamore::render_to_string(|_| node.clone()),
+            "<p>content</p>"
+        );
+        assert_eq!(builds.get(), 1);
+
+        // Stays mounted and is not rebuilt.
+        visible.set(false);
+        visible.set(true);
+        assert_eq!(
+            sycamore::render_to_string(|_| node.clone()),
+            "<p>content</p>"
+        );
+        assert_eq!(builds.get(), 1);
+    });
+}
+
+#[test]
+fn lazy_view_dispose_on_hide() {
+    create_scope_immediate(|ctx| {
+        let visible = ctx.create_signal(false);
+        let builds = ctx.create_ref(Cell::new(0));
+        let disposed = ctx.create_ref(Cell::new(0));
+        let node = View::lazy_dispose_on_hide(ctx, visible, |ctx| {
+            builds.set(builds.get() + 1);
+            ctx.on_cleanup(|| disposed.set(disposed.get() + 1));
+            view! { ctx, p { "content" } }
+        });
+        assert_eq!(builds.get(), 0);
+
+        visible.set(true);
+        assert_eq!(builds.get(), 1);
+
+        visible.set(false);
+        assert_eq!(sycamore::render_to_string(|_| node.clone()), "<!---->");
+        assert_eq!(disposed.get(), 1);
+
+        visible.set(true);
+        assert_eq!(builds.get(), 2);
+    });
+}
+
+#[test]
+fn keyed_max_with_overflow() {
+    create_scope_immediate(|ctx| {
+        let count = ctx.create_signal(vec![1, 2, 3, 4, 5]);
+        let built = ctx.create_ref(Cell::new(0));
+        let node = view! { ctx,
+            ul {
+                Keyed {
+                    iterable: count,
+                    view: |ctx, item| {
+                        built.set(built.get() + 1);
+                        view! { ctx, li { (item) } }
+                    },
+                    key: |item| *item,
+                    max: 3,
+                    overflow: move |hidden| view! { ctx, li { "+" (hidden) " more" } },
+                }
+            }
+        };
+
+        let actual = sycamore::render_to_string(|_| node.clone());
+        assert_eq!(
+            actual,
+            "<ul><li>1</li><li>2</li><li>3</li><li>+2 more</li></ul>"
+        );
+        assert_eq!(built.get(), 3, "only the first `max` items should be built");
+
+        count.set(vec![1, 2, 3, 4, 5, 6]);
+        let actual = sycamore::render_to_string(|_| node.clone());
+        assert_eq!(
+            actual,
+            "<ul><li>1</li><li>2</li><li>3</li><li>+3 more</li></ul>"
+        );
+        assert_eq!(built.get(), 3, "hidden items should not be built");
+
+        count.set(vec![1, 2]);
+        let actual = sycamore::render_to_string(|_| node.clone());
+        assert_eq!(actual, "<ul><li>1</li><li>2</li><!----></ul>");
+    });
+}
+
+#[test]
+fn keyed_len() {
+    create_scope_immediate(|ctx| {
+        let items = ctx.create_signal(vec![1, 2]);
+        let len = ctx.create_signal(0);
+        let len_updates = ctx.create_ref(Cell::new(0));
+        ctx.create_effect(|| {
+            len.track();
+            len_updates.set(len_updates.get() + 1);
+        });
+        let node = view! { ctx,
+            ul {
+                Keyed {
+                    iterable: items,
+                    view: |ctx, item| view! { ctx, li { (item) } },
+                    key: |item| *item,
+                    len: len,
+                }
+            }
+        };
+        assert_eq!(*len.get(), 2);
+        assert_eq!(len_updates.get(), 2);
+
+        items.set(vec![3, 4]);
+        assert_eq!(
+            sycamore::render_to_string(|_| node.clone()),
+            "<ul><li>3</li><li>4</li></ul>"
+        );
+        assert_eq!(len_updates.get(), 2, "the length did not change");
+
+        items.set(vec![3, 4, 5]);
+        assert_eq!(*len.get(), 3);
+        assert_eq!(len_updates.get(), 3);
+    });
+}
+
+#[test]
+fn keyed_renames() {
+    create_scope_immediate(|ctx| {
+        let todos = ctx.create_signal(vec![(1, "a")]);
+        let renames = ctx.create_ref(KeyRenames::new());
+        let built = ctx.create_ref(Cell::new(0));
+        let node = view! { ctx,
+            ul {
+                Keyed {
+                    iterable: todos,
+                    view: |ctx, (_, text)| {
+                        built.set(built.get() + 1);
+                        view! { ctx, li { (text) } }
+                    },
+                    key: |(id, _)| *id,
+                    renames: renames,
+                }
+            }
+        };
+
+        // Optimistic insertion with a temporary key.
+        todos.set(vec![(1, "a"), (-1, "b")]);
+        assert_eq!(built.get(), 2);
+
+        renames.rename(-1, 2);
+        todos.set(vec![(1, "a"), (2, "b")]);
+        assert_eq!(
+            sycamore::render_to_string(|_| node.clone()),
+            "<ul><li>a</li><li>b</li></ul>"
+        );
+        assert_eq!(built.get(), 2, "the confirmed item should not be rebuilt");
+    });
+}
+
+#[test]
+fn keyed_reverse() {
+    create_scope_immediate(|ctx| {
+        let messages = ctx.create_signal(vec![3, 2, 1]);
+        let built = ctx.create_ref(Cell::new(0));
+        let node = view! { ctx,
+            ul {
+                Keyed {
+                    iterable: messages,
+                    view: |ctx, item| {
+                        built.set(built.get() + 1);
+                        view! { ctx, li { (item) } }
+                    },
+                    key: |item| *item,
+                    reverse: true,
+                }
+            }
+        };
+        assert_eq!(
+            sycamore::render_to_string(|_| node.clone()),
+            "<ul><li>1</li><li>2</li><li>3</li></ul>"
+        );
+
+        // Newest first.
+        messages.set(vec![4, 3, 2, 1]);
+        assert_eq!(
+            sycamore::render_to_string(|_| node.clone()),
+            "<ul><li>1</li><li>2</li><li>3</li><li>4</li></ul>"
+        );
+        assert_eq!(built.get(), 4, "existing items should not be rebuilt");
+
+        messages.set(vec![2, 4, 3]);
+        assert_eq!(
+            sycamore::render_to_string(|_| node.clone()),
+            "<ul><li>3</li><li>4</li><li>2</li></ul>"
+        );
+        assert_eq!(built.get(), 4);
+    });
+}
+
+#[test]
+fn keyed_grouped_move_between_groups() {
+    create_scope_immediate(|ctx| {
+        let people = ctx.create_signal(vec![("Alice", "red"), ("Bob", "red"), ("Carol", "blue")]);
+        let items_built = ctx.create_ref(Cell::new(0));
+        let headers_built = ctx.create_ref(Cell::new(0));
+        let node = view! { ctx,
+            ul {
+                KeyedGrouped {
+                    iterable: people,
+                    view: |ctx, (name, _)| {
+                        items_built.set(items_built.get() + 1);
+                        view! { ctx, li { (name) } }
+                    },
+                    key: |(name, _)| *name,
+                    group_by: |(_, team)| *team,
+                    header: |ctx, team| {
+                        headers_built.set(headers_built.get() + 1);
+                        view! { ctx, li { "Team " (team) } }
+                    },
+                }
+            }
+        };
+
+        let actual = sycamore::render_to_string(|_| node.clone());
+        assert_eq!(
+            actual,
+            "<ul><li>Team red</li><li>Alice</li><li>Bob</li><li>Team blue</li><li>Carol</li></ul>"
+        );
+        assert_eq!(items_built.get(), 3);
+        assert_eq!(headers_built.get(), 2);
+
+        // Move Bob to the other group.
+        people.set(vec![("Alice", "red"), ("Bob", "blue"), ("Carol", "blue")]);
+        let actual = sycamore::render_to_string(|_| node.clone());
+        assert_eq!(
+            actual,
+            "<ul><li>Team red</li><li>Alice</li><li>Team blue</li><li>Bob</li><li>Carol</li></ul>"
+        );
+        assert_eq!(items_built.get(), 3, "moved item should not be rebuilt");
+        assert_eq!(headers_built.get(), 2, "headers should not be rebuilt");
+
+        // Emptying a group removes its header.
+        people.set(vec![("Alice", "blue"), ("Bob", "blue"), ("Carol", "blue")]);
+        let actual = sycamore::render_to_string(|_| node.clone());
+        assert_eq!(
+            actual,
+            "<ul><li>Team blue</li><li>Alice</li><li>Bob</li><li>Carol</li></ul>"
+        );
+        assert_eq!(items_built.get(), 3);
+        assert_eq!(headers_built.get(), 2);
+    });
+}
+
+#[test]
+fn indexed_max_with_overflow() {
+    create_scope_immediate(|ctx| {
+        let count = ctx.create_signal(vec![1, 2, 3, 4]);
+        let built = ctx.create_ref(Cell::new(0));
+        let node = view! { ctx,
+            ul {
+                Indexed {
+                    iterable: count,
+                    view: |ctx, item| {
+                        built.set(built.get() + 1);
+                        view! { ctx, li { (item) } }
+                    },
+                    max: 2,
+                    overflow: move |hidden| view! { ctx, li { (hidden) } },
+                }
+            }
+        };
+
+        let actual = sycamore::render_to_string(|_| node.clone());
+        assert_eq!(actual, "<ul><li>1</li><li>2</li><li>2</li></ul>");
+        assert_eq!(built.get(), 2);
+
+        count.set(vec![1]);
+        let actual = sycamore::render_to_string(|_| node.clone());
+        assert_eq!(actual, "<ul><li>1</li><!----></ul>");
+    });
+}
+
+#[test]
+fn keyed_with_context_selection() {
+    create_scope_immediate(|ctx| {
+        let ids = ctx.create_signal(vec![1, 2, 3]);
+        let selected = ctx.create_signal(1);
+        let updated = ctx.create_ref(RefCell::new(Vec::new()));
+        let node = view! { ctx,
+            ul {
+                KeyedWithContext {
+                    iterable: ids,
+                    context: selected,
+                    view: move |ctx, id, selected| {
+                        let is_selected = ctx.create_selector(move || *selected.get() == id);
+                        ctx.create_effect(move || {
+                            is_selected.track();
+                            updated.borrow_mut().push(id);
+                        });
+                        view! { ctx,
+                            li(class=if *is_selected.get() { "selected" } else { "" }) { (id) }
+                        }
+                    },
+                    key: |id| *id,
+                }
+            }
+        };
+
+        let actual = sycamore::render_to_string(|_| node.clone());
+        assert_eq!(
+            actual,
+            "<ul><li class=\"selected\">1</li><li class=\"\">2</li><li class=\"\">3</li></ul>"
+        );
+        assert_eq!(updated.take(), [1, 2, 3]);
+
+        selected.set(2);
+        let actual = sycamore::render_to_string(|_| node.clone());
+        assert_eq!(
+            actual,
+            "<ul><li class=\"\">1</li><li class=\"selected\">2</li><li class=\"\">3</li></ul>"
+        );
+        let mut updated = updated.take();
+        updated.sort_unstable();
+        assert_eq!(
+            updated,
+            [1, 2],
+            "only the previously and newly selected items are updated"
+        );
+    });
+}
+
+#[test]
+fn indexed_with_context() {
+    create_scope_immediate(|ctx| {
+        let count = ctx.create_signal(vec![1, 2]);
+        let node = view! { ctx,
+            ul {
+                IndexedWithContext {
+                    iterable: count,
+                    context: 10,
+                    view: |ctx, item, offset| {
+                        let item = item + offset;
+                        view! { ctx, li { (item) } }
+                    },
+                }
+            }
+        };
+
+        let actual = sycamore::render_to_string(|_| node.clone());
+        assert_eq!(actual, "<ul><li>11</li><li>12</li></ul>");
+    });
+}
+
+#[test]
+fn indexed_reverse() {
+    create_scope_immediate(|ctx| {
+        let count = ctx.create_signal(vec![1, 2]);
+        let node = view! { ctx,
+            ul {
+                Indexed {
+                    iterable: count,
+                    view: |ctx, item| view! { ctx, li { (item) } },
+                    reverse: true,
+                }
+            }
+        };
+        assert_eq!(
+            sycamore::render_to_string(|_| node.clone()),
+            "<ul><li>2</li><li>1</li></ul>"
+        );
+
+        count.set(vec![1, 2, 3]);
+        assert_eq!(
+            sycamore::render_to_string(|_| node.clone()),
+            "<ul><li>3</li><li>2</li><li>1</li></ul>"
+        );
+    });
+}
+
+#[derive(Prop)]
+struct CardProps<'a, G: GenericNode> {
+    slots: Slots<'a, G>,
+}
+
+#[component]
+fn Card<'a, G: Html>(ctx: ScopeRef<'a>, mut props: CardProps<'a, G>) -> View<G> {
+    let header = props.slots.render(ctx, "header");
+    let footer = props.slots.render(ctx, "footer");
+    let body = props.slots.render(ctx, "body");
+    view! { ctx,
+        header { (header) }
+        div { (body) }
+        footer { (footer) }
+    }
+}
+
+#[test]
+fn component_slots() {
+    let actual = sycamore::render_to_string(|ctx| {
+        view! { ctx,
+            Card {
+                slots: Slots::new()
+                    .add("footer", |ctx| view! { ctx, "Bottom" })
+                    .add("header", |ctx| view! { ctx, "Top" }),
+            }
+        }
+    });
+    assert_eq!(
+        actual,
+        "<header data-hk=\"1.0\">Top</header><div data-hk=\"1.1\"><!----></div><footer \
+         data-hk=\"1.2\">Bottom</footer>"
+    );
+}
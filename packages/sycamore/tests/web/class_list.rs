@@ -0,0 +1,35 @@
+use super::*;
+
+#[wasm_bindgen_test]
+fn class_list_toggles_classes() {
+    create_scope_immediate(|ctx| {
+        let active = ctx.create_signal(true);
+        let disabled = ctx.create_signal(false);
+        let button = ctx.create_node_ref();
+
+        let node = view! { ctx,
+            button(ref=button, class="btn")
+        };
+        ctx.class_list(button, [("active", &**active), ("disabled", &**disabled)]);
+
+        sycamore::render_to(|_| node, &test_container());
+
+        let class_list = document()
+            .query_selector("button")
+            .unwrap()
+            .unwrap()
+            .class_list();
+        assert_eq!(class_list.value(), "btn active");
+
+        disabled.set(true);
+        assert_eq!(class_list.value(), "btn active disabled");
+
+        active.set(false);
+        assert_eq!(class_list.value(), "btn disabled");
+        assert!(class_list.contains("btn"));
+
+        active.set(true);
+        disabled.set(false);
+        assert_eq!(class_list.value(), "btn active");
+    });
+}
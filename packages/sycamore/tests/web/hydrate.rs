@@ -288,3 +288,76 @@ mod top_level_dynamic_with_siblings {
         });
     }
 }
+
+mod mismatched_element {
+    use super::*;
+    fn server<G: Html>(ctx: ScopeRef) -> View<G> {
+        view! { ctx, div { span { "Old" } p { "Kept" } } }
+    }
+    fn client<G: Html>(ctx: ScopeRef) -> View<G> {
+        view! { ctx, div { em { "New" } p { "Kept" } } }
+    }
+    #[test]
+    fn ssr() {
+        check(
+            &sycamore::render_to_string(server),
+            expect![[
+                r#"<div data-hk="0.0"><span data-hk="0.1">Old</span><p data-hk="0.2">Kept</p></div>"#
+            ]],
+        );
+    }
+    #[wasm_bindgen_test]
+    fn test() {
+        let html = sycamore::render_to_string(server);
+        let c = test_container();
+        c.set_inner_html(&html);
+        let p = c.query_selector("p").unwrap().unwrap();
+
+        sycamore::hydrate_to(client, &c);
+
+        // Only the mismatched element is rendered on the client.
+        assert_eq!(
+            c.query_selector("div").unwrap().unwrap().inner_html(),
+            r#"<em>New</em><p data-hk="0.2">Kept</p>"#
+        );
+        // The rest of the view is hydrated.
+        assert_eq!(c.query_selector("p").unwrap(), Some(p));
+    }
+}
+
+mod nested_mismatched_element {
+    use super::*;
+    fn server<G: Html>(ctx: ScopeRef) -> View<G> {
+        view! { ctx, div { span { b { "Old" } } p { "Kept" } } }
+    }
+    fn client<G: Html>(ctx: ScopeRef) -> View<G> {
+        view! { ctx, div { em { i { "New" } u { "New" } } p { "Kept" } } }
+    }
+    #[test]
+    fn ssr() {
+        check(
+            &sycamore::render_to_string(server),
+            expect![[
+                r#"<div data-hk="0.0"><span data-hk="0.1"><b data-hk="0.2">Old</b></span><p data-hk="0.3">Kept</p></div>"#
+            ]],
+        );
+    }
+    #[wasm_bindgen_test]
+    fn test() {
+        let html = sycamore::render_to_string(server);
+        let c = test_container();
+        c.set_inner_html(&html);
+        let p = c.query_selector("p").unwrap().unwrap();
+
+        sycamore::hydrate_to(client, &c);
+
+        // The children of the mismatched element are rendered on the client without taking the
+        // hydration keys of the elements after it.
+        assert_eq!(
+            c.query_selector("div").unwrap().unwrap().inner_html(),
+            r#"<em><i>New</i><u>New</u></em><p data-hk="0.3">Kept</p>"#
+        );
+        // The sibling after the mismatched element is still hydrated.
+        assert_eq!(c.query_selector("p").unwrap(), Some(p));
+    }
+}
@@ -73,6 +73,61 @@ fn swap_rows() {
     });
 }
 
+#[wasm_bindgen_test]
+fn key_hint_moves_views() {
+    create_scope_immediate(|ctx| {
+        let count = ctx.create_signal(vec![1, 2, 3]);
+        let keyed = ctx.create_signal(vec![1, 2, 3]);
+
+        let node = view! { ctx,
+            ul(id="indexed") {
+                Indexed {
+                    iterable: count,
+                    view: |ctx, item| view! { ctx,
+                        li { (item) }
+                    },
+                }
+            }
+            ul(id="keyed") {
+                Indexed {
+                    iterable: keyed,
+                    view: |ctx, item| view! { ctx,
+                        li { (item) }
+                    },
+                    key_hint: |item| IndexedKey::new(*item),
+                }
+            }
+        };
+
+        sycamore::render_to(|_| node, &test_container());
+
+        let first_child = |id| {
+            document()
+                .query_selector(&format!("#{id} li"))
+                .unwrap()
+                .unwrap()
+        };
+        let last_child = |id| {
+            document()
+                .query_selector(&format!("#{id} li:last-child"))
+                .unwrap()
+                .unwrap()
+        };
+        let indexed_first = first_child("indexed");
+        let keyed_first = first_child("keyed");
+
+        count.set(vec![3, 2, 1]);
+        keyed.set(vec![3, 2, 1]);
+        let p = document().query_selector("#keyed").unwrap().unwrap();
+        assert_eq!(p.text_content().unwrap(), "321");
+
+        // With a key hint, the view of the first element is moved to the end.
+        assert_eq!(last_child("keyed"), keyed_first);
+        // Without a key hint, the view at the end is rebuilt.
+        assert_ne!(last_child("indexed"), indexed_first);
+    });
+}
+
 #[wasm_bindgen_test]
 fn update_row() {
     create_scope_immediate(|ctx| {
@@ -1,5 +1,8 @@
 use std::iter::once;
 
+use js_sys::{Function, Reflect};
+use wasm_bindgen::{JsCast, JsValue};
+
 use super::*;
 
 #[wasm_bindgen_test]
@@ -37,6 +40,43 @@ fn append() {
     });
 }
 
+#[wasm_bindgen_test]
+fn reverse_append() {
+    create_scope_immediate(|ctx| {
+        let count = ctx.create_signal(vec![2, 1]);
+
+        let node = view! { ctx,
+            ul {
+                Keyed {
+                    iterable: count,
+                    view: |ctx, item| view! { ctx,
+                        li { (item) }
+                    },
+                    key: |item| *item,
+                    reverse: true,
+                }
+            }
+        };
+
+        sycamore::render_to(|_| node, &test_container());
+
+        let p = document().query_selector("ul").unwrap().unwrap();
+        assert_eq!(p.text_content().unwrap(), "12");
+        let first = p.first_element_child().unwrap();
+
+        // Prepending to the source appends to the rendered list.
+        count.set(vec![3, 2, 1]);
+        assert_eq!(p.text_content().unwrap(), "123");
+        assert!(
+            first.is_same_node(p.first_element_child().as_deref()),
+            "existing nodes should be kept"
+        );
+
+        count.set(vec![3, 2, 1, 0]);
+        assert_eq!(p.text_content().unwrap(), "0123");
+    });
+}
+
 #[wasm_bindgen_test]
 fn swap_rows() {
     create_scope_immediate(|ctx| {
@@ -480,3 +520,77 @@ fn template_with_other_nodes_at_same_level() {
         assert_eq!(elem.text_content().unwrap(), "before145after");
     });
 }
+
+/// Replaces `window.IntersectionObserver` with a mock that exposes the callback of the last created
+/// observer as `window.__intersect`. Returns the original constructor.
+fn mock_intersection_observer() -> JsValue {
+    let original = Reflect::get(&window(), &"IntersectionObserver".into()).unwrap();
+    let mock = Function::new_with_args(
+        "callback",
+        "window.__intersect = (isIntersecting) => callback([{ isIntersecting }], this);
+         this.observe = () => {};
+         this.unobserve = () => {};
+         this.disconnect = () => { window.__intersect = undefined; };",
+    );
+    Reflect::set(&window(), &"IntersectionObserver".into(), &mock).unwrap();
+    original
+}
+
+/// Calls the callback of the mocked observer, or returns `false` if it was disconnected.
+fn intersect(is_intersecting: bool) -> bool {
+    let callback = Reflect::get(&window(), &"__intersect".into()).unwrap();
+    if callback.is_undefined() {
+        return false;
+    }
+    callback
+        .unchecked_into::<Function>()
+        .call1(&JsValue::NULL, &is_intersecting.into())
+        .unwrap();
+    true
+}
+
+#[wasm_bindgen_test]
+fn on_reach_end() {
+    let original = mock_intersection_observer();
+    create_scope_immediate(|ctx| {
+        let count = ctx.create_signal(vec![1, 2]);
+        let reached = ctx.create_signal(0);
+
+        let node = view! { ctx,
+            ul {
+                Keyed {
+                    iterable: count,
+                    view: |ctx, item| view! { ctx,
+                        li { (item) }
+                    },
+                    key: |item| *item,
+                    on_reach_end: move || reached.set(*reached.get_untracked() + 1),
+                }
+            }
+        };
+
+        sycamore::render_to(|_| node, &test_container());
+
+        let p = document().query_selector("ul").unwrap().unwrap();
+        assert_eq!(p.text_content().unwrap(), "12");
+        assert!(p.last_element_child().unwrap().has_attribute("aria-hidden"));
+
+        assert!(intersect(false));
+        assert_eq!(*reached.get(), 0);
+
+        assert!(intersect(true));
+        assert_eq!(*reached.get(), 1);
+
+        // The sentinel stays after the last item when the list grows.
+        count.set(vec![1, 2, 3]);
+        assert_eq!(p.text_content().unwrap(), "123");
+        assert!(p.last_element_child().unwrap().has_attribute("aria-hidden"));
+        assert!(intersect(true));
+        assert_eq!(*reached.get(), 2);
+    });
+    assert!(
+        !intersect(true),
+        "observer should be disconnected on dispose"
+    );
+    Reflect::set(&window(), &"IntersectionObserver".into(), &original).unwrap();
+}
@@ -1,5 +1,6 @@
 #[cfg(all(feature = "experimental-hydrate", feature = "builder"))]
 pub mod builder_hydrate;
+pub mod class_list;
 pub mod cleanup;
 #[cfg(feature = "experimental-hydrate")]
 pub mod hydrate;
@@ -8,6 +9,8 @@ pub mod keyed;
 pub mod portal;
 pub mod reconcile;
 pub mod render;
+pub mod reorder;
+pub mod style;
 
 use sycamore::prelude::*;
 use wasm_bindgen_test::*;
@@ -379,6 +382,22 @@ fn noderefs() {
     });
 }
 
+#[wasm_bindgen_test]
+fn noderef_is_empty_before_render() {
+    create_scope_immediate(|ctx| {
+        let noderef = ctx.create_node_ref();
+        assert!(noderef.try_get::<DomNode>().is_none());
+
+        sycamore::render_to(|ctx| view! { ctx, input(ref=noderef) }, &test_container());
+        let input_ref = document().query_selector("input").unwrap().unwrap();
+
+        assert_eq!(
+            Node::from(input_ref),
+            noderef.try_get::<DomNode>().unwrap().unchecked_into()
+        );
+    });
+}
+
 #[wasm_bindgen_test]
 fn fragments() {
     create_scope_immediate(|ctx| {
@@ -1,3 +1,5 @@
+use wasm_bindgen::JsCast;
+
 use super::*;
 
 #[wasm_bindgen_test]
@@ -71,3 +73,76 @@ fn lazy_in_fragment() {
         assert_eq!(test_container.text_content().unwrap(), "before1after");
     });
 }
+
+#[wasm_bindgen_test]
+fn dyn_patch_preserves_parent_node() {
+    create_scope_immediate(|ctx| {
+        let text = ctx.create_signal("before");
+        let node: View<DomNode> = View::new_dyn_patch(ctx, move || {
+            let text = *text.get();
+            view! { ctx,
+                p { (text) }
+            }
+        });
+
+        sycamore::render_to(|_| node, &test_container());
+        let p = document().query_selector("p").unwrap().unwrap();
+        assert_eq!(p.text_content().unwrap(), "before");
+
+        text.set("after");
+        let patched = document().query_selector("p").unwrap().unwrap();
+        assert_eq!(patched.text_content().unwrap(), "after");
+        assert!(
+            patched.is_same_node(Some(&p)),
+            "<p> should be patched in place"
+        );
+    });
+}
+
+#[wasm_bindgen_test]
+fn replace_with() {
+    create_scope_immediate(|ctx| {
+        let text = ctx.create_signal("a");
+        let show = ctx.create_signal(true);
+        let clicked = ctx.create_signal(false);
+        let node: View<DomNode> = view! { ctx,
+            p(on:click=|_| clicked.set(true)) { (text.get()) }
+        };
+
+        let parent = View::new_dyn(ctx, {
+            let node = node.clone();
+            move || {
+                if *show.get() {
+                    node.clone()
+                } else {
+                    View::empty()
+                }
+            }
+        });
+
+        sycamore::render_to(|_| parent, &test_container());
+        let old = node.as_node().unwrap().inner_element();
+
+        let widget = document().create_element("span").unwrap();
+        node.replace_with(DomNode::from_web_sys(widget.clone().into()));
+        let test_container = document()
+            .query_selector("test-container")
+            .unwrap()
+            .unwrap();
+        assert_eq!(test_container.inner_html(), "<span>a</span>");
+
+        text.set("b");
+        assert_eq!(widget.text_content().unwrap(), "b");
+
+        // Re-rendering the parent dynamic view uses the new node.
+        show.set(false);
+        assert!(widget.parent_node().is_none());
+        show.set(true);
+        assert!(widget.parent_node().is_some());
+        assert_eq!(test_container.text_content().unwrap(), "b");
+
+        // The event listeners of the old node are disabled.
+        old.unchecked_ref::<HtmlElement>().click();
+        assert!(!*clicked.get());
+    });
+}
@@ -0,0 +1,82 @@
+use web_sys::{PointerEvent, PointerEventInit};
+
+use super::*;
+
+fn dispatch_pointer_event(target: &Element, name: &str) {
+    let init = PointerEventInit::new();
+    init.set_bubbles(true);
+    let event = PointerEvent::new_with_event_init_dict(name, &init).unwrap();
+    target.dispatch_event(&event).unwrap();
+}
+
+#[wasm_bindgen_test]
+fn drag_reorder() {
+    create_scope_immediate(|ctx| {
+        let items = ctx.create_signal(vec![1, 2, 3]);
+        let list = ctx.create_node_ref();
+
+        let node = view! { ctx,
+            ul(ref=list) {
+                Keyed {
+                    iterable: items,
+                    view: |ctx, item| view! { ctx,
+                        li { (item) }
+                    },
+                    key: |item| *item,
+                }
+            }
+        };
+        ctx.enable_drag_reorder(list, items);
+
+        sycamore::render_to(|_| node, &test_container());
+
+        let ul = document().query_selector("ul").unwrap().unwrap();
+        let first = ul.children().item(0).unwrap();
+        let third = ul.children().item(2).unwrap();
+        assert_eq!(ul.text_content().unwrap(), "123");
+
+        dispatch_pointer_event(&first, "pointerdown");
+        dispatch_pointer_event(&third, "pointerup");
+        assert_eq!(*items.get(), [2, 3, 1]);
+        assert_eq!(ul.text_content().unwrap(), "231");
+        // The existing node was moved instead of being re-created.
+        assert_eq!(ul.children().item(2), Some(first));
+    });
+}
+
+#[wasm_bindgen_test]
+fn drag_reorder_removed_on_dispose() {
+    create_scope_immediate(|ctx| {
+        let items = ctx.create_signal(vec![1, 2, 3]);
+        let list = ctx.create_node_ref();
+
+        let node = view! { ctx,
+            ul(ref=list) {
+                Keyed {
+                    iterable: items,
+                    view: |ctx, item| view! { ctx,
+                        li { (item) }
+                    },
+                    key: |item| *item,
+                }
+            }
+        };
+        let ((), disposer) =
+            ctx.create_child_scope_with_safe(|ctx| ctx.enable_drag_reorder(list, items));
+
+        sycamore::render_to(|_| node, &test_container());
+
+        disposer.dispose();
+
+        let ul = document().query_selector("ul").unwrap().unwrap();
+        let first = ul.children().item(0).unwrap();
+        let third = ul.children().item(2).unwrap();
+
+        // The listeners are detached, so dispatching events neither invokes a dropped closure
+        // nor reorders the items.
+        dispatch_pointer_event(&first, "pointerdown");
+        dispatch_pointer_event(&third, "pointerup");
+        assert_eq!(*items.get(), [1, 2, 3]);
+        assert_eq!(ul.text_content().unwrap(), "123");
+    });
+}
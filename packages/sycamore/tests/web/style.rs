@@ -0,0 +1,37 @@
+use super::*;
+
+#[wasm_bindgen_test]
+fn bind_style_updates_and_removes_property() {
+    create_scope_immediate(|ctx| {
+        let width = ctx.create_signal(String::from("10px"));
+        let color = ctx.create_signal(String::new());
+        let div = ctx.create_node_ref();
+
+        let node = view! { ctx,
+            div(ref=div, style="height: 5px;")
+        };
+        ctx.bind_style(div, "width", width);
+        ctx.bind_styles(div, [("color", &**color)]);
+
+        sycamore::render_to(|_| node, &test_container());
+
+        let element = document().query_selector("div").unwrap().unwrap();
+        assert_eq!(
+            element.get_attribute("style").unwrap(),
+            "height: 5px; width: 10px;"
+        );
+
+        width.set(String::from("20px"));
+        color.set(String::from("red"));
+        assert_eq!(
+            element.get_attribute("style").unwrap(),
+            "height: 5px; width: 20px; color: red;"
+        );
+
+        width.set(String::new());
+        assert_eq!(
+            element.get_attribute("style").unwrap(),
+            "height: 5px; color: red;"
+        );
+    });
+}